@@ -1,11 +1,15 @@
 mod client;
 mod contest;
+mod history;
 mod problem;
+mod standings;
 mod submission;
 mod types;
 
 pub use client::AtCoderClient;
 pub use types::{
-    AtCoderContest, AtCoderProblem, AtCoderSubmission, AtCoderSubmissionListResponse,
-    ContestTypeSpecifier,
+    AtCoderContest, AtCoderProblem, AtCoderStandingsRow, AtCoderStandingsTaskResult,
+    AtCoderSubmission, AtCoderSubmissionDetail, AtCoderSubmissionListResponse,
+    AtCoderTestcaseResult, AtCoderUserHistory, ContestCategory, ContestTypeSpecifier, JudgeStatus,
+    RatedRange, SubmissionListFilter, Verdict,
 };
@@ -1,48 +1,158 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use crate::error::{AtCoderError, LoginError, Result};
+use crate::rate_limiter::{RateLimitConfig, RateLimiter};
+use crate::transport::{HttpRequest, HttpTransport, ReqwestTransport, SessionCookies};
 use crate::util;
-use anyhow::{Context, Result};
+use anyhow::Context;
+use reqwest::StatusCode;
 
 use super::*;
 
 const ATCODER_PREFIX: &str = "https://atcoder.jp";
 
+/// Clones share the same session and the same rate limiter.
 #[derive(Clone)]
 pub struct AtCoderClient {
-    client: Arc<reqwest::Client>,
+    transport: Arc<dyn HttpTransport>,
+    limiter: Arc<RateLimiter>,
+    /// Whether the session is known to be logged in, so that it is checked only once
+    /// until a request is refused.
+    verified: Arc<AtomicBool>,
 }
 
 impl AtCoderClient {
     pub async fn new(username: &str, password: &str) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .cookie_store(true)
-            .gzip(true)
-            .build()?;
-        let response = client
-            .get("https://atcoder.jp/login")
-            .send()
-            .await?
-            .text()
-            .await?;
-        let csrf_token = extract_csrf_token(&response).context("no csrf token")?;
+        Self::with_rate_limit(username, password, RateLimitConfig::default()).await
+    }
+
+    pub async fn with_rate_limit(
+        username: &str,
+        password: &str,
+        config: RateLimitConfig,
+    ) -> Result<Self> {
+        let client = Self::without_login(config)?;
+        client.login(username, password).await?;
+        Ok(client)
+    }
+
+    /// Creates a client which isn't logged in yet, e.g. to restore a saved session.
+    pub fn without_login(config: RateLimitConfig) -> Result<Self> {
+        Ok(Self::with_transport(ReqwestTransport::new()?, config))
+    }
+
+    /// Creates a client which sends requests through `transport` without logging in.
+    pub fn with_transport<T: HttpTransport + 'static>(
+        transport: T,
+        config: RateLimitConfig,
+    ) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            limiter: Arc::new(RateLimiter::new(config)),
+            verified: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Creates a client which has a session of its own but shares the rate limiter,
+    /// so that clients logged in as different users keep the same request budget in total.
+    pub fn with_new_session(&self) -> Result<Self> {
+        Ok(Self {
+            transport: Arc::new(ReqwestTransport::new()?),
+            limiter: self.limiter.clone(),
+            verified: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> Result<()> {
+        let url = format!("{}/login", ATCODER_PREFIX);
+        let response = self.get_html(&url).await?;
+        let csrf_token = extract_csrf_token(&response).ok_or(LoginError::CsrfTokenNotFound)?;
         let params = [
             ("username", username),
             ("password", password),
             ("csrf_token", &csrf_token),
         ];
 
-        let response = client
-            .post("https://atcoder.jp/login")
-            .form(&params)
-            .send()
-            .await?;
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("AtCoder authentication failure"));
+        let request = HttpRequest::post_form(&url, &params);
+        let response = util::send_ok(&request, self.transport.as_ref(), &self.limiter).await?;
+        match extract_user_screen_name(&response.body) {
+            Some(user) if !user.is_empty() => {
+                self.verified.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            _ => Err(LoginError::InvalidCredentials.into()),
         }
+    }
 
-        Ok(Self {
-            client: Arc::new(client),
-        })
+    /// Checks whether the current session is still logged in.
+    pub async fn is_logged_in(&self) -> Result<bool> {
+        let url = format!("{}/home", ATCODER_PREFIX);
+        let html = self.get_html(&url).await?;
+        Ok(extract_user_screen_name(&html).map_or(false, |user| !user.is_empty()))
+    }
+
+    /// Logs in again only if the session has expired.
+    /// The session is checked only for the first time and after AtCoder has refused a request,
+    /// so that it doesn't cost a request every time.
+    /// Returns `true` if it has logged in again.
+    pub async fn ensure_logged_in(&self, username: &str, password: &str) -> Result<bool> {
+        if self.verified.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        if self.is_logged_in().await? {
+            self.verified.store(true, Ordering::Relaxed);
+            return Ok(false);
+        }
+        log::info!("Session has expired, logging in as {} ...", username);
+        if let Ok(cookies) = self.cookies() {
+            cookies.clear();
+        }
+        self.login(username, password).await?;
+        Ok(true)
+    }
+
+    /// Serializes the cookies of the current session.
+    pub fn export_session(&self) -> Result<String> {
+        Ok(self.cookies()?.export()?)
+    }
+
+    pub fn restore_session(&self, session: &str) -> Result<()> {
+        self.verified.store(false, Ordering::Relaxed);
+        Ok(self.cookies()?.restore(session)?)
+    }
+
+    pub async fn save_session_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let session = self.export_session()?;
+        tokio::fs::write(path.as_ref(), session)
+            .await
+            .with_context(|| format!("Failed to write the session to {:?}", path.as_ref()))?;
+        Ok(())
+    }
+
+    pub async fn load_session_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let session = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .with_context(|| format!("Failed to read the session from {:?}", path.as_ref()))?;
+        self.restore_session(&session)
+    }
+
+    fn cookies(&self) -> Result<Arc<SessionCookies>> {
+        let cookies = self
+            .transport
+            .cookies()
+            .context("The transport doesn't keep a session.")?;
+        Ok(cookies)
+    }
+
+    async fn get_html(&self, url: &str) -> Result<String> {
+        let result = util::get_html(url, self.transport.as_ref(), &self.limiter).await;
+        if let Err(AtCoderError::Http(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)) = result {
+            // The session may have expired, so check it again at the next `ensure_logged_in`.
+            self.verified.store(false, Ordering::Relaxed);
+        }
+        result
     }
 
     pub async fn fetch_atcoder_contests(
@@ -52,70 +162,123 @@ impl AtCoderClient {
         match spf {
             ContestTypeSpecifier::Normal { page } => self.fetch_atcoder_normal_contests(page).await,
             ContestTypeSpecifier::Permanent => self.fetch_atcoder_permanent_contests().await,
-            ContestTypeSpecifier::Hidden => self.fetch_atcoder_hidden_contests().await,
+            ContestTypeSpecifier::Upcoming => self.fetch_atcoder_upcoming_contests().await,
+            ContestTypeSpecifier::Top => self.fetch_atcoder_top_contests().await,
         }
     }
 
     async fn fetch_atcoder_normal_contests(&self, page: u32) -> Result<Vec<AtCoderContest>> {
         let url = format!("{}/contests/archive?lang=ja&page={}", ATCODER_PREFIX, page);
-        let (html, _) = util::get_html(&url, &self.client).await?;
+        let html = self.get_html(&url).await?;
         contest::scrape_normal(&html)
     }
 
     async fn fetch_atcoder_permanent_contests(&self) -> Result<Vec<AtCoderContest>> {
         let url = format!("{}/contests/?lang=ja", ATCODER_PREFIX);
-        let (html, _) = util::get_html(&url, &self.client).await?;
+        let html = self.get_html(&url).await?;
         contest::scrape_permanent(&html)
     }
 
-    async fn fetch_atcoder_hidden_contests(&self) -> Result<Vec<AtCoderContest>> {
-        let uri = "https://kenkoooo.com/atcoder/static_data/backend/hidden_contests.json";
-        util::get_json(uri, &self.client).await
+    async fn fetch_atcoder_upcoming_contests(&self) -> Result<Vec<AtCoderContest>> {
+        let url = format!("{}/contests/?lang=ja", ATCODER_PREFIX);
+        let html = self.get_html(&url).await?;
+        contest::scrape_upcoming(&html)
+    }
+
+    /// Fetches the top page once for both of the tables.
+    async fn fetch_atcoder_top_contests(&self) -> Result<Vec<AtCoderContest>> {
+        let url = format!("{}/contests/?lang=ja", ATCODER_PREFIX);
+        let html = self.get_html(&url).await?;
+        let mut contests = contest::scrape_permanent(&html)?;
+        contests.extend(contest::scrape_upcoming(&html)?);
+        Ok(contests)
     }
 
-    /// Fetch a list of submissions.
+    /// Fetch a contest from its top page, which is available even if the contest isn't listed.
+    pub async fn fetch_contest_detail(&self, contest_id: &str) -> Result<AtCoderContest> {
+        let url = format!("{}/contests/{}?lang=ja", ATCODER_PREFIX, contest_id);
+        let html = self.get_html(&url).await?;
+        contest::scrape_detail(&html, contest_id)
+    }
+
+    /// Fetch a list of submissions narrowed down by `filter`.
     pub async fn fetch_atcoder_submission_list(
         &self,
         contest_id: &str,
         page: Option<u32>,
+        filter: &SubmissionListFilter,
     ) -> Result<AtCoderSubmissionListResponse> {
-        let page = page.unwrap_or(1);
+        let page = page.unwrap_or(1).to_string();
+        let url = format!("{}/contests/{}/submissions", ATCODER_PREFIX, contest_id);
+        let mut params = filter.query_pairs();
+        params.push(("page", &page));
+        let url = reqwest::Url::parse_with_params(&url, &params).context("Invalid URL")?;
+        let html = self.get_html(url.as_str()).await?;
+        let submissions = submission::scrape(&html, contest_id)?;
+        let max_page = submission::scrape_submission_page_count(&html)?;
+        Ok(AtCoderSubmissionListResponse {
+            max_page,
+            submissions,
+        })
+    }
+
+    /// Fetch the per-testcase judge result of a submission.
+    pub async fn fetch_submission_detail(
+        &self,
+        contest_id: &str,
+        submission_id: u64,
+    ) -> Result<AtCoderSubmissionDetail> {
         let url = format!(
-            "{}/contests/{}/submissions?page={}",
-            ATCODER_PREFIX, contest_id, page
+            "{}/contests/{}/submissions/{}?lang=en",
+            ATCODER_PREFIX, contest_id, submission_id
         );
-        let (html, status) = util::get_html(&url, &self.client).await?;
-
-        if status.is_success() {
-            let submissions = submission::scrape(&html, contest_id)?;
-            let max_page = submission::scrape_submission_page_count(&html)?;
-            Ok(AtCoderSubmissionListResponse {
-                max_page,
-                submissions,
-            })
-        } else if status == reqwest::StatusCode::NOT_FOUND {
-            log::warn!("404: {}", url);
-            Ok(AtCoderSubmissionListResponse {
-                max_page: 0,
-                submissions: Vec::new(),
-            })
-        } else {
-            Err(anyhow::anyhow!(
-                "Failed to fetch {}: status={} body={}",
-                url,
-                status,
-                html
-            ))
-        }
+        let html = self.get_html(&url).await?;
+        submission::scrape_detail(&html, contest_id, submission_id)
+    }
+
+    /// Fetch the final standings of a contest.
+    pub async fn fetch_contest_standings(
+        &self,
+        contest_id: &str,
+    ) -> Result<Vec<AtCoderStandingsRow>> {
+        let url = format!("{}/contests/{}/standings/json", ATCODER_PREFIX, contest_id);
+        let json = self.get_html(&url).await?;
+        standings::parse(&json)
+    }
+
+    /// Fetch the contest history of a user.
+    pub async fn fetch_user_history(&self, user_id: &str) -> Result<Vec<AtCoderUserHistory>> {
+        let url = format!("{}/users/{}/history/json", ATCODER_PREFIX, user_id);
+        let json = self.get_html(&url).await?;
+        history::parse(&json)
+    }
+
+    /// Fetch a JSON array of the contests which don't appear in the contest list.
+    /// The list is hosted outside of AtCoder, but is fetched in the same way
+    /// so that it is retried and rate limited as well.
+    pub async fn fetch_hidden_contest_list(&self, url: &str) -> Result<Vec<serde_json::Value>> {
+        let json = self.get_html(url).await?;
+        crate::error::parse_json(&json, "hidden contests")
     }
 
     pub async fn fetch_problem_list(&self, contest_id: &str) -> Result<Vec<AtCoderProblem>> {
         let url = format!("{}/contests/{}/tasks", ATCODER_PREFIX, contest_id);
-        let (html, _) = util::get_html(&url, &self.client).await?;
+        let html = self.get_html(&url).await?;
         problem::scrape(&html, contest_id)
     }
 }
 
+fn extract_user_screen_name(response: &str) -> Option<String> {
+    response.lines().find_map(|line| {
+        let name = line.trim().strip_prefix("var userScreenName = ")?;
+        Some(
+            name.trim()
+                .trim_matches(|c| c == '"' || c == ';')
+                .to_string(),
+        )
+    })
+}
+
 fn extract_csrf_token(response: &str) -> Option<String> {
     for line in response.split("\n") {
         let line = line.trim();
@@ -130,12 +293,104 @@ fn extract_csrf_token(response: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transport::{HttpResponse, ReplayTransport};
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicUsize;
+
+    fn replay_client() -> AtCoderClient {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/test_resources/replay");
+        AtCoderClient::with_transport(ReplayTransport::new(dir), RateLimitConfig::default())
+    }
+
+    #[test]
+    fn test_extract_user_screen_name() {
+        let html = "<script>\n  var csrfToken = \"token\"\n  var userScreenName = \"kenkoooo\";\n</script>";
+        assert_eq!(extract_csrf_token(html).unwrap(), "token");
+        assert_eq!(extract_user_screen_name(html).unwrap(), "kenkoooo");
+        assert_eq!(
+            extract_user_screen_name("var userScreenName = \"\";").unwrap(),
+            ""
+        );
+        assert_eq!(extract_user_screen_name("<html></html>"), None);
+    }
 
     #[tokio::test]
-    async fn test_fetch_contest_list() {
+    async fn test_login_with_invalid_credentials() {
+        let client = replay_client();
+        assert!(!client.is_logged_in().await.unwrap());
+        let result = client.login("user", "wrong-password").await;
+        assert!(matches!(
+            result,
+            Err(AtCoderError::LoginFailed(LoginError::InvalidCredentials))
+        ));
+        assert!(client.export_session().is_err());
+    }
+
+    /// Logged in as `user`, but refuses `/forbidden`.
+    #[derive(Default)]
+    struct CountingTransport {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpTransport for CountingTransport {
+        async fn execute(&self, request: &HttpRequest) -> anyhow::Result<HttpResponse> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            let status = if request.url.ends_with("/forbidden") {
+                403
+            } else {
+                200
+            };
+            Ok(HttpResponse {
+                status,
+                headers: vec![],
+                body: "var userScreenName = \"user\";".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensure_logged_in_checks_once() {
+        let transport = Arc::new(CountingTransport::default());
         let client = AtCoderClient {
-            client: Arc::new(reqwest::Client::builder().build().unwrap()),
+            transport: transport.clone(),
+            limiter: Arc::new(RateLimiter::new(RateLimitConfig::default())),
+            verified: Arc::new(AtomicBool::new(false)),
         };
+        let requests = || transport.requests.load(Ordering::Relaxed);
+
+        assert!(!client.ensure_logged_in("user", "pass").await.unwrap());
+        assert!(!client
+            .clone()
+            .ensure_logged_in("user", "pass")
+            .await
+            .unwrap());
+        assert_eq!(requests(), 1);
+
+        let url = format!("{}/forbidden", ATCODER_PREFIX);
+        assert!(client.get_html(&url).await.is_err());
+        assert!(!client.ensure_logged_in("user", "pass").await.unwrap());
+        assert_eq!(requests(), 3);
+    }
+
+    #[test]
+    fn test_with_new_session() {
+        let client = AtCoderClient::without_login(RateLimitConfig::default()).unwrap();
+        let other = client.with_new_session().unwrap();
+        assert!(Arc::ptr_eq(&client.limiter, &other.limiter));
+        assert!(!Arc::ptr_eq(&client.transport, &other.transport));
+    }
+
+    #[tokio::test]
+    async fn test_not_found() {
+        let client = replay_client();
+        let result = client.fetch_problem_list("abc000").await;
+        assert!(matches!(result, Err(AtCoderError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_contest_list() {
+        let client = replay_client();
         let contests = client
             .fetch_atcoder_contests(ContestTypeSpecifier::Normal { page: 1 })
             .await
@@ -144,23 +399,61 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_fetch_hidden_contest() {
-        let client = AtCoderClient {
-            client: Arc::new(reqwest::Client::builder().build().unwrap()),
-        };
+    async fn test_fetch_contest_detail() {
+        let client = replay_client();
+        let contest = client.fetch_contest_detail("abc107").await.unwrap();
+        assert_eq!(contest.title, "AtCoder Beginner Contest 107");
+        assert_eq!(contest.rated_range().unwrap().max, Some(1999));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_problem_list() {
+        let client = replay_client();
+        let problems = client.fetch_problem_list("abc107").await.unwrap();
+        assert_eq!(problems.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_permanent_contests() {
+        let client = replay_client();
         let contests = client
-            .fetch_atcoder_contests(ContestTypeSpecifier::Hidden)
+            .fetch_atcoder_contests(ContestTypeSpecifier::Permanent)
             .await
             .unwrap();
         assert!(!contests.is_empty());
+
+        let top = client
+            .fetch_atcoder_contests(ContestTypeSpecifier::Top)
+            .await
+            .unwrap();
+        assert!(top.len() >= contests.len());
+        assert_eq!(top[..contests.len()], contests[..]);
     }
 
     #[tokio::test]
-    async fn test_fetch_problem_list() {
-        let client = AtCoderClient {
-            client: Arc::new(reqwest::Client::builder().build().unwrap()),
-        };
-        let problems = client.fetch_problem_list("abc107").await.unwrap();
-        assert_eq!(problems.len(), 4);
+    async fn test_fetch_submission_list() {
+        let client = replay_client();
+        let response = client
+            .fetch_atcoder_submission_list("abc107", Some(1), &SubmissionListFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(response.submissions.len(), 20);
+        assert!(response.max_page > 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_filtered_submission_list() {
+        // The fixture of the filtered URL has no submissions, unlike the unfiltered one.
+        let client = replay_client();
+        let response = client
+            .fetch_atcoder_submission_list(
+                "abc107",
+                Some(1),
+                &SubmissionListFilter::user("tourist"),
+            )
+            .await
+            .unwrap();
+        assert!(response.submissions.is_empty());
+        assert_eq!(response.max_page, 1);
     }
 }
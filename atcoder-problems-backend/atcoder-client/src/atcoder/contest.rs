@@ -1,67 +1,75 @@
-use anyhow::{Context, Result};
-
 use super::AtCoderContest;
+use crate::error::{ParseContext, Result};
 
 use chrono::DateTime;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 
 const PERMANENT_CONTEST_DURATION_SECOND: u64 = 100 * 365 * 24 * 3600;
 
 pub(super) fn scrape_normal(html: &str) -> Result<Vec<AtCoderContest>> {
-    Html::parse_document(html)
+    const PAGE: &str = "contest archive";
+    let document = Html::parse_document(html);
+    let tbody = document
+        .select(&Selector::parse("tbody").unwrap())
+        .next()
+        .or_parse_error(PAGE, "tbody", None)?;
+    scrape_scheduled(tbody, PAGE)
+}
+
+/// Scrapes contests which are scheduled but haven't started yet.
+/// There may be no such contests, and then the table itself is omitted.
+pub(super) fn scrape_upcoming(html: &str) -> Result<Vec<AtCoderContest>> {
+    const PAGE: &str = "upcoming contests";
+    let document = Html::parse_document(html);
+    let table = match document
+        .select(&Selector::parse("#contest-table-upcoming").unwrap())
+        .next()
+    {
+        Some(table) => table,
+        None => return Ok(Vec::new()),
+    };
+    let tbody = table
         .select(&Selector::parse("tbody").unwrap())
         .next()
-        .context("Failed to parse html.")?
+        .or_parse_error(PAGE, "#contest-table-upcoming tbody", None)?;
+    scrape_scheduled(tbody, PAGE)
+}
+
+/// Scrapes a table of contests with their start time and duration.
+fn scrape_scheduled(tbody: ElementRef<'_>, page: &'static str) -> Result<Vec<AtCoderContest>> {
+    tbody
         .select(&Selector::parse("tr").unwrap())
-        .map(|tr| {
+        .enumerate()
+        .map(|(i, tr)| {
+            let row = Some(i);
             let selector = Selector::parse("td").unwrap();
             let mut tds = tr.select(&selector);
-            let start = tds
-                .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?;
-            let start = DateTime::parse_from_str(start, "%Y-%m-%d %H:%M:%S%z")?;
+            let start = tds.next().and_then(|td| td.text().next()).or_parse_error(
+                page,
+                "td:nth-child(1)",
+                row,
+            )?;
+            let start = DateTime::parse_from_str(start, "%Y-%m-%d %H:%M:%S%z").or_parse_error(
+                page,
+                "td:nth-child(1)",
+                row,
+            )?;
             let start = start.timestamp() as u64;
 
-            let contest = tds.next().context("Failed to parse html.")?;
-            let contest_title = contest
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?;
-            let contest_link = contest
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .context("Failed to parse html.")?
-                .value()
-                .attr("href")
-                .context("Failed to parse html.")?;
-            let contest_id = contest_link
-                .rsplit('/')
-                .next()
-                .context("Failed to parse html.")?;
+            let contest = tds.next().or_parse_error(page, "td:nth-child(2)", row)?;
+            let (contest_id, contest_title) =
+                scrape_contest_link(contest).or_parse_error(page, "td:nth-child(2) a", row)?;
 
-            let duration = tds.next().unwrap().text().next().unwrap();
-            let mut duration = duration.split(':');
-            let hours = duration
-                .next()
-                .context("Failed to parse html.")?
-                .parse::<u64>()?;
-            let minutes = duration
-                .next()
-                .context("Failed to parse html.")?
-                .parse::<u64>()?;
-            let duration = hours * 3600 + minutes * 60;
-            let rated = tds
-                .next()
-                .context("Failed to parse html.")?
-                .text()
+            let duration = tds
                 .next()
-                .context("Failed to parse html.")?;
+                .and_then(|td| td.text().next())
+                .and_then(parse_duration)
+                .or_parse_error(page, "td:nth-child(3)", row)?;
+            let rated = tds.next().and_then(|td| td.text().next()).or_parse_error(
+                page,
+                "td:nth-child(4)",
+                row,
+            )?;
             Ok(AtCoderContest {
                 id: contest_id.to_owned(),
                 start_epoch_second: start,
@@ -74,44 +82,30 @@ pub(super) fn scrape_normal(html: &str) -> Result<Vec<AtCoderContest>> {
 }
 
 pub(super) fn scrape_permanent(html: &str) -> Result<Vec<AtCoderContest>> {
+    const PAGE: &str = "permanent contests";
     Html::parse_document(html)
         .select(&Selector::parse("#contest-table-permanent").unwrap())
         .next()
-        .context("Failed to parse html.")?
+        .or_parse_error(PAGE, "#contest-table-permanent", None)?
         .select(&Selector::parse("tbody").unwrap())
         .next()
-        .context("Failed to parse html.")?
+        .or_parse_error(PAGE, "#contest-table-permanent tbody", None)?
         .select(&Selector::parse("tr").unwrap())
-        .map(|tr| {
+        .enumerate()
+        .map(|(i, tr)| {
+            let row = Some(i);
             let selector = Selector::parse("td").unwrap();
             let mut tds = tr.select(&selector);
 
-            let contest = tds.next().context("Failed to parse html.")?;
-            let contest_title = contest
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?;
-            let contest_link = contest
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .context("Failed to parse html.")?
-                .value()
-                .attr("href")
-                .context("Failed to parse html.")?;
-            let contest_id = contest_link
-                .rsplit('/')
-                .next()
-                .context("Failed to parse html.")?;
+            let contest = tds.next().or_parse_error(PAGE, "td:nth-child(1)", row)?;
+            let (contest_id, contest_title) =
+                scrape_contest_link(contest).or_parse_error(PAGE, "td:nth-child(1) a", row)?;
 
-            let rated = tds
-                .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?;
+            let rated = tds.next().and_then(|td| td.text().next()).or_parse_error(
+                PAGE,
+                "td:nth-child(2)",
+                row,
+            )?;
             Ok(AtCoderContest {
                 id: contest_id.to_owned(),
                 start_epoch_second: 0,
@@ -123,6 +117,69 @@ pub(super) fn scrape_permanent(html: &str) -> Result<Vec<AtCoderContest>> {
         .collect()
 }
 
+/// Scrapes the top page of a contest, for contests which may not be in the contest lists.
+pub(super) fn scrape_detail(html: &str, contest_id: &str) -> Result<AtCoderContest> {
+    const PAGE: &str = "contest";
+    let document = Html::parse_document(html);
+    let title = document
+        .select(&Selector::parse("a.contest-title").unwrap())
+        .next()
+        .and_then(|a| a.text().next())
+        .or_parse_error(PAGE, "a.contest-title", None)?;
+
+    let times = document
+        .select(&Selector::parse(".contest-duration time").unwrap())
+        .map(|time| {
+            let time = time.text().next()?;
+            let time = DateTime::parse_from_str(time.trim(), "%Y-%m-%d %H:%M:%S%z").ok()?;
+            u64::try_from(time.timestamp()).ok()
+        })
+        .collect::<Option<Vec<_>>>()
+        .or_parse_error(PAGE, ".contest-duration time", None)?;
+    let (start, end) = match times[..] {
+        [start, end] if start <= end => (start, end),
+        _ => return None.or_parse_error(PAGE, ".contest-duration time", None),
+    };
+
+    // Unrated contests are shown as "Rated対象: -".
+    let rated = document
+        .select(&Selector::parse("span").unwrap())
+        .filter_map(|span| span.text().next())
+        .find_map(|text| text.trim().strip_prefix("Rated対象:"))
+        .or_parse_error(PAGE, "span", None)?
+        .trim();
+    // Same as the contest lists, which show a range without the lower bound as " ~ 1999".
+    let rate_change = if rated.starts_with('~') {
+        format!(" {}", rated)
+    } else {
+        rated.to_owned()
+    };
+
+    Ok(AtCoderContest {
+        id: contest_id.to_owned(),
+        start_epoch_second: start,
+        duration_second: end - start,
+        title: title.trim().to_owned(),
+        rate_change,
+    })
+}
+
+/// Returns the contest id and the title from the link to the contest.
+fn scrape_contest_link(td: scraper::ElementRef<'_>) -> Option<(&str, &str)> {
+    let a = td.select(&Selector::parse("a").unwrap()).next()?;
+    let title = a.text().next()?;
+    let id = a.value().attr("href")?.rsplit('/').next()?;
+    Some((id, title))
+}
+
+/// Parses a duration like "01:40" into seconds.
+fn parse_duration(duration: &str) -> Option<u64> {
+    let mut duration = duration.split(':');
+    let hours = duration.next()?.parse::<u64>().ok()?;
+    let minutes = duration.next()?.parse::<u64>().ok()?;
+    Some(hours * 3600 + minutes * 60)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +199,66 @@ mod tests {
         let contests = scrape_permanent(contents).unwrap();
         assert_eq!(contests.len(), 4);
     }
+
+    #[test]
+    fn test_scrape_upcoming() {
+        let contents = include_str!("../../test_resources/contests_permanent");
+        let contests = scrape_upcoming(contents).unwrap();
+        assert!(!contests.is_empty());
+        assert_eq!(contests[0].id, "abc199");
+        assert_eq!(contests[0].duration_second, 100 * 60);
+        assert_eq!(contests[0].rate_change, " ~ 1999");
+        assert_eq!(contests[1].id, "ahc002");
+
+        assert!(scrape_upcoming("<html></html>").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scrape_detail() {
+        let contents = include_str!("../../test_resources/abc107_contest");
+        let contest = scrape_detail(contents, "abc107").unwrap();
+        assert_eq!(
+            contest,
+            AtCoderContest {
+                id: "abc107".to_owned(),
+                start_epoch_second: 1535198400,
+                duration_second: 100 * 60,
+                title: "AtCoder Beginner Contest 107".to_owned(),
+                rate_change: " ~ 1999".to_owned(),
+            }
+        );
+
+        let e = scrape_detail("<html></html>", "abc107").unwrap_err();
+        assert!(matches!(
+            e,
+            crate::AtCoderError::Parse {
+                selector: "a.contest-title",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_scrape_broken_page() {
+        let e = scrape_permanent("<html><body></body></html>").unwrap_err();
+        assert!(matches!(
+            e,
+            crate::AtCoderError::Parse {
+                selector: "#contest-table-permanent",
+                row: None,
+                ..
+            }
+        ));
+
+        let html = "<table><tbody><tr><td>2018-09-08 21:00:00+0900</td><td>ABC107</td></tr></tbody></table>";
+        let e = scrape_normal(html).unwrap_err();
+        assert!(matches!(
+            e,
+            crate::AtCoderError::Parse {
+                selector: "td:nth-child(2) a",
+                row: Some(0),
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,72 @@
+use super::AtCoderUserHistory;
+
+use crate::error::{parse_json, ParseContext, Result};
+use chrono::DateTime;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HistoryJson {
+    is_rated: bool,
+    place: u32,
+    old_rating: u32,
+    new_rating: u32,
+    performance: u32,
+    contest_screen_name: String,
+    end_time: String,
+}
+
+pub(super) fn parse(json: &str) -> Result<Vec<AtCoderUserHistory>> {
+    const PAGE: &str = "user history";
+    let history: Vec<HistoryJson> = parse_json(json, PAGE)?;
+    history
+        .into_iter()
+        .enumerate()
+        .map(|(i, h)| {
+            // ContestScreenName looks like "abc107.contest.atcoder.jp".
+            let contest_id = h
+                .contest_screen_name
+                .split('.')
+                .next()
+                .or_parse_error(PAGE, "ContestScreenName", Some(i))?
+                .to_string();
+            let end_epoch_second = DateTime::parse_from_rfc3339(&h.end_time)
+                .or_parse_error(PAGE, "EndTime", Some(i))?
+                .timestamp() as u64;
+            Ok(AtCoderUserHistory {
+                contest_id,
+                is_rated: h.is_rated,
+                rank: h.place,
+                performance: h.performance,
+                old_rating: h.old_rating,
+                new_rating: h.new_rating,
+                end_epoch_second,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json = include_str!("../../test_resources/user_history");
+        let history = parse(json).unwrap();
+        assert_eq!(history.len(), 3);
+
+        assert_eq!(history[0].contest_id, "abc107");
+        assert!(history[0].is_rated);
+        assert_eq!(history[0].rank, 1024);
+        assert_eq!(history[0].performance, 1112);
+        assert_eq!(history[0].old_rating, 0);
+        assert_eq!(history[0].new_rating, 556);
+        assert_eq!(history[0].end_epoch_second, 1535204400);
+
+        assert_eq!(history[2].contest_id, "agc027");
+        assert!(!history[2].is_rated);
+        assert_eq!(history[2].old_rating, 812);
+        assert_eq!(history[2].new_rating, 812);
+    }
+}
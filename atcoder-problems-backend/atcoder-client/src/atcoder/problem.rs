@@ -1,52 +1,81 @@
-use anyhow::{Context, Result};
-
 use super::AtCoderProblem;
+use crate::error::{ParseContext, Result};
 
 use scraper::{Html, Selector};
 
 pub(super) fn scrape(html: &str, contest_id: &str) -> Result<Vec<AtCoderProblem>> {
+    const PAGE: &str = "tasks";
     Html::parse_document(html)
         .select(&Selector::parse("tbody").unwrap())
         .next()
-        .context("Failed to parse html.")?
+        .or_parse_error(PAGE, "tbody", None)?
         .select(&Selector::parse("tr").unwrap())
-        .map(|tr| {
+        .enumerate()
+        .map(|(i, tr)| {
+            let row = Some(i);
             let selector = Selector::parse("td").unwrap();
             let mut tds = tr.select(&selector);
             let position = tds
                 .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?
+                .and_then(|td| td.text().next())
+                .or_parse_error(PAGE, "td:nth-child(1)", row)?
                 .to_owned();
-            let problem = tds.next().context("Failed to parse html.")?;
+            let problem = tds.next().or_parse_error(PAGE, "td:nth-child(2)", row)?;
             let id = problem
                 .select(&Selector::parse("a").unwrap())
                 .next()
-                .context("Failed to parse html.")?
-                .value()
-                .attr("href")
-                .context("Failed to parse html.")?
-                .rsplit('/')
-                .next()
-                .context("Failed to parse html.")?
+                .and_then(|a| a.value().attr("href"))
+                .and_then(|href| href.rsplit('/').next())
+                .or_parse_error(PAGE, "td:nth-child(2) a[href]", row)?
                 .to_owned();
             let title = problem
                 .text()
                 .next()
-                .context("Failed to parse html.")?
+                .or_parse_error(PAGE, "td:nth-child(2)", row)?
                 .to_owned();
+            let time_limit_ms = tds
+                .next()
+                .and_then(|td| parse_time_limit(&td.text().collect::<String>()));
+            let memory_limit_kb = tds
+                .next()
+                .and_then(|td| parse_memory_limit(&td.text().collect::<String>()));
             Ok(AtCoderProblem {
                 id,
                 contest_id: contest_id.to_owned(),
                 title,
                 position,
+                time_limit_ms,
+                memory_limit_kb,
             })
         })
         .collect()
 }
 
+/// Parses a time limit like "2 sec" into milliseconds.
+fn parse_time_limit(text: &str) -> Option<u64> {
+    let second = text
+        .trim()
+        .strip_suffix("sec")?
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    Some((second * 1000.0).round() as u64)
+}
+
+/// Parses a memory limit like "1024 MB" into kilobytes.
+fn parse_memory_limit(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (value, scale) = if let Some(value) = text.strip_suffix("GB") {
+        (value, 1024 * 1024)
+    } else if let Some(value) = text.strip_suffix("MB") {
+        (value, 1024)
+    } else {
+        (text.strip_suffix("KB")?, 1)
+    };
+    let value = value.trim().parse::<f64>().ok()?;
+    Some((value * scale as f64).round() as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,25 +91,33 @@ mod tests {
                     id: "abc107_a".to_owned(),
                     contest_id: "abc107".to_owned(),
                     title: "Train".to_owned(),
-                    position: "A".to_owned()
+                    position: "A".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(1048576),
                 },
                 AtCoderProblem {
                     id: "abc107_b".to_owned(),
                     contest_id: "abc107".to_owned(),
                     title: "Grid Compression".to_owned(),
-                    position: "B".to_owned()
+                    position: "B".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(1048576),
                 },
                 AtCoderProblem {
                     id: "arc101_a".to_owned(),
                     contest_id: "abc107".to_owned(),
                     title: "Candles".to_owned(),
-                    position: "C".to_owned()
+                    position: "C".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(1048576),
                 },
                 AtCoderProblem {
                     id: "arc101_b".to_owned(),
                     contest_id: "abc107".to_owned(),
                     title: "Median of Medians".to_owned(),
-                    position: "D".to_owned()
+                    position: "D".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(1048576),
                 }
             ]
         );
@@ -98,20 +135,37 @@ mod tests {
                     contest_id: "atc002".to_owned(),
                     title: "幅優先探索".to_owned(),
                     position: "A".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(262144),
                 },
                 AtCoderProblem {
                     id: "atc002_b".to_owned(),
                     contest_id: "atc002".to_owned(),
                     title: "n^p mod m".to_owned(),
                     position: "B".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(262144),
                 },
                 AtCoderProblem {
                     id: "atc002_c".to_owned(),
                     contest_id: "atc002".to_owned(),
                     title: "最適二分探索木".to_owned(),
                     position: "C".to_owned(),
+                    time_limit_ms: Some(2000),
+                    memory_limit_kb: Some(262144),
                 },
             ]
         )
     }
+
+    #[test]
+    fn test_parse_limits() {
+        assert_eq!(parse_time_limit("2 sec"), Some(2000));
+        assert_eq!(parse_time_limit("5.25 sec"), Some(5250));
+        assert_eq!(parse_time_limit(""), None);
+        assert_eq!(parse_memory_limit("256 MB"), Some(262144));
+        assert_eq!(parse_memory_limit("1 GB"), Some(1048576));
+        assert_eq!(parse_memory_limit("64000 KB"), Some(64000));
+        assert_eq!(parse_memory_limit("unknown"), None);
+    }
 }
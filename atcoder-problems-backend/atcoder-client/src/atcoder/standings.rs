@@ -0,0 +1,111 @@
+use super::{AtCoderStandingsRow, AtCoderStandingsTaskResult};
+
+use crate::error::{parse_json, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+const SCORE_SCALE: f64 = 100.0;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StandingsJson {
+    task_info: Vec<TaskInfo>,
+    standings_data: Vec<StandingsData>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TaskInfo {
+    task_screen_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StandingsData {
+    rank: u32,
+    user_screen_name: String,
+    is_rated: bool,
+    old_rating: u32,
+    rating: u32,
+    task_results: BTreeMap<String, TaskResult>,
+    total_result: TaskResult,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TaskResult {
+    score: i64,
+    penalty: u32,
+    elapsed: u64,
+}
+
+pub(super) fn parse(json: &str) -> Result<Vec<AtCoderStandingsRow>> {
+    let standings: StandingsJson = parse_json(json, "standings")?;
+    let rows = standings
+        .standings_data
+        .into_iter()
+        .map(|data| {
+            let mut task_results = Vec::new();
+            for task in standings.task_info.iter() {
+                if let Some(result) = data.task_results.get(&task.task_screen_name) {
+                    task_results.push(AtCoderStandingsTaskResult {
+                        problem_id: task.task_screen_name.clone(),
+                        score: result.score as f64 / SCORE_SCALE,
+                        penalty: result.penalty,
+                        elapsed_second: result.elapsed / NANOS_PER_SECOND,
+                    });
+                }
+            }
+            AtCoderStandingsRow {
+                rank: data.rank,
+                user_id: data.user_screen_name,
+                is_rated: data.is_rated,
+                old_rating: data.old_rating,
+                new_rating: data.rating,
+                score: data.total_result.score as f64 / SCORE_SCALE,
+                penalty: data.total_result.penalty,
+                elapsed_second: data.total_result.elapsed / NANOS_PER_SECOND,
+                task_results,
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json = include_str!("../../test_resources/abc107_standings");
+        let rows = parse(json).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let row = &rows[0];
+        assert_eq!(row.rank, 1);
+        assert_eq!(row.user_id, "user_a");
+        assert!(row.is_rated);
+        assert_eq!(row.old_rating, 1520);
+        assert_eq!(row.new_rating, 1603);
+        assert_eq!(row.score, 1200.0);
+        assert_eq!(row.penalty, 1);
+        assert_eq!(row.elapsed_second, 2845);
+        assert_eq!(row.task_results.len(), 4);
+        assert_eq!(row.task_results[0].problem_id, "abc107_a");
+        assert_eq!(row.task_results[0].score, 100.0);
+        assert_eq!(row.task_results[0].elapsed_second, 95);
+        assert_eq!(row.task_results[3].problem_id, "arc101_b");
+        assert_eq!(row.task_results[3].score, 600.0);
+        assert_eq!(row.task_results[3].penalty, 1);
+
+        let row = &rows[2];
+        assert_eq!(row.rank, 3);
+        assert!(!row.is_rated);
+        assert_eq!(row.task_results.len(), 2);
+        assert_eq!(row.task_results[1].problem_id, "abc107_b");
+        assert_eq!(row.task_results[1].score, 0.0);
+        assert_eq!(row.task_results[1].penalty, 2);
+    }
+}
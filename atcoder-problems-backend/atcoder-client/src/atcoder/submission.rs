@@ -1,75 +1,86 @@
-use super::AtCoderSubmission;
-
-use anyhow::{Context, Result};
+use super::{AtCoderSubmission, AtCoderSubmissionDetail, AtCoderTestcaseResult, JudgeStatus};
+use crate::error::{ParseContext, Result};
 
 use chrono::DateTime;
 use regex::Regex;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+
+const SUBMISSIONS_PAGE: &str = "submissions";
+const DETAIL_PAGE: &str = "submission detail";
+
+/// A list without submissions has neither the table nor the pagination,
+/// which is told from a broken page by the search form of the list.
+fn is_empty_list(document: &Html) -> bool {
+    let table_selector = Selector::parse("tbody").unwrap();
+    let form_selector = Selector::parse("input[name='f.User']").unwrap();
+    document.select(&table_selector).next().is_none()
+        && document.select(&form_selector).next().is_some()
+}
 
 pub(super) fn scrape_submission_page_count(html: &str) -> Result<u32> {
     let selector = Selector::parse("a").unwrap();
     let re = Regex::new(r"page=\d+$").unwrap();
-    Html::parse_document(html)
+    let document = Html::parse_document(html);
+    if is_empty_list(&document) {
+        return Ok(1);
+    }
+    document
         .select(&selector)
         .flat_map(|el| el.value().attr("href"))
         .filter(|href| re.is_match(href))
         .flat_map(|href| href.rsplit('=').next())
         .flat_map(str::parse)
         .max()
-        .context("Failed to parse html.")
+        .or_parse_error(SUBMISSIONS_PAGE, "a[href$=page]", None)
 }
 
 pub(super) fn scrape(html_text: &str, contest_id: &str) -> Result<Vec<AtCoderSubmission>> {
+    const PAGE: &str = SUBMISSIONS_PAGE;
     let tbody_selector = Selector::parse("tbody").unwrap();
     let tr_selector = Selector::parse("tr").unwrap();
     let td_selector = Selector::parse("td").unwrap();
     let a_selector = Selector::parse("a").unwrap();
     let re = Regex::new(r"submissions/\d+$").unwrap();
 
-    Html::parse_document(html_text)
+    let document = Html::parse_document(html_text);
+    if is_empty_list(&document) {
+        return Ok(Vec::new());
+    }
+    document
         .select(&tbody_selector)
         .next()
-        .context("Failed to parse html.")?
+        .or_parse_error(PAGE, "tbody", None)?
         .select(&tr_selector)
-        .map(|tr| {
+        .enumerate()
+        .map(|(i, tr)| {
+            let row = Some(i);
             let mut tds = tr.select(&td_selector);
+            let last_segment_of_link = |td: Option<ElementRef>| {
+                td?.select(&a_selector)
+                    .next()?
+                    .value()
+                    .attr("href")?
+                    .rsplit('/')
+                    .next()
+                    .map(str::to_owned)
+            };
 
-            let time = tds
-                .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?;
-            let time = DateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%z")?;
+            let time = tds.next().and_then(|td| td.text().next()).or_parse_error(
+                PAGE,
+                "td:nth-child(1)",
+                row,
+            )?;
+            let time = DateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%z").or_parse_error(
+                PAGE,
+                "td:nth-child(1)",
+                row,
+            )?;
             let epoch_second = time.timestamp() as u64;
 
-            let problem_id = tds
-                .next()
-                .context("Failed to parse html.")?
-                .select(&a_selector)
-                .next()
-                .context("Failed to parse html.")?
-                .value()
-                .attr("href")
-                .context("Failed to parse html.")?
-                .rsplit('/')
-                .next()
-                .context("Failed to parse html.")?
-                .to_owned();
-
-            let user_id = tds
-                .next()
-                .context("Failed to parse html.")?
-                .select(&a_selector)
-                .next()
-                .context("Failed to parse html.")?
-                .value()
-                .attr("href")
-                .context("Failed to parse html.")?
-                .rsplit('/')
-                .next()
-                .context("Failed to parse html.")?
-                .to_owned();
+            let problem_id =
+                last_segment_of_link(tds.next()).or_parse_error(PAGE, "td:nth-child(2) a", row)?;
+            let user_id =
+                last_segment_of_link(tds.next()).or_parse_error(PAGE, "td:nth-child(3) a", row)?;
 
             let language = tds
                 .next()
@@ -79,29 +90,23 @@ pub(super) fn scrape(html_text: &str, contest_id: &str) -> Result<Vec<AtCoderSub
 
             let point: f64 = tds
                 .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?
-                .parse()?;
+                .and_then(|td| td.text().next())
+                .and_then(|point| point.parse().ok())
+                .or_parse_error(PAGE, "td:nth-child(5)", row)?;
 
             let length = tds
                 .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?
-                .replace("Byte", "")
-                .trim()
-                .parse::<u64>()?;
+                .and_then(|td| td.text().next())
+                .and_then(|length| length.replace("Byte", "").trim().parse::<u64>().ok())
+                .or_parse_error(PAGE, "td:nth-child(6)", row)?;
 
+            // An unknown status is an error rather than being stored as is,
+            // so that a new verdict of AtCoder doesn't go unnoticed.
             let result = tds
                 .next()
-                .context("Failed to parse html.")?
-                .text()
-                .next()
-                .context("Failed to parse html.")?
-                .to_owned();
+                .map(|td| td.text().collect::<String>())
+                .and_then(|status| JudgeStatus::parse(&status))
+                .or_parse_error(PAGE, "td:nth-child(7)", row)?;
 
             let execution_time = tds
                 .next()
@@ -109,21 +114,19 @@ pub(super) fn scrape(html_text: &str, contest_id: &str) -> Result<Vec<AtCoderSub
                 .map(|s| s.replace("ms", ""))
                 .and_then(|s| s.trim().parse::<u64>().ok());
 
+            let memory = tds
+                .next()
+                .and_then(|e| e.text().next())
+                .map(|s| s.replace("KB", ""))
+                .and_then(|s| s.trim().parse::<u64>().ok());
+
             let id = tr
                 .select(&a_selector)
-                .find(|e| match e.value().attr("href") {
-                    Some(href) => re.is_match(href),
-                    None => false,
-                })
-                .context("Failed to parse html.")?
-                .value()
-                .attr("href")
-                .context("Failed to parse html.")?
-                .rsplit('/')
-                .next()
-                .context("Failed to parse html.")?
-                .trim()
-                .parse::<u64>()?;
+                .filter_map(|e| e.value().attr("href"))
+                .find(|href| re.is_match(href))
+                .and_then(|href| href.rsplit('/').next())
+                .and_then(|id| id.trim().parse::<u64>().ok())
+                .or_parse_error(PAGE, "a[href*=submissions]", row)?;
             Ok(AtCoderSubmission {
                 id,
                 epoch_second,
@@ -135,14 +138,98 @@ pub(super) fn scrape(html_text: &str, contest_id: &str) -> Result<Vec<AtCoderSub
                 length,
                 result,
                 execution_time,
+                memory,
             })
         })
         .collect()
 }
 
+pub(super) fn scrape_detail(
+    html_text: &str,
+    contest_id: &str,
+    submission_id: u64,
+) -> Result<AtCoderSubmissionDetail> {
+    const PAGE: &str = DETAIL_PAGE;
+    let h4_selector = Selector::parse("h4").unwrap();
+    let table_selector = Selector::parse("table").unwrap();
+    let th_selector = Selector::parse("thead th").unwrap();
+    let tr_selector = Selector::parse("tbody tr").unwrap();
+    let td_selector = Selector::parse("td").unwrap();
+
+    let document = Html::parse_document(html_text);
+
+    let compile_error = document
+        .select(&h4_selector)
+        .find(|h4| h4.text().any(|t| t.trim() == "Compile Error"))
+        .and_then(|h4| h4.next_siblings().find_map(ElementRef::wrap))
+        .filter(|e| e.value().name() == "pre")
+        .map(|pre| pre.text().collect::<String>());
+
+    let testcase_table = document.select(&table_selector).find(|table| {
+        table
+            .select(&th_selector)
+            .next()
+            .and_then(|th| th.text().next())
+            .map(|t| t.trim() == "Case Name")
+            .unwrap_or(false)
+    });
+    let testcases = match testcase_table {
+        Some(table) => table
+            .select(&tr_selector)
+            .enumerate()
+            .map(|(i, tr)| {
+                let row = Some(i);
+                let mut tds = tr.select(&td_selector);
+
+                let name = tds
+                    .next()
+                    .and_then(|td| td.text().next())
+                    .or_parse_error(PAGE, "td:nth-child(1)", row)?
+                    .trim()
+                    .to_owned();
+
+                let result = tds
+                    .next()
+                    .and_then(|td| td.text().next())
+                    .or_parse_error(PAGE, "td:nth-child(2)", row)?
+                    .trim()
+                    .to_owned();
+
+                let execution_time = tds
+                    .next()
+                    .and_then(|e| e.text().next())
+                    .map(|s| s.replace("ms", ""))
+                    .and_then(|s| s.trim().parse::<u64>().ok());
+
+                let memory = tds
+                    .next()
+                    .and_then(|e| e.text().next())
+                    .map(|s| s.replace("KB", ""))
+                    .and_then(|s| s.trim().parse::<u64>().ok());
+
+                Ok(AtCoderTestcaseResult {
+                    name,
+                    result,
+                    execution_time,
+                    memory,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+
+    Ok(AtCoderSubmissionDetail {
+        id: submission_id,
+        contest_id: contest_id.to_owned(),
+        compile_error,
+        testcases,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Verdict;
 
     #[test]
     fn test_scrape() {
@@ -150,8 +237,83 @@ mod tests {
         let submissions = scrape(contents, "abc107").unwrap();
         assert_eq!(submissions.len(), 20);
         assert!(submissions.iter().all(|s| s.user_id.is_ascii()));
+        assert_eq!(submissions[0].execution_time, Some(93));
+        assert_eq!(submissions[0].memory, Some(4764));
+
+        assert_eq!(submissions[0].result, JudgeStatus::Final(Verdict::Accepted));
 
         let max_page = scrape_submission_page_count(contents).unwrap();
         assert_eq!(max_page, 2208);
     }
+
+    #[test]
+    fn test_scrape_empty() {
+        let contents = include_str!("../../test_resources/abc107_submissions_empty");
+        assert!(scrape(contents, "abc107").unwrap().is_empty());
+        assert_eq!(scrape_submission_page_count(contents).unwrap(), 1);
+
+        // A page without the table nor the search form is broken.
+        let broken = contents.replace("f.User", "f.Unknown");
+        assert!(scrape(&broken, "abc107").unwrap_err().is_parse_error());
+        assert!(scrape_submission_page_count(&broken)
+            .unwrap_err()
+            .is_parse_error());
+    }
+
+    #[test]
+    fn test_scrape_unknown_status() {
+        let contents = include_str!("../../test_resources/abc107_submissions").replacen(
+            "title=\"Accepted\">AC<",
+            "title=\"Accepted\">XX<",
+            1,
+        );
+        let e = scrape(&contents, "abc107").unwrap_err();
+        assert!(e.is_parse_error());
+        assert!(e.to_string().contains("td:nth-child(7)"));
+    }
+
+    #[test]
+    fn test_scrape_detail() {
+        let contents = include_str!("../../test_resources/abc107_submission_detail");
+        let detail = scrape_detail(contents, "abc107", 22755348).unwrap();
+        assert_eq!(detail.id, 22755348);
+        assert_eq!(detail.contest_id, "abc107");
+        assert_eq!(detail.compile_error, None);
+        assert_eq!(detail.testcases.len(), 8);
+        assert_eq!(
+            detail.testcases[0],
+            AtCoderTestcaseResult {
+                name: "sample_01.txt".to_owned(),
+                result: "AC".to_owned(),
+                execution_time: Some(7),
+                memory: Some(3588),
+            }
+        );
+
+        let failed = detail
+            .testcases
+            .iter()
+            .filter(|t| t.result != "AC")
+            .map(|t| (t.name.as_str(), t.result.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            failed,
+            vec![
+                ("subtask_1_02.txt", "WA"),
+                ("subtask_1_04.txt", "WA"),
+                ("subtask_1_05.txt", "TLE")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scrape_detail_compile_error() {
+        let contents = include_str!("../../test_resources/abc107_submission_detail_ce");
+        let detail = scrape_detail(contents, "abc107", 22749910).unwrap();
+        assert!(detail.testcases.is_empty());
+
+        let compile_error = detail.compile_error.unwrap();
+        assert!(compile_error.starts_with("./Main.cpp: In function 'int main()':"));
+        assert!(compile_error.contains("error: expected initializer before 'std'"));
+    }
 }
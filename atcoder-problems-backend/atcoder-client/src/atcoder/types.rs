@@ -2,9 +2,45 @@ use crate::util::Problem;
 use serde::{Deserialize, Serialize};
 
 pub enum ContestTypeSpecifier {
-    Normal { page: u32 },
+    Normal {
+        page: u32,
+    },
     Permanent,
-    Hidden,
+    /// Contests which are scheduled but haven't started yet.
+    Upcoming,
+    /// Permanent contests and contests which are scheduled but haven't started yet,
+    /// both of which are listed in the top page of the contests.
+    Top,
+}
+
+/// Filters of the submission list, each of which corresponds to `f.*` query parameters of AtCoder.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubmissionListFilter {
+    pub task: Option<String>,
+    pub user: Option<String>,
+    pub language: Option<String>,
+    pub status: Option<String>,
+}
+
+impl SubmissionListFilter {
+    pub fn user<S: ToString>(user_id: S) -> Self {
+        Self {
+            user: Some(user_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn query_pairs(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("f.Task", &self.task),
+            ("f.User", &self.user),
+            ("f.LanguageName", &self.language),
+            ("f.Status", &self.status),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_deref().map(|value| (key, value)))
+        .collect()
+    }
 }
 
 pub struct AtCoderSubmissionListResponse {
@@ -21,6 +57,224 @@ pub struct AtCoderContest {
     pub rate_change: String,
 }
 
+impl AtCoderContest {
+    /// Range of ratings for which the contest is rated, or `None` if the contest is unrated.
+    pub fn rated_range(&self) -> Option<RatedRange> {
+        RatedRange::parse(&self.rate_change)
+    }
+
+    pub fn category(&self) -> ContestCategory {
+        ContestCategory::classify(&self.id, self.rated_range().is_some())
+    }
+}
+
+/// Range of ratings such as "All", " ~ 1999" or "1200 ~ ", where `None` means unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RatedRange {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+impl RatedRange {
+    /// Parses the "Rated" column of the contest list. Returns `None` for unrated contests ("-").
+    pub fn parse(rate_change: &str) -> Option<Self> {
+        let rate_change = rate_change.trim();
+        if rate_change == "All" {
+            return Some(Self::default());
+        }
+        let (min, max) = rate_change.split_once('~')?;
+        let parse_bound = |bound: &str| -> Option<Option<u32>> {
+            let bound = bound.trim();
+            if bound.is_empty() {
+                Some(None)
+            } else {
+                bound.parse().ok().map(Some)
+            }
+        };
+        Some(Self {
+            min: parse_bound(min)?,
+            max: parse_bound(max)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContestCategory {
+    Abc,
+    Arc,
+    Agc,
+    Ahc,
+    /// Rated contests held by sponsors, e.g. "keyence2021".
+    Sponsored,
+    Other,
+}
+
+impl ContestCategory {
+    pub fn classify(contest_id: &str, is_rated: bool) -> Self {
+        let is_series = |prefix: &str| {
+            contest_id.strip_prefix(prefix).map_or(false, |number| {
+                !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+            })
+        };
+        if is_series("abc") {
+            ContestCategory::Abc
+        } else if is_series("arc") {
+            ContestCategory::Arc
+        } else if is_series("agc") {
+            ContestCategory::Agc
+        } else if is_series("ahc") {
+            ContestCategory::Ahc
+        } else if is_rated {
+            ContestCategory::Sponsored
+        } else {
+            ContestCategory::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContestCategory::Abc => "ABC",
+            ContestCategory::Arc => "ARC",
+            ContestCategory::Agc => "AGC",
+            ContestCategory::Ahc => "AHC",
+            ContestCategory::Sponsored => "Sponsored",
+            ContestCategory::Other => "Other",
+        }
+    }
+}
+
+/// Final verdicts of a submission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    OutputLimitExceeded,
+    RuntimeError,
+    CompilationError,
+    QueryLimitExceeded,
+    InternalError,
+    /// Used in some old contests instead of `WrongAnswer`.
+    NoGood,
+}
+
+impl Verdict {
+    pub const ALL: [Verdict; 10] = [
+        Verdict::Accepted,
+        Verdict::WrongAnswer,
+        Verdict::TimeLimitExceeded,
+        Verdict::MemoryLimitExceeded,
+        Verdict::OutputLimitExceeded,
+        Verdict::RuntimeError,
+        Verdict::CompilationError,
+        Verdict::QueryLimitExceeded,
+        Verdict::InternalError,
+        Verdict::NoGood,
+    ];
+
+    pub fn parse(verdict: &str) -> Option<Self> {
+        let verdict = verdict.trim();
+        Self::ALL.into_iter().find(|v| v.as_str() == verdict)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "AC",
+            Verdict::WrongAnswer => "WA",
+            Verdict::TimeLimitExceeded => "TLE",
+            Verdict::MemoryLimitExceeded => "MLE",
+            Verdict::OutputLimitExceeded => "OLE",
+            Verdict::RuntimeError => "RE",
+            Verdict::CompilationError => "CE",
+            Verdict::QueryLimitExceeded => "QLE",
+            Verdict::InternalError => "IE",
+            Verdict::NoGood => "NG",
+        }
+    }
+}
+
+/// Status cell of the submission list, such as "AC", "WJ", "WR" or "3/20 TLE".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JudgeStatus {
+    Final(Verdict),
+    /// Waiting for judge ("WJ").
+    WaitingForJudge,
+    /// Waiting for rejudge ("WR").
+    WaitingForRejudge,
+    /// Being judged. `verdict` is the worst verdict so far, or `None` if nothing has failed yet.
+    Judging {
+        judged: u32,
+        total: u32,
+        verdict: Option<Verdict>,
+    },
+}
+
+impl JudgeStatus {
+    /// Returns `None` for unknown statuses.
+    pub fn parse(status: &str) -> Option<Self> {
+        let status = status.trim();
+        match status {
+            "WJ" => return Some(JudgeStatus::WaitingForJudge),
+            "WR" => return Some(JudgeStatus::WaitingForRejudge),
+            _ => {}
+        }
+        if let Some(verdict) = Verdict::parse(status) {
+            return Some(JudgeStatus::Final(verdict));
+        }
+
+        let (progress, verdict) = status
+            .split_once(char::is_whitespace)
+            .unwrap_or((status, "WJ"));
+        let (judged, total) = progress.split_once('/')?;
+        let judged = judged.parse().ok()?;
+        let total = total.parse().ok()?;
+        let verdict = match verdict.trim() {
+            "WJ" => None,
+            verdict => Some(Verdict::parse(verdict)?),
+        };
+        Some(JudgeStatus::Judging {
+            judged,
+            total,
+            verdict,
+        })
+    }
+
+    /// Whether the submission has been judged and its status won't change without a rejudge.
+    pub fn is_final(&self) -> bool {
+        matches!(self, JudgeStatus::Final(_))
+    }
+
+    /// All the final statuses as they are shown in the submission list.
+    pub fn final_statuses() -> Vec<String> {
+        Verdict::ALL
+            .into_iter()
+            .map(|verdict| JudgeStatus::Final(verdict).to_string())
+            .collect()
+    }
+}
+
+impl std::fmt::Display for JudgeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JudgeStatus::Final(verdict) => write!(f, "{}", verdict.as_str()),
+            JudgeStatus::WaitingForJudge => write!(f, "WJ"),
+            JudgeStatus::WaitingForRejudge => write!(f, "WR"),
+            JudgeStatus::Judging {
+                judged,
+                total,
+                verdict,
+            } => write!(
+                f,
+                "{}/{} {}",
+                judged,
+                total,
+                verdict.map_or("WJ", |v| v.as_str())
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct AtCoderSubmission {
     pub id: u64,
@@ -31,8 +285,57 @@ pub struct AtCoderSubmission {
     pub language: String,
     pub point: f64,
     pub length: u64,
+    pub result: JudgeStatus,
+    pub execution_time: Option<u64>,
+    pub memory: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtCoderSubmissionDetail {
+    pub id: u64,
+    pub contest_id: String,
+    pub compile_error: Option<String>,
+    pub testcases: Vec<AtCoderTestcaseResult>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtCoderTestcaseResult {
+    pub name: String,
     pub result: String,
     pub execution_time: Option<u64>,
+    pub memory: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtCoderStandingsRow {
+    pub rank: u32,
+    pub user_id: String,
+    pub is_rated: bool,
+    pub old_rating: u32,
+    pub new_rating: u32,
+    pub score: f64,
+    pub penalty: u32,
+    pub elapsed_second: u64,
+    pub task_results: Vec<AtCoderStandingsTaskResult>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtCoderStandingsTaskResult {
+    pub problem_id: String,
+    pub score: f64,
+    pub penalty: u32,
+    pub elapsed_second: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtCoderUserHistory {
+    pub contest_id: String,
+    pub is_rated: bool,
+    pub rank: u32,
+    pub performance: u32,
+    pub old_rating: u32,
+    pub new_rating: u32,
+    pub end_epoch_second: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -41,6 +344,8 @@ pub struct AtCoderProblem {
     pub title: String,
     pub position: String,
     pub contest_id: String,
+    pub time_limit_ms: Option<u64>,
+    pub memory_limit_kb: Option<u64>,
 }
 
 impl Problem for AtCoderProblem {
@@ -55,6 +360,80 @@ impl Problem for AtCoderProblem {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn test_rated_range() {
+        assert_eq!(RatedRange::parse("-"), None);
+        assert_eq!(RatedRange::parse(""), None);
+        assert_eq!(RatedRange::parse("All"), Some(RatedRange::default()));
+        assert_eq!(
+            RatedRange::parse(" ~ 1999"),
+            Some(RatedRange {
+                min: None,
+                max: Some(1999)
+            })
+        );
+        assert_eq!(
+            RatedRange::parse("1200 ~ "),
+            Some(RatedRange {
+                min: Some(1200),
+                max: None
+            })
+        );
+        assert_eq!(
+            RatedRange::parse("1200 ~ 2799"),
+            Some(RatedRange {
+                min: Some(1200),
+                max: Some(2799)
+            })
+        );
+        assert_eq!(RatedRange::parse("x ~ 1999"), None);
+    }
+
+    #[test]
+    fn test_contest_category() {
+        assert_eq!(
+            ContestCategory::classify("abc107", true),
+            ContestCategory::Abc
+        );
+        assert_eq!(
+            ContestCategory::classify("arc101", true),
+            ContestCategory::Arc
+        );
+        assert_eq!(
+            ContestCategory::classify("agc001", true),
+            ContestCategory::Agc
+        );
+        assert_eq!(
+            ContestCategory::classify("ahc002", false),
+            ContestCategory::Ahc
+        );
+        assert_eq!(
+            ContestCategory::classify("keyence2021", true),
+            ContestCategory::Sponsored
+        );
+        assert_eq!(
+            ContestCategory::classify("abc_like", false),
+            ContestCategory::Other
+        );
+        assert_eq!(
+            ContestCategory::classify("practice", false),
+            ContestCategory::Other
+        );
+    }
+
+    #[test]
+    fn test_submission_list_filter() {
+        assert!(SubmissionListFilter::default().query_pairs().is_empty());
+        let filter = SubmissionListFilter {
+            status: Some("WJ".to_string()),
+            ..SubmissionListFilter::user("kenkoooo")
+        };
+        assert_eq!(
+            filter.query_pairs(),
+            vec![("f.User", "kenkoooo"), ("f.Status", "WJ")]
+        );
+    }
+
     #[test]
     fn test_url() {
         let problem = AtCoderProblem {
@@ -62,10 +441,74 @@ mod tests {
             title: "".to_string(),
             position: "".to_string(),
             contest_id: "arc102".to_string(),
+            time_limit_ms: None,
+            memory_limit_kb: None,
         };
         assert_eq!(
             "https://atcoder.jp/contests/arc102/tasks/arc102_c",
             problem.url()
         );
     }
+
+    #[test]
+    fn test_final_statuses() {
+        let statuses = JudgeStatus::final_statuses();
+        assert_eq!(statuses.len(), Verdict::ALL.len());
+        assert!(statuses
+            .iter()
+            .all(|s| JudgeStatus::parse(s).map_or(false, |s| s.is_final())));
+        assert!(!statuses.contains(&"WJ".to_string()));
+    }
+
+    #[test]
+    fn test_judge_status() {
+        assert_eq!(
+            JudgeStatus::parse("AC"),
+            Some(JudgeStatus::Final(Verdict::Accepted))
+        );
+        assert_eq!(
+            JudgeStatus::parse(" TLE "),
+            Some(JudgeStatus::Final(Verdict::TimeLimitExceeded))
+        );
+        assert_eq!(JudgeStatus::parse("WJ"), Some(JudgeStatus::WaitingForJudge));
+        assert_eq!(
+            JudgeStatus::parse("WR"),
+            Some(JudgeStatus::WaitingForRejudge)
+        );
+        assert_eq!(
+            JudgeStatus::parse("3/20 WJ"),
+            Some(JudgeStatus::Judging {
+                judged: 3,
+                total: 20,
+                verdict: None
+            })
+        );
+        assert_eq!(
+            JudgeStatus::parse("3/20 TLE"),
+            Some(JudgeStatus::Judging {
+                judged: 3,
+                total: 20,
+                verdict: Some(Verdict::TimeLimitExceeded)
+            })
+        );
+        assert_eq!(
+            JudgeStatus::parse("3/20"),
+            Some(JudgeStatus::Judging {
+                judged: 3,
+                total: 20,
+                verdict: None
+            })
+        );
+        assert_eq!(JudgeStatus::parse("XX"), None);
+        assert_eq!(JudgeStatus::parse("3/20 XX"), None);
+        assert_eq!(JudgeStatus::parse("a/b WJ"), None);
+        assert_eq!(JudgeStatus::parse(""), None);
+
+        for status in ["AC", "WJ", "WR", "3/20 WJ", "3/20 TLE"] {
+            assert_eq!(JudgeStatus::parse(status).unwrap().to_string(), status);
+        }
+        assert!(JudgeStatus::parse("CE").unwrap().is_final());
+        assert!(!JudgeStatus::parse("3/20 WA").unwrap().is_final());
+        assert!(!JudgeStatus::parse("WR").unwrap().is_final());
+    }
 }
@@ -0,0 +1,123 @@
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use thiserror::Error;
+
+pub type Result<T, E = AtCoderError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum AtCoderError {
+    #[error("{0} is not found")]
+    NotFound(String),
+    #[error("failed to log in: {0}")]
+    LoginFailed(#[from] LoginError),
+    /// AtCoder kept answering 429 after all the retries.
+    #[error("rate limited by AtCoder")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("unexpected HTTP status {0}")]
+    Http(StatusCode),
+    /// The page doesn't look like what the scraper expects, probably because its layout has changed.
+    #[error("failed to parse {page}: {selector} is missing or invalid{}", .row.map(|row| format!(" at row {}", row)).unwrap_or_default())]
+    Parse {
+        page: &'static str,
+        selector: &'static str,
+        row: Option<usize>,
+    },
+    #[error("circuit breaker is open for {0:?}")]
+    CircuitOpen(Duration),
+    /// Errors from the transport, e.g. connection errors.
+    #[error(transparent)]
+    Transport(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum LoginError {
+    /// The login page didn't contain a CSRF token, e.g. because its layout has changed.
+    #[error("no csrf token in the login page")]
+    CsrfTokenNotFound,
+    /// AtCoder sent us back to the login page as an anonymous user.
+    #[error("invalid username or password")]
+    InvalidCredentials,
+}
+
+impl AtCoderError {
+    /// Whether this error means the scraper itself is broken and needs to be fixed.
+    pub fn is_parse_error(&self) -> bool {
+        matches!(self, AtCoderError::Parse { .. })
+    }
+}
+
+/// Converts a missing element or an unparsable value into `AtCoderError::Parse`.
+pub(crate) trait ParseContext<T> {
+    fn or_parse_error(
+        self,
+        page: &'static str,
+        selector: &'static str,
+        row: Option<usize>,
+    ) -> Result<T>;
+}
+
+impl<T> ParseContext<T> for Option<T> {
+    fn or_parse_error(
+        self,
+        page: &'static str,
+        selector: &'static str,
+        row: Option<usize>,
+    ) -> Result<T> {
+        self.ok_or(AtCoderError::Parse {
+            page,
+            selector,
+            row,
+        })
+    }
+}
+
+impl<T, E> ParseContext<T> for std::result::Result<T, E> {
+    fn or_parse_error(
+        self,
+        page: &'static str,
+        selector: &'static str,
+        row: Option<usize>,
+    ) -> Result<T> {
+        self.ok().or_parse_error(page, selector, row)
+    }
+}
+
+/// Deserializes a JSON page. Details of a broken JSON are only logged,
+/// because they are too verbose for `AtCoderError::Parse`.
+pub(crate) fn parse_json<T: DeserializeOwned>(json: &str, page: &'static str) -> Result<T> {
+    serde_json::from_str(json).map_err(|e| {
+        log::error!("Failed to parse {}: {}", page, e);
+        AtCoderError::Parse {
+            page,
+            selector: "json",
+            row: None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_message() {
+        let e = None::<()>
+            .or_parse_error("tasks", "tbody", None)
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "failed to parse tasks: tbody is missing or invalid"
+        );
+        assert!(e.is_parse_error());
+
+        let e = "x"
+            .parse::<u64>()
+            .or_parse_error("submissions", "td:nth-child(6)", Some(3))
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "failed to parse submissions: td:nth-child(6) is missing or invalid at row 3"
+        );
+    }
+}
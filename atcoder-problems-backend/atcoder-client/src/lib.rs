@@ -1,7 +1,15 @@
 pub(crate) mod atcoder;
 pub use atcoder::{
-    AtCoderClient, AtCoderContest, AtCoderProblem, AtCoderSubmission,
-    AtCoderSubmissionListResponse, ContestTypeSpecifier,
+    AtCoderClient, AtCoderContest, AtCoderProblem, AtCoderStandingsRow, AtCoderStandingsTaskResult,
+    AtCoderSubmission, AtCoderSubmissionDetail, AtCoderSubmissionListResponse,
+    AtCoderTestcaseResult, AtCoderUserHistory, ContestCategory, ContestTypeSpecifier, JudgeStatus,
+    RatedRange, SubmissionListFilter, Verdict,
 };
 
+mod error;
+pub(crate) mod rate_limiter;
+pub mod transport;
 pub(crate) mod util;
+
+pub use error::{AtCoderError, LoginError, Result};
+pub use rate_limiter::RateLimitConfig;
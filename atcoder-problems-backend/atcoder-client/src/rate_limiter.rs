@@ -0,0 +1,260 @@
+use crate::error::{AtCoderError, Result};
+use crate::transport::HttpResponse;
+use rand::Rng;
+use reqwest::StatusCode;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Politeness policy applied to every request sent to AtCoder.
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    /// Sustained number of requests per second.
+    /// The default is used instead if it isn't a positive number.
+    pub requests_per_second: f64,
+    /// Number of requests which can be sent at once after being idle.
+    pub burst: u32,
+    /// Number of retries of a request failed by a connection error, 429 or 5xx.
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Number of consecutive 5xx responses which opens the circuit breaker.
+    pub circuit_breaker_threshold: u32,
+    /// How long requests are rejected once the circuit breaker is open.
+    pub circuit_breaker_cooldown: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 4.0,
+            burst: 1,
+            max_retries: 5,
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            circuit_breaker_threshold: 10,
+            circuit_breaker_cooldown: Duration::from_secs(300),
+        }
+    }
+}
+
+struct State {
+    tokens: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+    consecutive_server_errors: u32,
+    open_until: Option<Instant>,
+}
+
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(mut config: RateLimitConfig) -> Self {
+        if !(config.requests_per_second.is_finite() && config.requests_per_second > 0.0) {
+            let default = RateLimitConfig::default().requests_per_second;
+            log::warn!(
+                "Invalid requests_per_second {}, falling back to {}",
+                config.requests_per_second,
+                default
+            );
+            config.requests_per_second = default;
+        }
+        let state = State {
+            tokens: config.burst as f64,
+            last_refill: Instant::now(),
+            blocked_until: None,
+            consecutive_server_errors: 0,
+            open_until: None,
+        };
+        Self {
+            config,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Waits until a request is allowed to be sent.
+    /// Returns an error without waiting while the circuit breaker is open.
+    pub(crate) async fn acquire(&self) -> Result<()> {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                if let Some(open_until) = state.open_until {
+                    if now < open_until {
+                        return Err(AtCoderError::CircuitOpen(open_until - now));
+                    }
+                    state.open_until = None;
+                }
+
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.config.requests_per_second)
+                    .min(self.config.burst.max(1) as f64);
+                state.last_refill = now;
+
+                match state.blocked_until {
+                    Some(blocked_until) if now < blocked_until => blocked_until - now,
+                    _ if state.tokens >= 1.0 => {
+                        state.tokens -= 1.0;
+                        return Ok(());
+                    }
+                    _ => Duration::from_secs_f64(
+                        (1.0 - state.tokens) / self.config.requests_per_second,
+                    ),
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Records the status of a response, and returns how long to wait before retrying
+    /// if the request should be retried.
+    pub(crate) fn record(
+        &self,
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        if status.is_server_error() {
+            state.consecutive_server_errors += 1;
+            if state.consecutive_server_errors >= self.config.circuit_breaker_threshold {
+                log::error!(
+                    "Opening the circuit breaker after {} consecutive server errors",
+                    state.consecutive_server_errors
+                );
+                state.consecutive_server_errors = 0;
+                state.open_until = Some(Instant::now() + self.config.circuit_breaker_cooldown);
+            }
+        } else {
+            state.consecutive_server_errors = 0;
+        }
+
+        if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+            return None;
+        }
+        let wait = retry_after.unwrap_or_else(|| self.backoff(attempt));
+        if let Some(retry_after) = retry_after {
+            // Retry-After is a request to the whole client, not only to this request.
+            let blocked_until = Instant::now() + retry_after;
+            state.blocked_until = Some(
+                state
+                    .blocked_until
+                    .map_or(blocked_until, |b| b.max(blocked_until)),
+            );
+        }
+        Some(wait)
+    }
+
+    /// Exponential backoff with full jitter.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let max = self
+            .config
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_backoff);
+        max.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+pub(crate) fn parse_retry_after(response: &HttpResponse) -> Option<Duration> {
+    let seconds = response.header("retry-after")?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_token_bucket() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: 20.0,
+            burst: 2,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire().await.unwrap();
+        }
+        // The first 2 requests are the burst, and the rest wait 50ms each.
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests_per_second() {
+        for requests_per_second in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let limiter = RateLimiter::new(RateLimitConfig {
+                requests_per_second,
+                burst: 0,
+                ..Default::default()
+            });
+            assert_eq!(
+                limiter.config().requests_per_second,
+                RateLimitConfig::default().requests_per_second
+            );
+            limiter.acquire().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: 1000.0,
+            circuit_breaker_threshold: 2,
+            ..Default::default()
+        });
+        assert!(limiter.record(StatusCode::BAD_GATEWAY, None, 0).is_some());
+        limiter.acquire().await.unwrap();
+        assert!(limiter.record(StatusCode::BAD_GATEWAY, None, 0).is_some());
+        assert!(limiter.acquire().await.is_err());
+    }
+
+    #[test]
+    fn test_record() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        assert_eq!(limiter.record(StatusCode::OK, None, 0), None);
+        assert_eq!(limiter.record(StatusCode::NOT_FOUND, None, 0), None);
+        assert_eq!(
+            limiter.record(
+                StatusCode::TOO_MANY_REQUESTS,
+                Some(Duration::from_secs(3)),
+                0
+            ),
+            Some(Duration::from_secs(3))
+        );
+        assert!(limiter.state.lock().unwrap().blocked_until.is_some());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            ..Default::default()
+        });
+        for attempt in 0..40 {
+            assert!(limiter.backoff(attempt) <= Duration::from_secs(10));
+        }
+        assert!(limiter.backoff(1) <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut response = HttpResponse {
+            status: 429,
+            headers: vec![],
+            body: String::new(),
+        };
+        assert_eq!(parse_retry_after(&response), None);
+        response
+            .headers
+            .push(("Retry-After".to_string(), "120".to_string()));
+        assert_eq!(parse_retry_after(&response), Some(Duration::from_secs(120)));
+    }
+}
@@ -0,0 +1,391 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{HeaderValue, SET_COOKIE};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub form: Vec<(String, String)>,
+}
+
+impl HttpRequest {
+    pub fn get<S: ToString>(url: S, accept: &str) -> Self {
+        Self {
+            method: HttpMethod::Get,
+            url: url.to_string(),
+            headers: vec![("accept".to_string(), accept.to_string())],
+            form: Vec::new(),
+        }
+    }
+
+    pub fn post_form<S: ToString>(url: S, form: &[(&str, &str)]) -> Self {
+        Self {
+            method: HttpMethod::Post,
+            url: url.to_string(),
+            headers: Vec::new(),
+            form: form
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends HTTP requests on behalf of `AtCoderClient`.
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    async fn execute(&self, request: &HttpRequest) -> Result<HttpResponse>;
+
+    /// Cookies of the session, if the transport keeps any.
+    fn cookies(&self) -> Option<Arc<SessionCookies>> {
+        None
+    }
+}
+
+/// Cookie jar which can be serialized to persist a login session.
+/// Cookies are kept per host, and attributes other than expiry are ignored.
+#[derive(Default)]
+pub struct SessionCookies {
+    cookies: Mutex<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl SessionCookies {
+    pub fn export(&self) -> Result<String> {
+        let cookies = self.cookies.lock().unwrap();
+        Ok(serde_json::to_string(&*cookies)?)
+    }
+
+    pub fn restore(&self, exported: &str) -> Result<()> {
+        let restored = serde_json::from_str(exported).context("Failed to parse the session.")?;
+        *self.cookies.lock().unwrap() = restored;
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.cookies.lock().unwrap().clear();
+    }
+}
+
+impl reqwest::cookie::CookieStore for SessionCookies {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let host = match url.host_str() {
+            Some(host) => host.to_string(),
+            None => return,
+        };
+        let mut cookies = self.cookies.lock().unwrap();
+        let jar = cookies.entry(host).or_default();
+        for header in cookie_headers {
+            let header = match header.to_str() {
+                Ok(header) => header,
+                Err(_) => continue,
+            };
+            let mut attributes = header.split(';').map(str::trim);
+            let (name, value) = match attributes.next().and_then(|pair| pair.split_once('=')) {
+                Some(pair) => pair,
+                None => continue,
+            };
+            let expired = attributes.any(|attribute| {
+                attribute
+                    .split_once('=')
+                    .filter(|(key, _)| key.eq_ignore_ascii_case("max-age"))
+                    .and_then(|(_, age)| age.parse::<i64>().ok())
+                    .map_or(false, |age| age <= 0)
+            });
+            if expired {
+                jar.remove(name);
+            } else {
+                jar.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let cookies = self.cookies.lock().unwrap();
+        let jar = cookies.get(url.host_str()?)?;
+        if jar.is_empty() {
+            return None;
+        }
+        let header = jar
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+/// Sends requests to the real site, keeping the session cookies.
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    cookies: Arc<SessionCookies>,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Result<Self> {
+        let cookies = Arc::new(SessionCookies::default());
+        let client = reqwest::Client::builder()
+            .cookie_provider(cookies.clone())
+            .gzip(true)
+            .build()?;
+        Ok(Self { client, cookies })
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn execute(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let mut builder = match request.method {
+            HttpMethod::Get => self.client.get(&request.url),
+            HttpMethod::Post => self.client.post(&request.url).form(&request.form),
+        };
+        for (key, value) in request.headers.iter() {
+            builder = builder.header(key, value);
+        }
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        // Cookies are kept by the cookie store, and shouldn't leak through the response.
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(key, _)| *key != SET_COOKIE)
+            .filter_map(|(key, value)| Some((key.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| anyhow!("Failed to parse HTTP body: {:?}", e))?;
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    fn cookies(&self) -> Option<Arc<SessionCookies>> {
+        Some(self.cookies.clone())
+    }
+}
+
+/// Response headers which are saved into fixtures. The others, e.g. cookies, are dropped.
+const RECORDED_HEADERS: [&str; 3] = ["content-type", "location", "retry-after"];
+
+/// Passes requests to the inner transport, and saves every response into `dir`
+/// so that it can be served by `ReplayTransport` later.
+/// Neither request forms nor response headers other than `RECORDED_HEADERS` are saved,
+/// so that fixtures never contain credentials or session cookies.
+pub struct RecordingTransport<T> {
+    inner: T,
+    dir: PathBuf,
+}
+
+impl<T: HttpTransport> RecordingTransport<T> {
+    pub fn new<P: AsRef<Path>>(inner: T, dir: P) -> Self {
+        Self {
+            inner,
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: HttpTransport> HttpTransport for RecordingTransport<T> {
+    async fn execute(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let response = self.inner.execute(request).await?;
+        let recorded = HttpResponse {
+            status: response.status,
+            headers: response
+                .headers
+                .iter()
+                .filter(|(key, _)| {
+                    RECORDED_HEADERS
+                        .iter()
+                        .any(|name| key.eq_ignore_ascii_case(name))
+                })
+                .cloned()
+                .collect(),
+            body: response.body.clone(),
+        };
+        let path = self.dir.join(fixture_name(request));
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(&path, serde_json::to_vec_pretty(&recorded)?)
+            .await
+            .with_context(|| format!("Failed to record {:?}", path))?;
+        Ok(response)
+    }
+
+    fn cookies(&self) -> Option<Arc<SessionCookies>> {
+        self.inner.cookies()
+    }
+}
+
+/// Serves responses recorded by `RecordingTransport` without any network access.
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReplayTransport {
+    async fn execute(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let path = self.dir.join(fixture_name(request));
+        let recorded = tokio::fs::read_to_string(&path).await.with_context(|| {
+            format!(
+                "No fixture of {:?} {} at {:?}",
+                request.method, request.url, path
+            )
+        })?;
+        let response = serde_json::from_str(&recorded)
+            .with_context(|| format!("Failed to parse fixture {:?}", path))?;
+        Ok(response)
+    }
+}
+
+/// Fixtures are keyed by method and URL only, so that a request is replayed whatever its form is.
+pub(crate) fn fixture_name(request: &HttpRequest) -> String {
+    let method = match request.method {
+        HttpMethod::Get => "get",
+        HttpMethod::Post => "post",
+    };
+    let url = request
+        .url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    format!("{}_{}.json", method, url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticTransport;
+
+    #[async_trait]
+    impl HttpTransport for StaticTransport {
+        async fn execute(&self, request: &HttpRequest) -> Result<HttpResponse> {
+            Ok(HttpResponse {
+                status: 200,
+                headers: vec![
+                    ("Retry-After".to_string(), "1".to_string()),
+                    (
+                        "Set-Cookie".to_string(),
+                        "REVEL_SESSION=abc; Path=/; HttpOnly".to_string(),
+                    ),
+                ],
+                body: request.url.clone(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_session_cookies() {
+        use reqwest::cookie::CookieStore;
+
+        let url = Url::parse("https://atcoder.jp/login").unwrap();
+        let cookies = SessionCookies::default();
+        assert!(CookieStore::cookies(&cookies, &url).is_none());
+
+        let headers = [
+            HeaderValue::from_static("REVEL_SESSION=abc; Path=/; HttpOnly"),
+            HeaderValue::from_static("REVEL_FLASH=; Max-Age=0"),
+            HeaderValue::from_static("language=ja; Path=/"),
+        ];
+        cookies.set_cookies(&mut headers.iter(), &url);
+        let exported = cookies.export().unwrap();
+
+        let restored = SessionCookies::default();
+        restored.restore(&exported).unwrap();
+        assert_eq!(
+            CookieStore::cookies(&restored, &url).unwrap(),
+            "REVEL_SESSION=abc; language=ja"
+        );
+        let other = Url::parse("https://kenkoooo.com/atcoder/").unwrap();
+        assert!(CookieStore::cookies(&restored, &other).is_none());
+
+        let headers = [HeaderValue::from_static("REVEL_SESSION=; Max-Age=-1")];
+        restored.set_cookies(&mut headers.iter(), &url);
+        assert_eq!(
+            CookieStore::cookies(&restored, &url).unwrap(),
+            "language=ja"
+        );
+    }
+
+    #[test]
+    fn test_fixture_name() {
+        let request = HttpRequest::get("https://atcoder.jp/contests/?lang=ja", "text/html");
+        assert_eq!(
+            fixture_name(&request),
+            "get_atcoder_jp_contests__lang_ja.json"
+        );
+        let request = HttpRequest::post_form("https://atcoder.jp/login", &[("password", "p")]);
+        assert_eq!(fixture_name(&request), "post_atcoder_jp_login.json");
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("atcoder-client-{}", std::process::id()));
+        let request = HttpRequest::get("https://atcoder.jp/contests/abc107", "text/html");
+
+        let recording = RecordingTransport::new(StaticTransport, &dir);
+        let recorded = recording.execute(&request).await.unwrap();
+
+        assert!(recorded.header("set-cookie").is_some());
+
+        let fixture = std::fs::read_to_string(dir.join(fixture_name(&request))).unwrap();
+        assert!(!fixture.to_lowercase().contains("cookie"));
+        assert!(!fixture.contains("REVEL_SESSION"));
+
+        let replay = ReplayTransport::new(&dir);
+        let replayed = replay.execute(&request).await.unwrap();
+        assert_eq!(recorded.body, replayed.body);
+        assert_eq!(replayed.header("retry-after"), Some("1"));
+        assert_eq!(replayed.header("set-cookie"), None);
+
+        let missing = HttpRequest::get("https://atcoder.jp/contests/abc108", "text/html");
+        assert!(replay.execute(&missing).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
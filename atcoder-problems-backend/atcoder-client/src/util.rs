@@ -1,36 +1,74 @@
-use anyhow::{anyhow, Context, Result};
+use crate::error::{AtCoderError, Result};
+use crate::rate_limiter::{parse_retry_after, RateLimiter};
+use crate::transport::{HttpRequest, HttpResponse, HttpTransport};
+use reqwest::StatusCode;
 
-use reqwest::Client;
-use serde::de::DeserializeOwned;
+/// Sends a request through the rate limiter, retrying on connection errors, 429 and 5xx.
+pub(crate) async fn send(
+    request: &HttpRequest,
+    transport: &dyn HttpTransport,
+    limiter: &RateLimiter,
+) -> Result<HttpResponse> {
+    let max_retries = limiter.config().max_retries;
+    let mut attempt = 0;
+    loop {
+        limiter.acquire().await?;
+        match transport.execute(request).await {
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = parse_retry_after(&response);
+                match limiter.record(status, retry_after, attempt) {
+                    Some(wait) if attempt < max_retries => {
+                        log::warn!(
+                            "{} from {}, retrying in {:?} ...",
+                            status,
+                            request.url,
+                            wait
+                        );
+                        tokio::time::sleep(wait).await;
+                    }
+                    _ => return Ok(response),
+                }
+            }
+            Err(e) if attempt < max_retries => {
+                let wait = limiter.backoff(attempt);
+                log::warn!("Connection error: {:?}, retrying in {:?} ...", e, wait);
+                tokio::time::sleep(wait).await;
+            }
+            Err(e) => return Err(AtCoderError::Transport(e.context("Connection error"))),
+        }
+        attempt += 1;
+    }
+}
 
-pub(crate) async fn get_html(url: &str, client: &Client) -> Result<(String, reqwest::StatusCode)> {
-    let response = client
-        .get(url)
-        .header("accept", "text/html")
-        .send()
-        .await
-        .map_err(|e| anyhow!("Connection error: {:?}", e))?;
-    let status = response.status();
-    if !status.is_success() {
-        log::error!("{:?}", response);
+/// Sends a request, and turns an unsuccessful response into an error.
+pub(crate) async fn send_ok(
+    request: &HttpRequest,
+    transport: &dyn HttpTransport,
+    limiter: &RateLimiter,
+) -> Result<HttpResponse> {
+    let response = send(request, transport, limiter).await?;
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND => Err(AtCoderError::NotFound(request.url.clone())),
+        StatusCode::TOO_MANY_REQUESTS => Err(AtCoderError::RateLimited {
+            retry_after: parse_retry_after(&response),
+        }),
+        status => {
+            log::error!("{} from {}", status, request.url);
+            Err(AtCoderError::Http(status))
+        }
     }
-    let body = response
-        .text()
-        .await
-        .map_err(|e| anyhow!("Failed to parse HTTP body: {:?}", e))?;
-    Ok((body, status))
 }
 
-pub(crate) async fn get_json<T: DeserializeOwned>(url: &str, client: &Client) -> Result<T> {
-    client
-        .get(url)
-        .header("accept", "application/json")
-        .send()
-        .await
-        .with_context(|| format!("Failed to get json from {}", url))?
-        .json::<T>()
-        .await
-        .with_context(|| format!("Failed to parse json from {}", url))
+pub(crate) async fn get_html(
+    url: &str,
+    transport: &dyn HttpTransport,
+    limiter: &RateLimiter,
+) -> Result<String> {
+    let request = HttpRequest::get(url, "text/html");
+    let response = send_ok(&request, transport, limiter).await?;
+    Ok(response.body)
 }
 
 pub trait Problem {
@@ -0,0 +1,14 @@
+CREATE TABLE submission_details (
+  submission_id BIGINT NOT NULL,
+  compile_error TEXT,
+  PRIMARY KEY (submission_id)
+);
+
+CREATE TABLE submission_testcases (
+  submission_id   BIGINT NOT NULL,
+  case_name       VARCHAR(255) NOT NULL,
+  result          VARCHAR(255) NOT NULL,
+  execution_time  INT,
+  memory          INT,
+  PRIMARY KEY (submission_id, case_name)
+);
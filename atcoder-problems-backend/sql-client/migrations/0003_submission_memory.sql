@@ -0,0 +1,8 @@
+ALTER TABLE submissions ADD COLUMN memory INT;
+
+CREATE TABLE least_memory (
+  contest_id    VARCHAR(255)  NOT NULL,
+  problem_id    VARCHAR(255)  NOT NULL,
+  submission_id BIGINT  NOT NULL,
+  PRIMARY KEY (problem_id)
+);
@@ -0,0 +1,24 @@
+CREATE TABLE contest_standings (
+  contest_id      VARCHAR(255) NOT NULL,
+  user_id         VARCHAR(255) NOT NULL,
+  rank            INT NOT NULL,
+  is_rated        BOOLEAN NOT NULL,
+  old_rating      INT NOT NULL,
+  new_rating      INT NOT NULL,
+  score           DOUBLE PRECISION NOT NULL,
+  penalty         INT NOT NULL,
+  elapsed_second  BIGINT NOT NULL,
+  PRIMARY KEY (contest_id, user_id)
+);
+CREATE INDEX ON contest_standings (LOWER(user_id));
+
+CREATE TABLE contest_standings_task_results (
+  contest_id      VARCHAR(255) NOT NULL,
+  user_id         VARCHAR(255) NOT NULL,
+  problem_id      VARCHAR(255) NOT NULL,
+  score           DOUBLE PRECISION NOT NULL,
+  penalty         INT NOT NULL,
+  elapsed_second  BIGINT NOT NULL,
+  PRIMARY KEY (contest_id, user_id, problem_id)
+);
+CREATE INDEX ON contest_standings_task_results (problem_id);
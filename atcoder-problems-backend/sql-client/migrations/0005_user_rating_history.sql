@@ -0,0 +1,12 @@
+CREATE TABLE user_rating_history (
+  user_id           VARCHAR(255) NOT NULL,
+  contest_id        VARCHAR(255) NOT NULL,
+  is_rated          BOOLEAN NOT NULL,
+  rank              INT NOT NULL,
+  performance       INT NOT NULL,
+  old_rating        INT NOT NULL,
+  new_rating        INT NOT NULL,
+  end_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (user_id, contest_id)
+);
+CREATE INDEX ON user_rating_history (LOWER(user_id), end_epoch_second);
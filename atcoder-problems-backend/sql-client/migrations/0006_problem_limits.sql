@@ -0,0 +1,3 @@
+ALTER TABLE problems
+  ADD COLUMN time_limit_ms   INT,
+  ADD COLUMN memory_limit_kb INT;
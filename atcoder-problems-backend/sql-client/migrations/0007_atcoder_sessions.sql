@@ -0,0 +1,6 @@
+CREATE TABLE atcoder_sessions (
+  username              VARCHAR(255) NOT NULL,
+  session               TEXT NOT NULL,
+  updated_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (username)
+);
@@ -0,0 +1,5 @@
+ALTER TABLE contests
+  ADD COLUMN category   VARCHAR(255) NOT NULL DEFAULT 'Other',
+  ADD COLUMN rated_min  INT,
+  ADD COLUMN rated_max  INT;
+CREATE INDEX ON contests (category);
@@ -0,0 +1,10 @@
+CREATE TABLE hidden_contests (
+  id                    VARCHAR(255) NOT NULL,
+  start_epoch_second    BIGINT       NOT NULL,
+  duration_second       BIGINT       NOT NULL,
+  title                 VARCHAR(255) NOT NULL,
+  rate_change           VARCHAR(255) NOT NULL,
+  PRIMARY KEY (id)
+);
+
+ALTER TABLE internal_users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
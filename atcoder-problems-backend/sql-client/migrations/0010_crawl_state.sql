@@ -0,0 +1,8 @@
+CREATE TABLE crawl_state (
+  crawler               VARCHAR(255) NOT NULL,
+  contest_id            VARCHAR(255) NOT NULL,
+  last_page             INTEGER NOT NULL,
+  max_page              INTEGER NOT NULL,
+  updated_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (crawler, contest_id)
+);
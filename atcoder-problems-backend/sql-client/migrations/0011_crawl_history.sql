@@ -0,0 +1,6 @@
+CREATE TABLE crawl_history (
+  crawler                    VARCHAR(255) NOT NULL,
+  contest_id                 VARCHAR(255) NOT NULL,
+  last_crawled_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (crawler, contest_id)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE crawl_requests (
+  id                      BIGSERIAL NOT NULL,
+  atcoder_user_id         VARCHAR(255) NOT NULL,
+  status                  VARCHAR(255) NOT NULL DEFAULT 'queued',
+  requested_epoch_second  BIGINT NOT NULL,
+  started_epoch_second    BIGINT DEFAULT NULL,
+  finished_epoch_second   BIGINT DEFAULT NULL,
+  error                   TEXT DEFAULT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON crawl_requests (LOWER(atcoder_user_id), id);
+CREATE INDEX ON crawl_requests (status, id);
@@ -0,0 +1,10 @@
+CREATE TABLE failed_pages (
+  contest_id                 VARCHAR(255) NOT NULL,
+  page                       INTEGER NOT NULL,
+  crawler                    VARCHAR(255) NOT NULL,
+  error                      TEXT NOT NULL,
+  attempts                   INTEGER NOT NULL,
+  first_failed_epoch_second  BIGINT NOT NULL,
+  last_failed_epoch_second   BIGINT NOT NULL,
+  PRIMARY KEY (contest_id, page)
+);
@@ -0,0 +1,17 @@
+CREATE INDEX ON submissions (contest_id);
+
+CREATE TABLE submission_history (
+  id                     BIGSERIAL NOT NULL,
+  submission_id          BIGINT NOT NULL,
+  kind                   VARCHAR(255) NOT NULL,
+  contest_id             VARCHAR(255) NOT NULL,
+  problem_id             VARCHAR(255) NOT NULL,
+  user_id                VARCHAR(255) NOT NULL,
+  old_result             VARCHAR(255) NOT NULL,
+  new_result             VARCHAR(255),
+  old_point              DOUBLE PRECISION NOT NULL,
+  new_point              DOUBLE PRECISION,
+  detected_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON submission_history (submission_id);
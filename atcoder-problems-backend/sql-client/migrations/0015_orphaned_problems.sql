@@ -0,0 +1,7 @@
+CREATE TABLE orphaned_problems (
+  contest_id                   VARCHAR(255) NOT NULL,
+  problem_id                   VARCHAR(255) NOT NULL,
+  first_detected_epoch_second  BIGINT NOT NULL,
+  last_detected_epoch_second   BIGINT NOT NULL,
+  PRIMARY KEY (contest_id, problem_id)
+);
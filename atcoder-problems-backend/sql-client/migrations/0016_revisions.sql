@@ -0,0 +1,21 @@
+CREATE TABLE contest_revisions (
+  id                     BIGSERIAL NOT NULL,
+  contest_id             VARCHAR(255) NOT NULL,
+  field                  VARCHAR(255) NOT NULL,
+  old_value              TEXT,
+  new_value              TEXT,
+  detected_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON contest_revisions (contest_id);
+
+CREATE TABLE problem_revisions (
+  id                     BIGSERIAL NOT NULL,
+  problem_id             VARCHAR(255) NOT NULL,
+  field                  VARCHAR(255) NOT NULL,
+  old_value              TEXT,
+  new_value              TEXT,
+  detected_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON problem_revisions (problem_id);
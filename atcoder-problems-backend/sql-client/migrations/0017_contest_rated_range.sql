@@ -0,0 +1,8 @@
+-- A rated contest has a lower bound, which is 0 if the range has none,
+-- so that rated contests are told by `rated_min` alone.
+UPDATE contests SET
+  rated_min = COALESCE(NULLIF(TRIM(SPLIT_PART(rate_change, '~', 1)), '')::INT, 0),
+  rated_max = NULLIF(TRIM(SPLIT_PART(rate_change, '~', 2)), '')::INT
+WHERE rate_change ~ '^\s*\d*\s*~\s*\d*\s*$';
+UPDATE contests SET rated_min = 0, rated_max = NULL WHERE TRIM(rate_change) = 'All';
+CREATE INDEX ON contests (rated_min);
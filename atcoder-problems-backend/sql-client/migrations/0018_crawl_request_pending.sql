@@ -0,0 +1,12 @@
+-- A user has at most one pending request, so the duplicated ones are marked as failed.
+UPDATE crawl_requests SET
+  status = 'failed',
+  finished_epoch_second = requested_epoch_second,
+  error = 'Duplicated request'
+WHERE status IN ('queued', 'running')
+AND id NOT IN (
+  SELECT MIN(id) FROM crawl_requests
+  WHERE status IN ('queued', 'running')
+  GROUP BY LOWER(atcoder_user_id)
+);
+CREATE UNIQUE INDEX ON crawl_requests (LOWER(atcoder_user_id)) WHERE status IN ('queued', 'running');
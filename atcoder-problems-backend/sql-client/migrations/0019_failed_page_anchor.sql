@@ -0,0 +1,3 @@
+-- Pages shift as new submissions are made, so a failed page is anchored to
+-- the smallest submission id of the page before it, which is NULL if it's not known.
+ALTER TABLE failed_pages ADD COLUMN before_id BIGINT DEFAULT NULL;
@@ -0,0 +1,18 @@
+-- Submissions which have disappeared from AtCoder are moved here instead of being deleted,
+-- so that the removal can be audited and undone.
+CREATE TABLE vanished_submissions (
+  id                     BIGINT NOT NULL,
+  epoch_second           BIGINT NOT NULL,
+  problem_id             VARCHAR(255) NOT NULL,
+  contest_id             VARCHAR(255) NOT NULL,
+  user_id                VARCHAR(255) NOT NULL,
+  language               VARCHAR(255) NOT NULL,
+  point                  DOUBLE PRECISION NOT NULL,
+  length                 INT NOT NULL,
+  result                 VARCHAR(255) NOT NULL,
+  execution_time         INT,
+  memory                 INT,
+  vanished_epoch_second  BIGINT NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX ON vanished_submissions (contest_id);
@@ -0,0 +1,3 @@
+-- How many times the problems of an orphan have been crawled without resolving it,
+-- so that the orphans which are never resolved stop being crawled again.
+ALTER TABLE orphaned_problems ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
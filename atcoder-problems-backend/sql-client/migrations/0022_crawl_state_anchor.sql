@@ -0,0 +1,3 @@
+-- Pages shift as new submissions are made, so the progress of a crawl is anchored to
+-- the smallest submission id crawled so far, which is NULL if it's not known.
+ALTER TABLE crawl_state ADD COLUMN before_id BIGINT DEFAULT NULL;
@@ -2,7 +2,7 @@ use crate::models::{Submission, UserProblemCount};
 use crate::{PgPool, MAX_INSERT_ROWS};
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::Row;
+use sqlx::{PgConnection, Row};
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Range;
 
@@ -84,38 +84,49 @@ impl AcceptedCountClient for PgPool {
     }
 
     async fn update_accepted_count(&self, submissions: &[Submission]) -> Result<()> {
-        let accepted_count = submissions
-            .iter()
-            .map(|s| (s.user_id.as_str(), s.problem_id.as_str()))
-            .fold(BTreeMap::new(), |mut map, (user_id, problem_id)| {
-                map.entry(user_id)
-                    .or_insert_with(BTreeSet::new)
-                    .insert(problem_id);
-                map
-            })
-            .into_iter()
-            .map(|(user_id, set)| (user_id, set.len() as i32))
-            .collect::<Vec<_>>();
+        let accepted_count = count_accepted(submissions);
+        let mut conn = self.acquire().await?;
+        save_accepted_count(&mut conn, &accepted_count).await
+    }
+}
 
-        for chunk in accepted_count.chunks(MAX_INSERT_ROWS) {
-            let (user_ids, ac_counts): (Vec<&str>, Vec<i32>) = chunk.iter().copied().unzip();
-            sqlx::query(
-                r"
-                INSERT INTO accepted_count (user_id, problem_count)
-                VALUES (
-                    UNNEST($1::VARCHAR(255)[]),
-                    UNNEST($2::INTEGER[])
-                )
-                ON CONFLICT (user_id)
-                DO UPDATE SET problem_count = EXCLUDED.problem_count
-                ",
-            )
-            .bind(user_ids)
-            .bind(ac_counts)
-            .execute(self)
-            .await?;
-        }
+/// Counts the distinct problems each user has solved.
+pub(crate) fn count_accepted(submissions: &[Submission]) -> Vec<(&str, i32)> {
+    submissions
+        .iter()
+        .map(|s| (s.user_id.as_str(), s.problem_id.as_str()))
+        .fold(BTreeMap::new(), |mut map, (user_id, problem_id)| {
+            map.entry(user_id)
+                .or_insert_with(BTreeSet::new)
+                .insert(problem_id);
+            map
+        })
+        .into_iter()
+        .map(|(user_id, set)| (user_id, set.len() as i32))
+        .collect()
+}
 
-        Ok(())
+pub(crate) async fn save_accepted_count(
+    conn: &mut PgConnection,
+    accepted_count: &[(&str, i32)],
+) -> Result<()> {
+    for chunk in accepted_count.chunks(MAX_INSERT_ROWS) {
+        let (user_ids, ac_counts): (Vec<&str>, Vec<i32>) = chunk.iter().copied().unzip();
+        sqlx::query(
+            r"
+            INSERT INTO accepted_count (user_id, problem_count)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::INTEGER[])
+            )
+            ON CONFLICT (user_id)
+            DO UPDATE SET problem_count = EXCLUDED.problem_count
+            ",
+        )
+        .bind(user_ids)
+        .bind(ac_counts)
+        .execute(&mut *conn)
+        .await?;
     }
+    Ok(())
 }
@@ -0,0 +1,152 @@
+use crate::models::CrawlRequest;
+use crate::PgPool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+
+pub const CRAWL_REQUEST_QUEUED: &str = "queued";
+pub const CRAWL_REQUEST_RUNNING: &str = "running";
+pub const CRAWL_REQUEST_FINISHED: &str = "finished";
+pub const CRAWL_REQUEST_FAILED: &str = "failed";
+
+/// Queue of requests to crawl the submissions of users on demand.
+#[async_trait]
+pub trait CrawlRequestClient: Sync {
+    /// Enqueues a request, or returns the pending one if the user already has it.
+    async fn insert_crawl_request(&self, _atcoder_user_id: &str) -> Result<CrawlRequest> {
+        Err(anyhow!("insert_crawl_request is not supported"))
+    }
+    async fn get_latest_crawl_request(
+        &self,
+        _atcoder_user_id: &str,
+    ) -> Result<Option<CrawlRequest>> {
+        Err(anyhow!("get_latest_crawl_request is not supported"))
+    }
+
+    /// Takes the oldest queued request and marks it as running.
+    async fn pop_crawl_request(&self) -> Result<Option<CrawlRequest>>;
+
+    /// Puts the requests which have been running for longer than `timeout_second` back in the queue,
+    /// e.g. the ones left by a worker which has crashed, and returns how many they are.
+    async fn requeue_stale_crawl_requests(&self, timeout_second: i64) -> Result<usize>;
+
+    /// Marks a request as finished, or as failed if `error` is given.
+    async fn finish_crawl_request(&self, id: i64, error: Option<&str>) -> Result<()>;
+}
+
+#[async_trait]
+impl CrawlRequestClient for PgPool {
+    async fn insert_crawl_request(&self, atcoder_user_id: &str) -> Result<CrawlRequest> {
+        // The conflict target matches the unique index of the pending requests.
+        let inserted = sqlx::query_as(
+            r"
+            INSERT INTO crawl_requests (atcoder_user_id, status, requested_epoch_second)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (LOWER(atcoder_user_id)) WHERE status IN ('queued', 'running')
+            DO NOTHING
+            RETURNING *
+            ",
+        )
+        .bind(atcoder_user_id)
+        .bind(CRAWL_REQUEST_QUEUED)
+        .bind(Utc::now().timestamp())
+        .fetch_optional(self)
+        .await?;
+        if let Some(request) = inserted {
+            return Ok(request);
+        }
+
+        let pending = sqlx::query_as(
+            r"
+            SELECT * FROM crawl_requests
+            WHERE LOWER(atcoder_user_id) = LOWER($1)
+            AND status IN ($2, $3)
+            ",
+        )
+        .bind(atcoder_user_id)
+        .bind(CRAWL_REQUEST_QUEUED)
+        .bind(CRAWL_REQUEST_RUNNING)
+        .fetch_one(self)
+        .await?;
+        Ok(pending)
+    }
+
+    async fn get_latest_crawl_request(
+        &self,
+        atcoder_user_id: &str,
+    ) -> Result<Option<CrawlRequest>> {
+        let request = sqlx::query_as(
+            r"
+            SELECT * FROM crawl_requests
+            WHERE LOWER(atcoder_user_id) = LOWER($1)
+            ORDER BY id DESC
+            LIMIT 1
+            ",
+        )
+        .bind(atcoder_user_id)
+        .fetch_optional(self)
+        .await?;
+        Ok(request)
+    }
+
+    async fn pop_crawl_request(&self) -> Result<Option<CrawlRequest>> {
+        let request = sqlx::query_as(
+            r"
+            UPDATE crawl_requests
+            SET status = $1, started_epoch_second = $2
+            WHERE id = (
+                SELECT id FROM crawl_requests
+                WHERE status = $3
+                ORDER BY id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            ",
+        )
+        .bind(CRAWL_REQUEST_RUNNING)
+        .bind(Utc::now().timestamp())
+        .bind(CRAWL_REQUEST_QUEUED)
+        .fetch_optional(self)
+        .await?;
+        Ok(request)
+    }
+
+    async fn requeue_stale_crawl_requests(&self, timeout_second: i64) -> Result<usize> {
+        let result = sqlx::query(
+            r"
+            UPDATE crawl_requests
+            SET status = $1, started_epoch_second = NULL
+            WHERE status = $2
+            AND started_epoch_second < $3
+            ",
+        )
+        .bind(CRAWL_REQUEST_QUEUED)
+        .bind(CRAWL_REQUEST_RUNNING)
+        .bind(Utc::now().timestamp() - timeout_second)
+        .execute(self)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn finish_crawl_request(&self, id: i64, error: Option<&str>) -> Result<()> {
+        let status = match error {
+            Some(_) => CRAWL_REQUEST_FAILED,
+            None => CRAWL_REQUEST_FINISHED,
+        };
+        sqlx::query(
+            r"
+            UPDATE crawl_requests
+            SET status = $1, finished_epoch_second = $2, error = $3
+            WHERE id = $4
+            ",
+        )
+        .bind(status)
+        .bind(Utc::now().timestamp())
+        .bind(error)
+        .bind(id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+}
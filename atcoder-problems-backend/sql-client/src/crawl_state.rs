@@ -0,0 +1,135 @@
+use crate::models::CrawlState;
+use crate::PgPool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+
+/// Stores the last completed page of each contest per crawler and the smallest submission id
+/// crawled so far, so that a crawler can resume from there after a crash or a redeploy.
+/// Also stores when each contest was crawled last, so that crawlers can visit stale contests first.
+#[async_trait]
+pub trait CrawlStateClient: Sync {
+    async fn load_crawl_states(&self, crawler: &str) -> Result<Vec<CrawlState>>;
+    async fn save_crawl_state(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        last_page: u32,
+        max_page: u32,
+        before_id: Option<i64>,
+    ) -> Result<()>;
+    async fn delete_crawl_state(&self, crawler: &str, contest_id: &str) -> Result<()>;
+
+    /// Returns pairs of a contest id and when it was crawled last.
+    async fn load_last_crawled(&self, _crawler: &str) -> Result<Vec<(String, i64)>> {
+        Err(anyhow!("load_last_crawled is not supported"))
+    }
+    async fn save_last_crawled(
+        &self,
+        _crawler: &str,
+        _contest_id: &str,
+        _epoch_second: i64,
+    ) -> Result<()> {
+        Err(anyhow!("save_last_crawled is not supported"))
+    }
+
+    async fn load_crawl_state(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+    ) -> Result<Option<CrawlState>> {
+        let state = self
+            .load_crawl_states(crawler)
+            .await?
+            .into_iter()
+            .find(|state| state.contest_id == contest_id);
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl CrawlStateClient for PgPool {
+    async fn load_crawl_states(&self, crawler: &str) -> Result<Vec<CrawlState>> {
+        let states = sqlx::query_as("SELECT * FROM crawl_state WHERE crawler = $1")
+            .bind(crawler)
+            .fetch_all(self)
+            .await?;
+        Ok(states)
+    }
+
+    async fn save_crawl_state(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        last_page: u32,
+        max_page: u32,
+        before_id: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO crawl_state
+                (crawler, contest_id, last_page, max_page, updated_epoch_second, before_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (crawler, contest_id)
+            DO UPDATE SET
+                last_page = EXCLUDED.last_page,
+                max_page = EXCLUDED.max_page,
+                updated_epoch_second = EXCLUDED.updated_epoch_second,
+                before_id = EXCLUDED.before_id
+            ",
+        )
+        .bind(crawler)
+        .bind(contest_id)
+        .bind(last_page as i32)
+        .bind(max_page as i32)
+        .bind(Utc::now().timestamp())
+        .bind(before_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_crawl_state(&self, crawler: &str, contest_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM crawl_state WHERE crawler = $1 AND contest_id = $2")
+            .bind(crawler)
+            .bind(contest_id)
+            .execute(self)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_last_crawled(&self, crawler: &str) -> Result<Vec<(String, i64)>> {
+        let last_crawled = sqlx::query_as(
+            r"
+            SELECT contest_id, last_crawled_epoch_second FROM crawl_history
+            WHERE crawler = $1
+            ",
+        )
+        .bind(crawler)
+        .fetch_all(self)
+        .await?;
+        Ok(last_crawled)
+    }
+
+    async fn save_last_crawled(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        epoch_second: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO crawl_history (crawler, contest_id, last_crawled_epoch_second)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (crawler, contest_id)
+            DO UPDATE SET last_crawled_epoch_second = EXCLUDED.last_crawled_epoch_second
+            ",
+        )
+        .bind(crawler)
+        .bind(contest_id)
+        .bind(epoch_second)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+}
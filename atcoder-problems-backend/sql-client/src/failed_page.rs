@@ -0,0 +1,134 @@
+use crate::models::FailedPage;
+use crate::PgPool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+
+/// Dead-letter queue of submission pages which failed to be fetched,
+/// so that they are retried later instead of being lost.
+#[async_trait]
+pub trait FailedPageClient: Sync {
+    /// Records a failure of a page, counting up its attempts if it has failed before.
+    /// The crawler and the anchor of the first failure are kept.
+    async fn record_failed_page(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        page: u32,
+        before_id: Option<i64>,
+        error: &str,
+    ) -> Result<()>;
+
+    /// Merges failed pages recorded somewhere else, e.g. by the JSON Lines sink.
+    /// The larger attempts, the earlier first failure and the later last failure are kept,
+    /// and so are the crawler and the anchor if the page has failed here before.
+    async fn merge_failed_pages(&self, _pages: &[FailedPage]) -> Result<()> {
+        Err(anyhow!("merge_failed_pages is not supported"))
+    }
+
+    /// Returns all the failed pages in the order of their last failures, the oldest first.
+    async fn load_failed_pages(&self) -> Result<Vec<FailedPage>> {
+        Err(anyhow!("load_failed_pages is not supported"))
+    }
+    async fn delete_failed_page(&self, _contest_id: &str, _page: u32) -> Result<()> {
+        Err(anyhow!("delete_failed_page is not supported"))
+    }
+}
+
+#[async_trait]
+impl FailedPageClient for PgPool {
+    async fn record_failed_page(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        page: u32,
+        before_id: Option<i64>,
+        error: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO failed_pages
+                (contest_id, page, crawler, error, attempts, first_failed_epoch_second, last_failed_epoch_second, before_id)
+            VALUES ($1, $2, $3, $4, 1, $5, $5, $6)
+            ON CONFLICT (contest_id, page)
+            DO UPDATE SET
+                error = EXCLUDED.error,
+                before_id = COALESCE(failed_pages.before_id, EXCLUDED.before_id),
+                attempts = failed_pages.attempts + 1,
+                last_failed_epoch_second = EXCLUDED.last_failed_epoch_second
+            ",
+        )
+        .bind(contest_id)
+        .bind(page as i32)
+        .bind(crawler)
+        .bind(error)
+        .bind(Utc::now().timestamp())
+        .bind(before_id)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn merge_failed_pages(&self, pages: &[FailedPage]) -> Result<()> {
+        let mut tx = self.begin().await?;
+        for page in pages {
+            sqlx::query(
+                r"
+                INSERT INTO failed_pages
+                    (contest_id, page, crawler, error, attempts, first_failed_epoch_second, last_failed_epoch_second, before_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (contest_id, page)
+                DO UPDATE SET
+                    error = CASE
+                        WHEN EXCLUDED.last_failed_epoch_second > failed_pages.last_failed_epoch_second
+                        THEN EXCLUDED.error
+                        ELSE failed_pages.error
+                    END,
+                    before_id = COALESCE(failed_pages.before_id, EXCLUDED.before_id),
+                    attempts = GREATEST(failed_pages.attempts, EXCLUDED.attempts),
+                    first_failed_epoch_second = LEAST(
+                        failed_pages.first_failed_epoch_second,
+                        EXCLUDED.first_failed_epoch_second
+                    ),
+                    last_failed_epoch_second = GREATEST(
+                        failed_pages.last_failed_epoch_second,
+                        EXCLUDED.last_failed_epoch_second
+                    )
+                ",
+            )
+            .bind(&page.contest_id)
+            .bind(page.page)
+            .bind(&page.crawler)
+            .bind(&page.error)
+            .bind(page.attempts)
+            .bind(page.first_failed_epoch_second)
+            .bind(page.last_failed_epoch_second)
+            .bind(page.before_id)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_failed_pages(&self) -> Result<Vec<FailedPage>> {
+        let pages = sqlx::query_as(
+            r"
+            SELECT * FROM failed_pages
+            ORDER BY last_failed_epoch_second, contest_id, page
+            ",
+        )
+        .fetch_all(self)
+        .await?;
+        Ok(pages)
+    }
+
+    async fn delete_failed_page(&self, contest_id: &str, page: u32) -> Result<()> {
+        sqlx::query("DELETE FROM failed_pages WHERE contest_id = $1 AND page = $2")
+            .bind(contest_id)
+            .bind(page as i32)
+            .execute(self)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::models::HiddenContest;
+use crate::PgPool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait HiddenContestClient: Sync {
+    async fn load_hidden_contests(&self) -> Result<Vec<HiddenContest>> {
+        Err(anyhow!("load_hidden_contests is not supported"))
+    }
+    async fn update_hidden_contests(&self, _contests: &[HiddenContest]) -> Result<()> {
+        Err(anyhow!("update_hidden_contests is not supported"))
+    }
+    async fn delete_hidden_contest(&self, _contest_id: &str) -> Result<()> {
+        Err(anyhow!("delete_hidden_contest is not supported"))
+    }
+}
+
+#[async_trait]
+impl HiddenContestClient for PgPool {
+    async fn load_hidden_contests(&self) -> Result<Vec<HiddenContest>> {
+        let contests = sqlx::query_as(
+            r"
+            SELECT id, start_epoch_second, duration_second, title, rate_change
+            FROM hidden_contests
+            ORDER BY id
+            ",
+        )
+        .fetch_all(self)
+        .await?;
+        Ok(contests)
+    }
+
+    async fn update_hidden_contests(&self, contests: &[HiddenContest]) -> Result<()> {
+        let (ids, start_epoch_seconds, duration_seconds, titles, rate_changes) =
+            contests.iter().fold(
+                (vec![], vec![], vec![], vec![], vec![]),
+                |(
+                    mut ids,
+                    mut start_epoch_seconds,
+                    mut duration_seconds,
+                    mut titles,
+                    mut rate_changes,
+                ),
+                 cur| {
+                    ids.push(cur.id.as_str());
+                    start_epoch_seconds.push(cur.start_epoch_second);
+                    duration_seconds.push(cur.duration_second);
+                    titles.push(cur.title.as_str());
+                    rate_changes.push(cur.rate_change.as_str());
+                    (
+                        ids,
+                        start_epoch_seconds,
+                        duration_seconds,
+                        titles,
+                        rate_changes,
+                    )
+                },
+            );
+
+        sqlx::query(
+            r"
+            INSERT INTO hidden_contests
+            (id, start_epoch_second, duration_second, title, rate_change)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::BIGINT[]),
+                UNNEST($3::BIGINT[]),
+                UNNEST($4::VARCHAR(255)[]),
+                UNNEST($5::VARCHAR(255)[])
+            )
+            ON CONFLICT (id)
+            DO UPDATE SET
+                start_epoch_second = EXCLUDED.start_epoch_second,
+                duration_second = EXCLUDED.duration_second,
+                title = EXCLUDED.title,
+                rate_change = EXCLUDED.rate_change
+            ",
+        )
+        .bind(ids)
+        .bind(start_epoch_seconds)
+        .bind(duration_seconds)
+        .bind(titles)
+        .bind(rate_changes)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_hidden_contest(&self, contest_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM hidden_contests WHERE id = $1")
+            .bind(contest_id)
+            .execute(self)
+            .await?;
+        Ok(())
+    }
+}
@@ -18,6 +18,7 @@ pub trait UserManager {
         atcoder_user_id: &str,
     ) -> Result<()>;
     async fn get_internal_user_info(&self, internal_user_id: &str) -> Result<InternalUserInfo>;
+    async fn is_admin(&self, internal_user_id: &str) -> Result<bool>;
 }
 
 #[async_trait]
@@ -68,4 +69,19 @@ impl UserManager for PgPool {
         .await?;
         Ok(res)
     }
+
+    /// Admins are granted by setting `is_admin` in the database directly.
+    async fn is_admin(&self, internal_user_id: &str) -> Result<bool> {
+        let is_admin = sqlx::query_scalar(
+            r"
+            SELECT is_admin
+            FROM internal_users
+            WHERE internal_user_id = $1
+            ",
+        )
+        .bind(internal_user_id)
+        .fetch_optional(self)
+        .await?;
+        Ok(is_admin.unwrap_or(false))
+    }
 }
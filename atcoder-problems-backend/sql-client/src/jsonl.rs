@@ -0,0 +1,657 @@
+use crate::contest_problem::ContestProblemClient;
+use crate::crawl_state::CrawlStateClient;
+use crate::failed_page::FailedPageClient;
+use crate::hidden_contest::HiddenContestClient;
+use crate::models::{
+    Contest, ContestProblem, ContestRevision, ContestStanding, ContestStandingTaskResult,
+    CrawlState, FailedPage, HiddenContest, OrphanedProblem, Problem, ProblemRevision, Submission,
+    SubmissionDetail, SubmissionTestcase, UserRatingHistory,
+};
+use crate::orphan::OrphanClient;
+use crate::rating_history::RatingHistoryClient;
+use crate::revision::RevisionClient;
+use crate::session::SessionClient;
+use crate::simple_client::SimpleClient;
+use crate::standings::StandingsClient;
+use crate::submission_client::{SubmissionClient, SubmissionRequest};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+pub const SUBMISSIONS_FILE: &str = "submissions.jsonl";
+pub const CONTESTS_FILE: &str = "contests.jsonl";
+pub const PROBLEMS_FILE: &str = "problems.jsonl";
+pub const FAILED_PAGES_FILE: &str = "failed_pages.jsonl";
+pub const CONTEST_PROBLEM_FILE: &str = "contest_problem.jsonl";
+pub const CONTEST_REVISIONS_FILE: &str = "contest_revisions.jsonl";
+pub const PROBLEM_REVISIONS_FILE: &str = "problem_revisions.jsonl";
+pub const RATING_HISTORY_FILE: &str = "user_rating_history.jsonl";
+pub const STANDINGS_FILE: &str = "contest_standings.jsonl";
+pub const STANDINGS_TASK_RESULTS_FILE: &str = "contest_standings_task_results.jsonl";
+const HIDDEN_CONTESTS_FILE: &str = "hidden_contests.jsonl";
+const CRAWL_STATES_FILE: &str = "crawl_states.jsonl";
+const LAST_CRAWLED_FILE: &str = "last_crawled.jsonl";
+
+#[derive(Serialize, Deserialize)]
+struct LastCrawled {
+    crawler: String,
+    contest_id: String,
+    epoch_second: i64,
+}
+
+/// Client which appends records to JSON Lines files in a directory instead of PostgreSQL,
+/// so that crawlers can run without a database.
+/// Each kind of records has a file of its own, e.g. `submissions.jsonl`,
+/// and the last line wins if there are lines of the same key.
+#[derive(Clone, Debug)]
+pub struct JsonlClient {
+    dir: PathBuf,
+}
+
+impl JsonlClient {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        Ok(Self { dir })
+    }
+
+    pub fn append<T: Serialize>(&self, file: &str, records: &[T]) -> Result<()> {
+        let path = self.dir.join(file);
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&lines))
+            .with_context(|| format!("Failed to append to {:?}", path))?;
+        Ok(())
+    }
+
+    /// Reads all the lines of a file, regarding a missing file as empty.
+    pub fn read<T: DeserializeOwned>(&self, file: &str) -> Result<Vec<T>> {
+        let path = self.dir.join(file);
+        let reader = match File::open(&path) {
+            Ok(file) => BufReader::new(file),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e).with_context(|| format!("Failed to open {:?}", path)),
+        };
+        let mut records = vec![];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.with_context(|| format!("Failed to read {:?}", path))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line)
+                .with_context(|| format!("Malformed line {} of {:?}", i + 1, path))?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Reads the last line of each key in the order of the keys.
+    pub fn read_latest<T, K, F>(&self, file: &str, key: F) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        let mut records = BTreeMap::new();
+        for record in self.read(file)? {
+            records.insert(key(&record), record);
+        }
+        Ok(records.into_values().collect())
+    }
+
+    /// Replaces the whole file, which is needed only for records which can be deleted.
+    fn rewrite<T: Serialize>(&self, file: &str, records: &[T]) -> Result<()> {
+        let path = self.dir.join(file);
+        let temporary = self.dir.join(format!("{}.tmp", file));
+        let _ = fs::remove_file(&temporary);
+        self.append(&format!("{}.tmp", file), records)?;
+        fs::rename(&temporary, &path).with_context(|| format!("Failed to replace {:?}", path))?;
+        Ok(())
+    }
+
+    fn load_submissions(&self) -> Result<Vec<Submission>> {
+        self.read_latest(SUBMISSIONS_FILE, |s: &Submission| s.id)
+    }
+
+    /// Orphans are not stored but found from the records whenever they are needed.
+    fn find_orphans<'a, I>(&self, submissions: I) -> Result<Vec<OrphanedProblem>>
+    where
+        I: IntoIterator<Item = &'a Submission>,
+    {
+        let problem_ids = self
+            .read_latest(PROBLEMS_FILE, |p: &Problem| p.id.clone())?
+            .into_iter()
+            .map(|p| p.id)
+            .collect::<BTreeSet<_>>();
+        let contest_ids = self
+            .read_latest(CONTESTS_FILE, |c: &Contest| c.id.clone())?
+            .into_iter()
+            .map(|c| c.id)
+            .collect::<BTreeSet<_>>();
+        let contest_problem = self
+            .read::<ContestProblem>(CONTEST_PROBLEM_FILE)?
+            .into_iter()
+            .map(|c| (c.contest_id, c.problem_id))
+            .collect::<BTreeSet<_>>();
+
+        let now = Utc::now().timestamp();
+        let orphans = submissions
+            .into_iter()
+            .map(|s| (s.contest_id.clone(), s.problem_id.clone()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|(contest_id, problem_id)| OrphanedProblem {
+                problem_missing: !problem_ids.contains(&problem_id),
+                contest_missing: !contest_ids.contains(&contest_id),
+                contest_problem_missing: !contest_problem
+                    .contains(&(contest_id.clone(), problem_id.clone())),
+                contest_id,
+                problem_id,
+                first_detected_epoch_second: now,
+                last_detected_epoch_second: now,
+                attempts: 0,
+            })
+            .filter(|o| o.problem_missing || o.contest_missing || o.contest_problem_missing)
+            .collect();
+        Ok(orphans)
+    }
+}
+
+#[async_trait]
+impl SubmissionClient for JsonlClient {
+    async fn get_submissions<'a>(&self, request: SubmissionRequest<'a>) -> Result<Vec<Submission>> {
+        let submissions = self.load_submissions()?.into_iter();
+        let submissions = match request {
+            SubmissionRequest::UserAll { user_id } => submissions
+                .filter(|s| s.user_id.eq_ignore_ascii_case(user_id))
+                .collect(),
+            SubmissionRequest::ContestAll { contest_id } => {
+                submissions.filter(|s| s.contest_id == contest_id).collect()
+            }
+            SubmissionRequest::ByIds { ids } => {
+                submissions.filter(|s| ids.contains(&s.id)).collect()
+            }
+            SubmissionRequest::FromUserAndTime {
+                user_id,
+                from_second,
+                count,
+            } => {
+                let mut submissions = submissions
+                    .filter(|s| s.user_id.eq_ignore_ascii_case(user_id))
+                    .filter(|s| s.epoch_second >= from_second)
+                    .collect::<Vec<_>>();
+                submissions.sort_by_key(|s| s.epoch_second);
+                submissions.truncate(count);
+                submissions
+            }
+            SubmissionRequest::RecentAll { count } => {
+                let mut submissions = submissions.collect::<Vec<_>>();
+                submissions.sort_by_key(|s| std::cmp::Reverse(s.id));
+                submissions.truncate(count as usize);
+                submissions
+            }
+            SubmissionRequest::RecentAccepted { count } => {
+                let mut submissions = submissions.filter(|s| s.result == "AC").collect::<Vec<_>>();
+                submissions.sort_by_key(|s| std::cmp::Reverse(s.id));
+                submissions.truncate(count as usize);
+                submissions
+            }
+            _ => bail!("The request is not supported by the JSON Lines client."),
+        };
+        Ok(submissions)
+    }
+
+    async fn update_submissions(&self, values: &[Submission]) -> Result<usize> {
+        self.append(SUBMISSIONS_FILE, values)?;
+        Ok(values.len())
+    }
+
+    async fn get_user_submission_count(&self, user_id: &str, range: Range<i64>) -> Result<usize> {
+        let count = self
+            .load_submissions()?
+            .iter()
+            .filter(|s| s.user_id.eq_ignore_ascii_case(user_id))
+            .filter(|s| range.contains(&s.epoch_second))
+            .count();
+        Ok(count)
+    }
+
+    async fn update_submission_detail(
+        &self,
+        _: &SubmissionDetail,
+        _: &[SubmissionTestcase],
+    ) -> Result<()> {
+        Err(anyhow!(
+            "Submission details are not supported by the JSON Lines client."
+        ))
+    }
+
+    async fn get_submission_detail(&self, _: i64) -> Result<Option<SubmissionDetail>> {
+        Err(anyhow!(
+            "Submission details are not supported by the JSON Lines client."
+        ))
+    }
+
+    async fn get_submission_testcases(&self, _: &[i64]) -> Result<Vec<SubmissionTestcase>> {
+        Err(anyhow!(
+            "Submission details are not supported by the JSON Lines client."
+        ))
+    }
+}
+
+#[async_trait]
+impl SimpleClient for JsonlClient {
+    async fn insert_contests(&self, values: &[Contest]) -> Result<usize> {
+        self.append(CONTESTS_FILE, values)?;
+        Ok(values.len())
+    }
+
+    async fn insert_problems(&self, values: &[Problem]) -> Result<usize> {
+        self.append(PROBLEMS_FILE, values)?;
+        Ok(values.len())
+    }
+
+    async fn load_problems(&self) -> Result<Vec<Problem>> {
+        self.read_latest(PROBLEMS_FILE, |p: &Problem| p.id.clone())
+    }
+
+    async fn load_contests(&self) -> Result<Vec<Contest>> {
+        self.read_latest(CONTESTS_FILE, |c: &Contest| c.id.clone())
+    }
+}
+
+#[async_trait]
+impl CrawlStateClient for JsonlClient {
+    async fn load_crawl_states(&self, crawler: &str) -> Result<Vec<CrawlState>> {
+        let states = self
+            .read::<CrawlState>(CRAWL_STATES_FILE)?
+            .into_iter()
+            .filter(|state| state.crawler == crawler)
+            .collect();
+        Ok(states)
+    }
+
+    async fn save_crawl_state(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        last_page: u32,
+        max_page: u32,
+        before_id: Option<i64>,
+    ) -> Result<()> {
+        let mut states = self.read::<CrawlState>(CRAWL_STATES_FILE)?;
+        states.retain(|state| state.crawler != crawler || state.contest_id != contest_id);
+        states.push(CrawlState {
+            crawler: crawler.to_string(),
+            contest_id: contest_id.to_string(),
+            last_page: last_page as i32,
+            max_page: max_page as i32,
+            updated_epoch_second: Utc::now().timestamp(),
+            before_id,
+        });
+        self.rewrite(CRAWL_STATES_FILE, &states)
+    }
+
+    async fn delete_crawl_state(&self, crawler: &str, contest_id: &str) -> Result<()> {
+        let mut states = self.read::<CrawlState>(CRAWL_STATES_FILE)?;
+        states.retain(|state| state.crawler != crawler || state.contest_id != contest_id);
+        self.rewrite(CRAWL_STATES_FILE, &states)
+    }
+
+    async fn load_last_crawled(&self, crawler: &str) -> Result<Vec<(String, i64)>> {
+        let last_crawled = self
+            .read_latest(LAST_CRAWLED_FILE, |l: &LastCrawled| {
+                (l.crawler.clone(), l.contest_id.clone())
+            })?
+            .into_iter()
+            .filter(|l| l.crawler == crawler)
+            .map(|l| (l.contest_id, l.epoch_second))
+            .collect();
+        Ok(last_crawled)
+    }
+
+    async fn save_last_crawled(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        epoch_second: i64,
+    ) -> Result<()> {
+        self.append(
+            LAST_CRAWLED_FILE,
+            &[LastCrawled {
+                crawler: crawler.to_string(),
+                contest_id: contest_id.to_string(),
+                epoch_second,
+            }],
+        )
+    }
+}
+
+#[async_trait]
+impl FailedPageClient for JsonlClient {
+    async fn record_failed_page(
+        &self,
+        crawler: &str,
+        contest_id: &str,
+        page: u32,
+        before_id: Option<i64>,
+        error: &str,
+    ) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let mut pages = self.load_failed_pages().await?;
+        let previous = pages
+            .iter()
+            .position(|p| p.contest_id == contest_id && p.page == page as i32)
+            .map(|i| pages.remove(i));
+        pages.push(match previous {
+            Some(previous) => FailedPage {
+                error: error.to_string(),
+                attempts: previous.attempts + 1,
+                last_failed_epoch_second: now,
+                before_id: previous.before_id.or(before_id),
+                ..previous
+            },
+            None => FailedPage {
+                contest_id: contest_id.to_string(),
+                page: page as i32,
+                crawler: crawler.to_string(),
+                error: error.to_string(),
+                attempts: 1,
+                first_failed_epoch_second: now,
+                last_failed_epoch_second: now,
+                before_id,
+            },
+        });
+        self.rewrite(FAILED_PAGES_FILE, &pages)
+    }
+
+    async fn merge_failed_pages(&self, merged: &[FailedPage]) -> Result<()> {
+        let mut pages = self.load_failed_pages().await?;
+        for page in merged {
+            match pages
+                .iter_mut()
+                .find(|p| p.contest_id == page.contest_id && p.page == page.page)
+            {
+                Some(p) => {
+                    if page.last_failed_epoch_second > p.last_failed_epoch_second {
+                        p.error = page.error.clone();
+                    }
+                    p.before_id = p.before_id.or(page.before_id);
+                    p.attempts = p.attempts.max(page.attempts);
+                    p.first_failed_epoch_second = p
+                        .first_failed_epoch_second
+                        .min(page.first_failed_epoch_second);
+                    p.last_failed_epoch_second = p
+                        .last_failed_epoch_second
+                        .max(page.last_failed_epoch_second);
+                }
+                None => pages.push(page.clone()),
+            }
+        }
+        self.rewrite(FAILED_PAGES_FILE, &pages)
+    }
+
+    async fn load_failed_pages(&self) -> Result<Vec<FailedPage>> {
+        let mut pages = self.read::<FailedPage>(FAILED_PAGES_FILE)?;
+        pages.sort_by(|a, b| {
+            let key = |p: &FailedPage| (p.last_failed_epoch_second, p.contest_id.clone(), p.page);
+            key(a).cmp(&key(b))
+        });
+        Ok(pages)
+    }
+
+    async fn delete_failed_page(&self, contest_id: &str, page: u32) -> Result<()> {
+        let mut pages = self.read::<FailedPage>(FAILED_PAGES_FILE)?;
+        pages.retain(|p| p.contest_id != contest_id || p.page != page as i32);
+        self.rewrite(FAILED_PAGES_FILE, &pages)
+    }
+}
+
+/// Sessions are never written, since the records are meant to be shared but the cookies must not be.
+/// Each run logs in again instead.
+#[async_trait]
+impl SessionClient for JsonlClient {
+    async fn save_atcoder_session(&self, _: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_atcoder_session(&self, _: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl ContestProblemClient for JsonlClient {
+    async fn insert_contest_problem(&self, contest_problems: &[ContestProblem]) -> Result<()> {
+        let stored = self
+            .load_contest_problem()
+            .await?
+            .into_iter()
+            .map(|c| (c.contest_id, c.problem_id))
+            .collect::<BTreeSet<_>>();
+        let inserted = contest_problems
+            .iter()
+            .filter(|c| !stored.contains(&(c.contest_id.clone(), c.problem_id.clone())))
+            .collect::<Vec<_>>();
+        self.append(CONTEST_PROBLEM_FILE, &inserted)
+    }
+
+    async fn load_contest_problem(&self) -> Result<Vec<ContestProblem>> {
+        self.read_latest(CONTEST_PROBLEM_FILE, |c: &ContestProblem| {
+            (c.contest_id.clone(), c.problem_id.clone())
+        })
+    }
+}
+
+#[async_trait]
+impl HiddenContestClient for JsonlClient {
+    async fn load_hidden_contests(&self) -> Result<Vec<HiddenContest>> {
+        self.read_latest(HIDDEN_CONTESTS_FILE, |c: &HiddenContest| c.id.clone())
+    }
+
+    async fn update_hidden_contests(&self, contests: &[HiddenContest]) -> Result<()> {
+        self.append(HIDDEN_CONTESTS_FILE, contests)
+    }
+
+    async fn delete_hidden_contest(&self, contest_id: &str) -> Result<()> {
+        let mut contests = self.read::<HiddenContest>(HIDDEN_CONTESTS_FILE)?;
+        contests.retain(|c| c.id != contest_id);
+        self.rewrite(HIDDEN_CONTESTS_FILE, &contests)
+    }
+}
+
+#[async_trait]
+impl OrphanClient for JsonlClient {
+    async fn record_submission_orphans(&self, submissions: &[Submission]) -> Result<usize> {
+        Ok(self.find_orphans(submissions)?.len())
+    }
+
+    async fn record_orphans(&self, from_epoch_second: i64) -> Result<usize> {
+        let submissions = self.load_submissions()?;
+        let submissions = submissions
+            .iter()
+            .filter(|s| s.epoch_second >= from_epoch_second);
+        Ok(self.find_orphans(submissions)?.len())
+    }
+
+    async fn load_orphans(&self) -> Result<Vec<OrphanedProblem>> {
+        self.find_orphans(&self.load_submissions()?)
+    }
+
+    async fn resolve_orphans(&self) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+#[async_trait]
+impl RevisionClient for JsonlClient {
+    async fn insert_contest_revisions(&self, revisions: &[ContestRevision]) -> Result<()> {
+        self.append(CONTEST_REVISIONS_FILE, revisions)
+    }
+
+    async fn revise_problems(
+        &self,
+        problems: &[Problem],
+        revisions: &[ProblemRevision],
+    ) -> Result<()> {
+        self.append(PROBLEM_REVISIONS_FILE, revisions)?;
+        let stored = self
+            .load_problems()
+            .await?
+            .into_iter()
+            .map(|p| (p.id.clone(), p))
+            .collect::<BTreeMap<_, _>>();
+        let revised = problems
+            .iter()
+            .filter_map(|p| {
+                let stored = stored.get(&p.id)?;
+                Some(Problem {
+                    name: p.name.clone(),
+                    title: p.title.clone(),
+                    time_limit_ms: p.time_limit_ms.or(stored.time_limit_ms),
+                    memory_limit_kb: p.memory_limit_kb.or(stored.memory_limit_kb),
+                    ..stored.clone()
+                })
+            })
+            .collect::<Vec<_>>();
+        self.append(PROBLEMS_FILE, &revised)
+    }
+
+    async fn load_contest_revisions(&self, contest_id: &str) -> Result<Vec<ContestRevision>> {
+        let revisions = self
+            .read::<ContestRevision>(CONTEST_REVISIONS_FILE)?
+            .into_iter()
+            .filter(|r| r.contest_id == contest_id)
+            .collect();
+        Ok(revisions)
+    }
+
+    async fn load_problem_revisions(&self, problem_id: &str) -> Result<Vec<ProblemRevision>> {
+        let revisions = self
+            .read::<ProblemRevision>(PROBLEM_REVISIONS_FILE)?
+            .into_iter()
+            .filter(|r| r.problem_id == problem_id)
+            .collect();
+        Ok(revisions)
+    }
+}
+
+#[async_trait]
+impl RatingHistoryClient for JsonlClient {
+    async fn update_user_rating_history(
+        &self,
+        user_id: &str,
+        history: &[UserRatingHistory],
+    ) -> Result<()> {
+        let history = history
+            .iter()
+            .map(|h| UserRatingHistory {
+                user_id: user_id.to_string(),
+                ..h.clone()
+            })
+            .collect::<Vec<_>>();
+        self.append(RATING_HISTORY_FILE, &history)
+    }
+
+    async fn load_user_rating_history(&self, user_id: &str) -> Result<Vec<UserRatingHistory>> {
+        let mut history = self
+            .read_latest(RATING_HISTORY_FILE, |h: &UserRatingHistory| {
+                (h.user_id.to_lowercase(), h.contest_id.clone())
+            })?
+            .into_iter()
+            .filter(|h| h.user_id.eq_ignore_ascii_case(user_id))
+            .collect::<Vec<_>>();
+        history.sort_by_key(|h| h.end_epoch_second);
+        Ok(history)
+    }
+
+    async fn get_users_rating(&self, user_id: &str) -> Result<Option<i32>> {
+        let rating = self
+            .load_user_rating_history(user_id)
+            .await?
+            .into_iter()
+            .rev()
+            .find(|h| h.is_rated)
+            .map(|h| h.new_rating);
+        Ok(rating)
+    }
+}
+
+#[async_trait]
+impl StandingsClient for JsonlClient {
+    async fn update_contest_standings(
+        &self,
+        contest_id: &str,
+        standings: &[ContestStanding],
+        task_results: &[ContestStandingTaskResult],
+    ) -> Result<()> {
+        // Standings are replaced as a whole, since a rejudge may change every row.
+        let mut stored = self.read::<ContestStanding>(STANDINGS_FILE)?;
+        stored.retain(|s| s.contest_id != contest_id);
+        stored.extend_from_slice(standings);
+        self.rewrite(STANDINGS_FILE, &stored)?;
+
+        let mut stored = self.read::<ContestStandingTaskResult>(STANDINGS_TASK_RESULTS_FILE)?;
+        stored.retain(|r| r.contest_id != contest_id);
+        stored.extend_from_slice(task_results);
+        self.rewrite(STANDINGS_TASK_RESULTS_FILE, &stored)
+    }
+
+    async fn load_contest_standings(&self, contest_id: &str) -> Result<Vec<ContestStanding>> {
+        let mut standings = self
+            .read::<ContestStanding>(STANDINGS_FILE)?
+            .into_iter()
+            .filter(|s| s.contest_id == contest_id)
+            .collect::<Vec<_>>();
+        standings.sort_by(|a, b| (a.rank, &a.user_id).cmp(&(b.rank, &b.user_id)));
+        Ok(standings)
+    }
+
+    async fn load_contest_standings_task_results(
+        &self,
+        contest_id: &str,
+    ) -> Result<Vec<ContestStandingTaskResult>> {
+        let mut task_results = self
+            .read::<ContestStandingTaskResult>(STANDINGS_TASK_RESULTS_FILE)?
+            .into_iter()
+            .filter(|r| r.contest_id == contest_id)
+            .collect::<Vec<_>>();
+        task_results.sort_by(|a, b| (&a.user_id, &a.problem_id).cmp(&(&b.user_id, &b.problem_id)));
+        Ok(task_results)
+    }
+
+    async fn load_user_standings(&self, user_id: &str) -> Result<Vec<ContestStanding>> {
+        let mut standings = self
+            .read::<ContestStanding>(STANDINGS_FILE)?
+            .into_iter()
+            .filter(|s| s.user_id.eq_ignore_ascii_case(user_id))
+            .collect::<Vec<_>>();
+        standings.sort_by(|a, b| a.contest_id.cmp(&b.contest_id));
+        Ok(standings)
+    }
+
+    async fn load_standings_contest_ids(&self) -> Result<Vec<String>> {
+        let contest_ids = self
+            .read::<ContestStanding>(STANDINGS_FILE)?
+            .into_iter()
+            .map(|s| s.contest_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        Ok(contest_ids)
+    }
+}
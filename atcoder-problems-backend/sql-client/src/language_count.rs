@@ -4,7 +4,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use regex::Regex;
 use sqlx::postgres::PgRow;
-use sqlx::Row;
+use sqlx::{PgConnection, Row};
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Range;
 
@@ -36,32 +36,7 @@ impl LanguageCountClient for PgPool {
         submissions: &[Submission],
         current_counts: &[UserLanguageCount],
     ) -> Result<()> {
-        let mut simplified_languages = BTreeMap::new();
-        let mut language_count = submissions
-            .iter()
-            .map(|s| {
-                (
-                    s.user_id.as_str(),
-                    s.problem_id.as_str(),
-                    s.language.as_str(),
-                )
-            })
-            .fold(
-                BTreeMap::new(),
-                |mut map, (user_id, problem_id, language)| {
-                    let simplified_language = simplified_languages
-                        .entry(language)
-                        .or_insert_with(|| simplify_language(language));
-                    map.entry((user_id, simplified_language.to_string()))
-                        .or_insert_with(BTreeSet::new)
-                        .insert(problem_id);
-                    map
-                },
-            )
-            .into_iter()
-            .map(|((user_id, language), set)| ((user_id, language), set.len() as i32))
-            .collect::<BTreeMap<_, _>>();
-
+        let mut language_count = count_languages(submissions);
         for old_count in current_counts {
             let key = &(
                 old_count.user_id.as_str(),
@@ -74,40 +49,8 @@ impl LanguageCountClient for PgPool {
             }
         }
 
-        let language_count = language_count
-            .into_iter()
-            .map(|((user_id, language), count)| (user_id, language, count))
-            .collect::<Vec<_>>();
-        for chunk in language_count.chunks(MAX_INSERT_ROWS) {
-            let (user_ids, languages, counts) = chunk.iter().fold(
-                (vec![], vec![], vec![]),
-                |(mut user_ids, mut languages, mut counts), cur| {
-                    user_ids.push(cur.0);
-                    languages.push(cur.1.as_str());
-                    counts.push(cur.2);
-                    (user_ids, languages, counts)
-                },
-            );
-
-            sqlx::query(
-                r"
-                INSERT INTO language_count (user_id, simplified_language, problem_count)
-                VALUES (
-                    UNNEST($1::VARCHAR(255)[]),
-                    UNNEST($2::VARCHAR(255)[]),
-                    UNNEST($3::INTEGER[])
-                )
-                ON CONFLICT (user_id, simplified_language)
-                DO UPDATE SET problem_count = EXCLUDED.problem_count
-                ",
-            )
-            .bind(user_ids)
-            .bind(languages)
-            .bind(counts)
-            .execute(self)
-            .await?;
-        }
-        Ok(())
+        let mut conn = self.acquire().await?;
+        save_language_count(&mut conn, language_count).await
     }
 
     async fn load_language_count(&self) -> Result<Vec<UserLanguageCount>> {
@@ -201,6 +144,75 @@ fn simplify_language(lang: &str) -> String {
     }
 }
 
+/// Counts the distinct problems each user has solved in each simplified language.
+pub(crate) fn count_languages(submissions: &[Submission]) -> BTreeMap<(&str, String), i32> {
+    let mut simplified_languages = BTreeMap::new();
+    submissions
+        .iter()
+        .map(|s| {
+            (
+                s.user_id.as_str(),
+                s.problem_id.as_str(),
+                s.language.as_str(),
+            )
+        })
+        .fold(
+            BTreeMap::new(),
+            |mut map, (user_id, problem_id, language)| {
+                let simplified_language = simplified_languages
+                    .entry(language)
+                    .or_insert_with(|| simplify_language(language));
+                map.entry((user_id, simplified_language.to_string()))
+                    .or_insert_with(BTreeSet::new)
+                    .insert(problem_id);
+                map
+            },
+        )
+        .into_iter()
+        .map(|((user_id, language), set)| ((user_id, language), set.len() as i32))
+        .collect()
+}
+
+pub(crate) async fn save_language_count(
+    conn: &mut PgConnection,
+    language_count: BTreeMap<(&str, String), i32>,
+) -> Result<()> {
+    let language_count = language_count
+        .into_iter()
+        .map(|((user_id, language), count)| (user_id, language, count))
+        .collect::<Vec<_>>();
+    for chunk in language_count.chunks(MAX_INSERT_ROWS) {
+        let (user_ids, languages, counts) = chunk.iter().fold(
+            (vec![], vec![], vec![]),
+            |(mut user_ids, mut languages, mut counts), cur| {
+                user_ids.push(cur.0);
+                languages.push(cur.1.as_str());
+                counts.push(cur.2);
+                (user_ids, languages, counts)
+            },
+        );
+
+        sqlx::query(
+            r"
+            INSERT INTO language_count (user_id, simplified_language, problem_count)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::VARCHAR(255)[]),
+                UNNEST($3::INTEGER[])
+            )
+            ON CONFLICT (user_id, simplified_language)
+            DO UPDATE SET problem_count = EXCLUDED.problem_count
+            ",
+        )
+        .bind(user_ids)
+        .bind(languages)
+        .bind(counts)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,21 +3,33 @@ use std::time::Duration;
 
 pub mod accepted_count;
 pub mod contest_problem;
+pub mod crawl_request;
+pub mod crawl_state;
+pub mod failed_page;
+pub mod hidden_contest;
 pub mod internal;
+pub mod jsonl;
 pub mod language_count;
+pub mod migration;
 pub mod models;
+pub mod orphan;
 pub mod problem_info;
 pub mod problems_submissions;
 pub mod rated_point_sum;
+pub mod rating_history;
+pub mod revision;
+pub mod session;
 pub mod simple_client;
+pub mod standings;
 pub mod streak;
 pub mod submission_client;
+pub mod submission_history;
+pub mod user_statistics;
 
 pub use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
 pub use sqlx::{query, Row};
 
 const FIRST_AGC_EPOCH_SECOND: i64 = 1_468_670_400;
-const UNRATED_STATE: &str = "-";
 const MAX_INSERT_ROWS: usize = 10_000;
 
 pub async fn initialize_pool<S: AsRef<str>>(database_url: S) -> Result<PgPool> {
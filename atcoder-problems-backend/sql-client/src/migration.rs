@@ -0,0 +1,361 @@
+use crate::models::AppliedMigration;
+use crate::PgPool;
+use anyhow::{bail, ensure, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::{Executor, Postgres, Transaction};
+
+/// Key of the advisory lock which serializes the processes changing `schema_migrations`.
+const MIGRATION_LOCK_KEY: i64 = 0x006d_6967_7261_7465;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+macro_rules! migration {
+    ($version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            sql: include_str!(concat!("../migrations/", $name, ".sql")),
+        }
+    };
+}
+
+/// Migrations in the order of their versions.
+/// A migration must not be edited once it has been applied; add a new one instead.
+/// The baseline is the schema before the migrations were introduced,
+/// which is what `migrate baseline` marks as applied on an existing database.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(1, "0001_baseline"),
+    migration!(2, "0002_submission_details"),
+    migration!(3, "0003_submission_memory"),
+    migration!(4, "0004_contest_standings"),
+    migration!(5, "0005_user_rating_history"),
+    migration!(6, "0006_problem_limits"),
+    migration!(7, "0007_atcoder_sessions"),
+    migration!(8, "0008_contest_category"),
+    migration!(9, "0009_hidden_contests"),
+    migration!(10, "0010_crawl_state"),
+    migration!(11, "0011_crawl_history"),
+    migration!(12, "0012_crawl_requests"),
+    migration!(13, "0013_failed_pages"),
+    migration!(14, "0014_submission_history"),
+    migration!(15, "0015_orphaned_problems"),
+    migration!(16, "0016_revisions"),
+    migration!(17, "0017_contest_rated_range"),
+    migration!(18, "0018_crawl_request_pending"),
+    migration!(19, "0019_failed_page_anchor"),
+    migration!(20, "0020_vanished_submissions"),
+    migration!(21, "0021_orphan_attempts"),
+    migration!(22, "0022_crawl_state_anchor"),
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the migration has been edited since then.
+    Modified,
+    /// Applied, but not known to this build, e.g. applied by a newer one.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+}
+
+/// Compares the migrations with the applied ones, in the order of their versions.
+pub fn compare_migrations(
+    migrations: &[Migration],
+    applied: &[AppliedMigration],
+) -> Vec<MigrationStatus> {
+    let mut statuses = migrations
+        .iter()
+        .map(|migration| {
+            let state = match applied.iter().find(|a| a.version == migration.version) {
+                Some(a) if a.checksum == migration.checksum() => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                state,
+            }
+        })
+        .collect::<Vec<_>>();
+    statuses.extend(
+        applied
+            .iter()
+            .filter(|a| migrations.iter().all(|m| m.version != a.version))
+            .map(|a| MigrationStatus {
+                version: a.version,
+                name: a.name.clone(),
+                state: MigrationState::Unknown,
+            }),
+    );
+    statuses.sort_by_key(|status| status.version);
+    statuses
+}
+
+/// Fails if the applied migrations disagree with the migrations of this build.
+pub fn verify_statuses(statuses: &[MigrationStatus]) -> Result<()> {
+    for status in statuses {
+        match status.state {
+            MigrationState::Modified => bail!(
+                "Migration {} ({}) has been modified after it was applied",
+                status.version,
+                status.name
+            ),
+            MigrationState::Unknown => bail!(
+                "Migration {} ({}) is applied, but unknown to this build",
+                status.version,
+                status.name
+            ),
+            MigrationState::Applied | MigrationState::Pending => {}
+        }
+    }
+    Ok(())
+}
+
+/// Versioned schema of the database, recorded in `schema_migrations`.
+#[async_trait]
+pub trait MigrationClient {
+    /// Loads the applied migrations, which is empty if no migration has ever been applied.
+    async fn load_applied_migrations(&self) -> Result<Vec<AppliedMigration>>;
+
+    /// Applies the pending migrations in a transaction, and returns their versions.
+    async fn migrate_up(&self) -> Result<Vec<i64>>;
+
+    /// Records the baseline as applied without running it,
+    /// for a database created before the migrations were introduced.
+    async fn mark_baseline_applied(&self) -> Result<()>;
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>>;
+
+    /// Fails unless all the migrations of this build have been applied as they are.
+    async fn ensure_schema_up_to_date(&self) -> Result<()>;
+}
+
+#[async_trait]
+impl MigrationClient for PgPool {
+    async fn load_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        let (exists,): (bool,) =
+            sqlx::query_as("SELECT to_regclass('schema_migrations') IS NOT NULL")
+                .fetch_one(self)
+                .await?;
+        if !exists {
+            return Ok(Vec::new());
+        }
+        let migrations = sqlx::query_as("SELECT * FROM schema_migrations ORDER BY version")
+            .fetch_all(self)
+            .await?;
+        Ok(migrations)
+    }
+
+    async fn migrate_up(&self) -> Result<Vec<i64>> {
+        let mut tx = self.begin().await?;
+        let applied = lock_migrations(&mut tx).await?;
+        let statuses = compare_migrations(MIGRATIONS, &applied);
+        verify_statuses(&statuses)?;
+
+        if applied.is_empty() {
+            // The baseline drops the tables before creating them.
+            ensure!(
+                !table_exists(&mut tx, "submissions").await?,
+                "The database was created before the migrations, run `migrate baseline` first"
+            );
+        }
+
+        let mut versions = Vec::new();
+        for migration in MIGRATIONS {
+            if applied.iter().any(|a| a.version == migration.version) {
+                continue;
+            }
+            tx.execute(migration.sql).await?;
+            record_migration(&mut tx, migration).await?;
+            versions.push(migration.version);
+        }
+        tx.commit().await?;
+        Ok(versions)
+    }
+
+    async fn mark_baseline_applied(&self) -> Result<()> {
+        let mut tx = self.begin().await?;
+        let applied = lock_migrations(&mut tx).await?;
+        ensure!(
+            applied.is_empty(),
+            "Some migrations have already been applied"
+        );
+        ensure!(
+            table_exists(&mut tx, "submissions").await?,
+            "The database has not been created yet"
+        );
+        record_migration(&mut tx, &MIGRATIONS[0]).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        let applied = self.load_applied_migrations().await?;
+        Ok(compare_migrations(MIGRATIONS, &applied))
+    }
+
+    async fn ensure_schema_up_to_date(&self) -> Result<()> {
+        let statuses = self.migration_status().await?;
+        verify_statuses(&statuses)?;
+        let pending = statuses
+            .iter()
+            .filter(|status| status.state == MigrationState::Pending)
+            .map(|status| status.version.to_string())
+            .collect::<Vec<_>>();
+        ensure!(
+            pending.is_empty(),
+            "The database schema is out of date, migrations {} are pending",
+            pending.join(", ")
+        );
+        Ok(())
+    }
+}
+
+/// Waits for the other processes migrating the database, and loads the applied migrations.
+async fn lock_migrations(tx: &mut Transaction<'_, Postgres>) -> Result<Vec<AppliedMigration>> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+          version               BIGINT NOT NULL,
+          name                  VARCHAR(255) NOT NULL,
+          checksum              VARCHAR(64) NOT NULL,
+          applied_epoch_second  BIGINT NOT NULL,
+          PRIMARY KEY (version)
+        )
+        ",
+    )
+    .execute(&mut *tx)
+    .await?;
+    let applied = sqlx::query_as("SELECT * FROM schema_migrations ORDER BY version")
+        .fetch_all(&mut *tx)
+        .await?;
+    Ok(applied)
+}
+
+async fn table_exists(tx: &mut Transaction<'_, Postgres>, table: &str) -> Result<bool> {
+    let (exists,): (bool,) = sqlx::query_as("SELECT to_regclass($1) IS NOT NULL")
+        .bind(table)
+        .fetch_one(&mut *tx)
+        .await?;
+    Ok(exists)
+}
+
+async fn record_migration(tx: &mut Transaction<'_, Postgres>, migration: &Migration) -> Result<()> {
+    sqlx::query(
+        r"
+        INSERT INTO schema_migrations (version, name, checksum, applied_epoch_second)
+        VALUES ($1, $2, $3, $4)
+        ",
+    )
+    .bind(migration.version)
+    .bind(migration.name)
+    .bind(migration.checksum())
+    .bind(Utc::now().timestamp())
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATION: Migration = Migration {
+        version: 1,
+        name: "first",
+        sql: "CREATE TABLE t (id BIGINT);",
+    };
+
+    fn applied(version: i64, checksum: String) -> AppliedMigration {
+        AppliedMigration {
+            version,
+            name: format!("migration{}", version),
+            checksum,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        for migration in MIGRATIONS {
+            assert!(migration
+                .name
+                .starts_with(&format!("{:04}_", migration.version)));
+        }
+        assert_eq!(MIGRATIONS[0].name, "0001_baseline");
+    }
+
+    #[test]
+    fn test_compare_migrations() {
+        let second = Migration {
+            version: 2,
+            name: "second",
+            sql: "ALTER TABLE t ADD COLUMN name VARCHAR(255);",
+        };
+        let migrations = [MIGRATION, second];
+        let states = |applied: &[AppliedMigration]| {
+            compare_migrations(&migrations, applied)
+                .into_iter()
+                .map(|status| (status.version, status.state))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            states(&[]),
+            vec![(1, MigrationState::Pending), (2, MigrationState::Pending)]
+        );
+        assert_eq!(
+            states(&[applied(1, MIGRATION.checksum())]),
+            vec![(1, MigrationState::Applied), (2, MigrationState::Pending)]
+        );
+        assert_eq!(
+            states(&[
+                applied(1, "edited".to_string()),
+                applied(3, "newer".to_string())
+            ]),
+            vec![
+                (1, MigrationState::Modified),
+                (2, MigrationState::Pending),
+                (3, MigrationState::Unknown)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_statuses() {
+        let status = |state| MigrationStatus {
+            version: 1,
+            name: "first".to_string(),
+            state,
+        };
+        assert!(verify_statuses(&[status(MigrationState::Applied)]).is_ok());
+        assert!(verify_statuses(&[status(MigrationState::Pending)]).is_ok());
+        assert!(verify_statuses(&[status(MigrationState::Modified)]).is_err());
+        assert!(verify_statuses(&[status(MigrationState::Unknown)]).is_err());
+    }
+}
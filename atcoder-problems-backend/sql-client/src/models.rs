@@ -1,21 +1,117 @@
+use crate::submission_history::{SUBMISSION_REJUDGED, SUBMISSION_VANISHED};
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Eq, PartialEq, Serialize, sqlx::FromRow)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Contest {
     pub id: String,
     pub start_epoch_second: i64,
     pub duration_second: i64,
     pub title: String,
     pub rate_change: String,
+    /// One of "ABC", "ARC", "AGC", "AHC", "Sponsored" and "Other".
+    pub category: String,
+    /// Bounds of the rated range. `rated_min` is `None` only for unrated contests,
+    /// and is 0 for a range without the lower bound. `rated_max` is `None` if unbounded.
+    pub rated_min: Option<i32>,
+    pub rated_max: Option<i32>,
 }
 
-#[derive(Debug, Eq, PartialEq, Serialize, sqlx::FromRow)]
+/// Contest which doesn't appear in the contest list of AtCoder.
+/// The fields are in the same format as `AtCoderContest`.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct HiddenContest {
+    pub id: String,
+    pub start_epoch_second: i64,
+    pub duration_second: i64,
+    pub title: String,
+    pub rate_change: String,
+}
+
+/// Change of a field of a contest, whose values are formatted as strings.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ContestRevision {
+    pub id: i64,
+    pub contest_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub detected_epoch_second: i64,
+}
+
+/// Progress of a crawler paging through the submissions of a contest.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CrawlState {
+    pub crawler: String,
+    pub contest_id: String,
+    pub last_page: i32,
+    pub max_page: i32,
+    pub updated_epoch_second: i64,
+    /// The smallest submission id crawled so far, which the rest of the contest is older than.
+    pub before_id: Option<i64>,
+}
+
+/// Request to crawl the submissions of a user, which is processed in the order of `id`.
+/// `status` is one of `CRAWL_REQUEST_*` in `crawl_request`.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, sqlx::FromRow)]
+pub struct CrawlRequest {
+    pub id: i64,
+    pub atcoder_user_id: String,
+    pub status: String,
+    pub requested_epoch_second: i64,
+    pub started_epoch_second: Option<i64>,
+    pub finished_epoch_second: Option<i64>,
+    pub error: Option<String>,
+}
+
+/// Submission page which failed to be fetched, waiting to be retried.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FailedPage {
+    pub contest_id: String,
+    pub page: i32,
+    pub crawler: String,
+    pub error: String,
+    pub attempts: i32,
+    pub first_failed_epoch_second: i64,
+    pub last_failed_epoch_second: i64,
+    /// Smallest submission id of the previous page when the page failed,
+    /// so that the submissions of the page are found even after they have shifted to later pages.
+    pub before_id: Option<i64>,
+}
+
+/// Problem referenced by submissions, which is missing in some of the problem tables.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, sqlx::FromRow)]
+pub struct OrphanedProblem {
+    pub contest_id: String,
+    pub problem_id: String,
+    pub problem_missing: bool,
+    pub contest_missing: bool,
+    pub contest_problem_missing: bool,
+    pub first_detected_epoch_second: i64,
+    pub last_detected_epoch_second: i64,
+    pub attempts: i32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Problem {
     pub id: String,
     pub contest_id: String,
     pub problem_index: String,
     pub name: String,
     pub title: String,
+    pub time_limit_ms: Option<i32>,
+    pub memory_limit_kb: Option<i32>,
+}
+
+/// Change of a field of a problem, whose values are formatted as strings.
+/// `None` means the field is not set, e.g. the time limit is unknown.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProblemRevision {
+    pub id: i64,
+    pub problem_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub detected_epoch_second: i64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Default, Deserialize, sqlx::FromRow)]
@@ -30,6 +126,101 @@ pub struct Submission {
     pub length: i32,
     pub result: String,
     pub execution_time: Option<i32>,
+    pub memory: Option<i32>,
+}
+
+/// Change of a stored submission. `kind` is one of `SUBMISSION_*` in `submission_history`,
+/// and `new_result` and `new_point` are `None` if the submission has vanished.
+#[derive(Debug, Clone, PartialEq, Serialize, Default, Deserialize, sqlx::FromRow)]
+pub struct SubmissionChange {
+    pub id: i64,
+    pub submission_id: i64,
+    pub kind: String,
+    pub contest_id: String,
+    pub problem_id: String,
+    pub user_id: String,
+    pub old_result: String,
+    pub new_result: Option<String>,
+    pub old_point: f64,
+    pub new_point: Option<f64>,
+    pub detected_epoch_second: i64,
+}
+
+impl SubmissionChange {
+    pub fn rejudged(old: &Submission, new: &Submission, detected_epoch_second: i64) -> Self {
+        Self {
+            kind: SUBMISSION_REJUDGED.to_string(),
+            new_result: Some(new.result.clone()),
+            new_point: Some(new.point),
+            ..Self::vanished(old, detected_epoch_second)
+        }
+    }
+
+    pub fn vanished(old: &Submission, detected_epoch_second: i64) -> Self {
+        Self {
+            id: 0,
+            submission_id: old.id,
+            kind: SUBMISSION_VANISHED.to_string(),
+            contest_id: old.contest_id.clone(),
+            problem_id: old.problem_id.clone(),
+            user_id: old.user_id.clone(),
+            old_result: old.result.clone(),
+            new_result: None,
+            old_point: old.point,
+            new_point: None,
+            detected_epoch_second,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default, Deserialize, sqlx::FromRow)]
+pub struct SubmissionDetail {
+    pub submission_id: i64,
+    pub compile_error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default, Deserialize, sqlx::FromRow)]
+pub struct SubmissionTestcase {
+    pub submission_id: i64,
+    pub case_name: String,
+    pub result: String,
+    pub execution_time: Option<i32>,
+    pub memory: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Default, Deserialize, sqlx::FromRow)]
+pub struct ContestStanding {
+    pub contest_id: String,
+    pub user_id: String,
+    pub rank: i32,
+    pub is_rated: bool,
+    pub old_rating: i32,
+    pub new_rating: i32,
+    pub score: f64,
+    pub penalty: i32,
+    pub elapsed_second: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Default, Deserialize, sqlx::FromRow)]
+pub struct ContestStandingTaskResult {
+    pub contest_id: String,
+    pub user_id: String,
+    pub problem_id: String,
+    pub score: f64,
+    pub penalty: i32,
+    pub elapsed_second: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default, Deserialize, sqlx::FromRow)]
+pub struct UserRatingHistory {
+    pub user_id: String,
+    pub contest_id: String,
+    pub is_rated: bool,
+    pub rank: i32,
+    pub performance: i32,
+    pub old_rating: i32,
+    pub new_rating: i32,
+    pub end_epoch_second: i64,
 }
 
 #[derive(Debug, Eq, PartialEq, Serialize, sqlx::FromRow)]
@@ -65,7 +256,7 @@ pub struct UserSum {
     pub point_sum: i64,
 }
 
-#[derive(PartialEq, Debug, Serialize, sqlx::FromRow)]
+#[derive(PartialEq, Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ContestProblem {
     pub contest_id: String,
     pub problem_id: String,
@@ -77,3 +268,12 @@ pub struct UserStreak {
     pub user_id: String,
     pub streak: i64,
 }
+
+/// Migration recorded in `schema_migrations` when it was applied.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, sqlx::FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_epoch_second: i64,
+}
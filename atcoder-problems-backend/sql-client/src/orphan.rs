@@ -0,0 +1,147 @@
+use crate::models::{OrphanedProblem, Submission};
+use crate::PgPool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::BTreeSet;
+
+/// Problems referenced by submissions which are not fully known yet,
+/// i.e. lacking any of `problems`, `contests` and `contest_problem`.
+#[async_trait]
+pub trait OrphanClient: Sync {
+    /// Records the orphaned problems of `submissions`, e.g. a batch which has just been stored,
+    /// and returns how many there are.
+    async fn record_submission_orphans(&self, _submissions: &[Submission]) -> Result<usize> {
+        Err(anyhow!("record_submission_orphans is not supported"))
+    }
+
+    /// Records the orphaned problems of the submissions submitted since `from_epoch_second`,
+    /// and returns how many there are.
+    /// It scans the submissions, so it's only a backstop of `record_submission_orphans`.
+    async fn record_orphans(&self, _from_epoch_second: i64) -> Result<usize> {
+        Err(anyhow!("record_orphans is not supported"))
+    }
+
+    /// Loads the outstanding orphans with which of the tables lack them.
+    async fn load_orphans(&self) -> Result<Vec<OrphanedProblem>> {
+        Err(anyhow!("load_orphans is not supported"))
+    }
+
+    /// Deletes the orphans which are not orphaned anymore, and returns how many it has deleted.
+    /// The attempts of the outstanding ones are counted up.
+    async fn resolve_orphans(&self) -> Result<usize> {
+        Err(anyhow!("resolve_orphans is not supported"))
+    }
+}
+
+#[async_trait]
+impl OrphanClient for PgPool {
+    async fn record_submission_orphans(&self, submissions: &[Submission]) -> Result<usize> {
+        let (contest_ids, problem_ids): (Vec<_>, Vec<_>) = submissions
+            .iter()
+            .map(|s| (s.contest_id.as_str(), s.problem_id.as_str()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .unzip();
+        if contest_ids.is_empty() {
+            return Ok(0);
+        }
+        let result = sqlx::query(
+            r"
+            INSERT INTO orphaned_problems
+            (contest_id, problem_id, first_detected_epoch_second, last_detected_epoch_second)
+            SELECT s.contest_id, s.problem_id, $3, $3
+            FROM UNNEST($1::VARCHAR(255)[], $2::VARCHAR(255)[]) AS s (contest_id, problem_id)
+            WHERE NOT EXISTS (SELECT 1 FROM problems WHERE id = s.problem_id)
+            OR NOT EXISTS (SELECT 1 FROM contests WHERE id = s.contest_id)
+            OR NOT EXISTS (
+                SELECT 1 FROM contest_problem
+                WHERE contest_id = s.contest_id AND problem_id = s.problem_id
+            )
+            ON CONFLICT (contest_id, problem_id)
+            DO UPDATE SET last_detected_epoch_second = EXCLUDED.last_detected_epoch_second
+            ",
+        )
+        .bind(contest_ids)
+        .bind(problem_ids)
+        .bind(Utc::now().timestamp())
+        .execute(self)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn record_orphans(&self, from_epoch_second: i64) -> Result<usize> {
+        let result = sqlx::query(
+            r"
+            INSERT INTO orphaned_problems
+            (contest_id, problem_id, first_detected_epoch_second, last_detected_epoch_second)
+            SELECT s.contest_id, s.problem_id, $2, $2
+            FROM (
+                SELECT DISTINCT contest_id, problem_id FROM submissions
+                WHERE epoch_second >= $1
+            ) AS s
+            WHERE NOT EXISTS (SELECT 1 FROM problems WHERE id = s.problem_id)
+            OR NOT EXISTS (SELECT 1 FROM contests WHERE id = s.contest_id)
+            OR NOT EXISTS (
+                SELECT 1 FROM contest_problem
+                WHERE contest_id = s.contest_id AND problem_id = s.problem_id
+            )
+            ON CONFLICT (contest_id, problem_id)
+            DO UPDATE SET last_detected_epoch_second = EXCLUDED.last_detected_epoch_second
+            ",
+        )
+        .bind(from_epoch_second)
+        .bind(Utc::now().timestamp())
+        .execute(self)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn load_orphans(&self) -> Result<Vec<OrphanedProblem>> {
+        let orphans = sqlx::query_as(
+            r"
+            SELECT
+                o.contest_id,
+                o.problem_id,
+                p.id IS NULL AS problem_missing,
+                c.id IS NULL AS contest_missing,
+                NOT EXISTS (
+                    SELECT 1 FROM contest_problem
+                    WHERE contest_id = o.contest_id AND problem_id = o.problem_id
+                ) AS contest_problem_missing,
+                o.first_detected_epoch_second,
+                o.last_detected_epoch_second,
+                o.attempts
+            FROM orphaned_problems AS o
+            LEFT JOIN problems AS p ON p.id = o.problem_id
+            LEFT JOIN contests AS c ON c.id = o.contest_id
+            ORDER BY o.contest_id, o.problem_id
+            ",
+        )
+        .fetch_all(self)
+        .await?;
+        Ok(orphans)
+    }
+
+    async fn resolve_orphans(&self) -> Result<usize> {
+        let mut tx = self.begin().await?;
+        let result = sqlx::query(
+            r"
+            DELETE FROM orphaned_problems AS o
+            WHERE EXISTS (SELECT 1 FROM problems WHERE id = o.problem_id)
+            AND EXISTS (SELECT 1 FROM contests WHERE id = o.contest_id)
+            AND EXISTS (
+                SELECT 1 FROM contest_problem
+                WHERE contest_id = o.contest_id AND problem_id = o.problem_id
+            )
+            ",
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query("UPDATE orphaned_problems SET attempts = attempts + 1")
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() as usize)
+    }
+}
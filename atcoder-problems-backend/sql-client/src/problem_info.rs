@@ -5,6 +5,10 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait ProblemInfoUpdater {
     async fn update_solver_count(&self) -> Result<()>;
+
+    /// Recounts the solvers of the problems, including the ones which nobody has solved any more,
+    /// which `update_solver_count` leaves as they are.
+    async fn update_solver_count_of_problems(&self, problem_ids: &[&str]) -> Result<()>;
     async fn update_problem_points(&self) -> Result<()>;
 }
 
@@ -27,6 +31,26 @@ impl ProblemInfoUpdater for PgPool {
         Ok(())
     }
 
+    async fn update_solver_count_of_problems(&self, problem_ids: &[&str]) -> Result<()> {
+        sqlx::query(
+            r"
+                INSERT INTO solver (user_count, problem_id)
+                    SELECT COUNT(DISTINCT(submissions.user_id)), problems.problem_id
+                    FROM UNNEST($1::VARCHAR(255)[]) AS problems(problem_id)
+                    LEFT JOIN submissions
+                    ON submissions.problem_id = problems.problem_id
+                    AND submissions.result = 'AC'
+                    GROUP BY problems.problem_id
+                ON CONFLICT (problem_id) DO UPDATE
+                SET user_count = EXCLUDED.user_count;
+            ",
+        )
+        .bind(problem_ids)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
     async fn update_problem_points(&self) -> Result<()> {
         sqlx::query(
             r"
@@ -35,7 +59,7 @@ impl ProblemInfoUpdater for PgPool {
                     FROM submissions
                     INNER JOIN contests ON contests.id = submissions.contest_id
                     WHERE contests.start_epoch_second >= $1
-                    AND contests.rate_change != '-'
+                    AND contests.rated_min IS NOT NULL
                     GROUP BY submissions.problem_id
                 ON CONFLICT (problem_id) DO UPDATE
                 SET point = EXCLUDED.point;
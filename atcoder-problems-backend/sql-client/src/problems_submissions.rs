@@ -5,6 +5,10 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait ProblemsSubmissionUpdater {
     async fn update_submissions_of_problems(&self) -> Result<()>;
+
+    /// Deletes the first, fastest, shortest and least memory records which point to the submissions,
+    /// so that records of submissions which are no longer accepted don't survive the next update.
+    async fn delete_submissions_of_problems(&self, submission_ids: &[i64]) -> Result<()>;
 }
 
 #[async_trait]
@@ -13,15 +17,28 @@ impl ProblemsSubmissionUpdater for PgPool {
         let first_sql = generate_query("first", "id");
         let fastest_sql = generate_query("fastest", "execution_time");
         let shortest_sql = generate_query("shortest", "length");
+        let least_memory_sql = generate_query("least_memory", "memory");
 
         tokio::try_join!(
             sqlx::query(&first_sql).execute(self),
             sqlx::query(&fastest_sql).execute(self),
             sqlx::query(&shortest_sql).execute(self),
+            sqlx::query(&least_memory_sql).execute(self),
         )?;
 
         Ok(())
     }
+
+    async fn delete_submissions_of_problems(&self, submission_ids: &[i64]) -> Result<()> {
+        for table in ["first", "fastest", "shortest", "least_memory"] {
+            let query = format!("DELETE FROM {} WHERE submission_id = ANY($1)", table);
+            sqlx::query(&query)
+                .bind(submission_ids)
+                .execute(self)
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 fn generate_query(table: &str, column: &str) -> String {
@@ -1,8 +1,8 @@
 use crate::models::{ContestProblem, Submission, UserSum};
-use crate::{PgPool, FIRST_AGC_EPOCH_SECOND, MAX_INSERT_ROWS, UNRATED_STATE};
+use crate::{PgPool, FIRST_AGC_EPOCH_SECOND, MAX_INSERT_ROWS};
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::Row;
+use sqlx::{PgConnection, Row};
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Range;
 
@@ -18,88 +18,10 @@ pub trait RatedPointSumClient {
 #[async_trait]
 impl RatedPointSumClient for PgPool {
     async fn update_rated_point_sum(&self, ac_submissions: &[Submission]) -> Result<()> {
-        let rated_contest_ids_fut = sqlx::query(
-            r"
-            SELECT contests.id FROM
-            (
-                SELECT COUNT(*) AS problem_count, contest_id
-                FROM contest_problem
-                GROUP BY contest_id
-            ) AS contest_problem_count
-            JOIN contests ON contests.id=contest_problem_count.contest_id
-            WHERE
-                contests.start_epoch_second >= $1
-                AND contests.rate_change != $2
-                AND contest_problem_count.problem_count >= 2
-            ",
-        )
-        .bind(FIRST_AGC_EPOCH_SECOND)
-        .bind(UNRATED_STATE)
-        .try_map(|row| row.try_get::<String, _>("id"))
-        .fetch_all(self);
-
-        let rated_problem_ids_fut = sqlx::query_as::<_, ContestProblem>(
-            "SELECT contest_id, problem_id, problem_index FROM contest_problem",
-        )
-        .fetch_all(self);
-
-        let (rated_contest_ids, rated_problem_ids) =
-            tokio::try_join!(rated_contest_ids_fut, rated_problem_ids_fut)?;
-
-        let rated_contest_ids = BTreeSet::from_iter(rated_contest_ids);
-        let rated_problem_ids = rated_problem_ids
-            .into_iter()
-            .filter(|p| rated_contest_ids.contains(&p.contest_id))
-            .map(|p| p.problem_id)
-            .collect::<BTreeSet<_>>();
-        let rated_point_sum = ac_submissions
-            .iter()
-            .filter(|s| rated_problem_ids.contains(&s.problem_id))
-            .map(|s| {
-                if s.point.fract() == 0. {
-                    Ok((s.user_id.as_str(), s.problem_id.as_str(), s.point as i64))
-                } else {
-                    Err(anyhow::anyhow!(
-                        "Problem of {} is {}, which is a rated problem, but has non-integer point",
-                        s.id,
-                        s.problem_id
-                    ))
-                }
-            })
-            .collect::<Result<Vec<_>>>()?
-            .into_iter()
-            .fold(BTreeMap::new(), |mut map, (user_id, problem_id, point)| {
-                map.entry(user_id)
-                    .or_insert_with(BTreeMap::new)
-                    .insert(problem_id, point);
-                map
-            })
-            .into_iter()
-            .map(|(user_id, set)| {
-                let sum = set.into_values().sum::<i64>();
-                (user_id, sum)
-            })
-            .collect::<Vec<_>>();
-
-        for chunk in rated_point_sum.chunks(MAX_INSERT_ROWS) {
-            let (user_ids, point_sums): (Vec<&str>, Vec<i64>) = chunk.iter().copied().unzip();
-            sqlx::query(
-                r"
-                INSERT INTO rated_point_sum (user_id, point_sum)
-                VALUES (
-                    UNNEST($1::VARCHAR(255)[]),
-                    UNNEST($2::BIGINT[])
-                )
-                ON CONFLICT (user_id)
-                DO UPDATE SET point_sum = EXCLUDED.point_sum
-                ",
-            )
-            .bind(user_ids)
-            .bind(point_sums)
-            .execute(self)
-            .await?;
-        }
-        Ok(())
+        let rated_problem_ids = load_rated_problem_ids(self).await?;
+        let rated_point_sum = sum_rated_points(ac_submissions, &rated_problem_ids)?;
+        let mut conn = self.acquire().await?;
+        save_rated_point_sum(&mut conn, &rated_point_sum).await
     }
 
     async fn get_users_rated_point_sum(&self, user_id: &str) -> Option<i64> {
@@ -144,3 +66,101 @@ impl RatedPointSumClient for PgPool {
         Ok(list)
     }
 }
+
+/// Loads the problems of the rated contests which have more than one problem.
+pub(crate) async fn load_rated_problem_ids(pool: &PgPool) -> Result<BTreeSet<String>> {
+    let rated_contest_ids_fut = sqlx::query(
+        r"
+        SELECT contests.id FROM
+        (
+            SELECT COUNT(*) AS problem_count, contest_id
+            FROM contest_problem
+            GROUP BY contest_id
+        ) AS contest_problem_count
+        JOIN contests ON contests.id=contest_problem_count.contest_id
+        WHERE
+            contests.start_epoch_second >= $1
+            AND contests.rated_min IS NOT NULL
+            AND contest_problem_count.problem_count >= 2
+        ",
+    )
+    .bind(FIRST_AGC_EPOCH_SECOND)
+    .try_map(|row| row.try_get::<String, _>("id"))
+    .fetch_all(pool);
+
+    let rated_problem_ids_fut = sqlx::query_as::<_, ContestProblem>(
+        "SELECT contest_id, problem_id, problem_index FROM contest_problem",
+    )
+    .fetch_all(pool);
+
+    let (rated_contest_ids, rated_problem_ids) =
+        tokio::try_join!(rated_contest_ids_fut, rated_problem_ids_fut)?;
+
+    let rated_contest_ids = BTreeSet::from_iter(rated_contest_ids);
+    Ok(rated_problem_ids
+        .into_iter()
+        .filter(|p| rated_contest_ids.contains(&p.contest_id))
+        .map(|p| p.problem_id)
+        .collect())
+}
+
+/// Sums up the points of the distinct rated problems each user has solved.
+pub(crate) fn sum_rated_points<'a>(
+    ac_submissions: &'a [Submission],
+    rated_problem_ids: &BTreeSet<String>,
+) -> Result<Vec<(&'a str, i64)>> {
+    let rated_point_sum = ac_submissions
+        .iter()
+        .filter(|s| rated_problem_ids.contains(&s.problem_id))
+        .map(|s| {
+            if s.point.fract() == 0. {
+                Ok((s.user_id.as_str(), s.problem_id.as_str(), s.point as i64))
+            } else {
+                Err(anyhow::anyhow!(
+                    "Problem of {} is {}, which is a rated problem, but has non-integer point",
+                    s.id,
+                    s.problem_id
+                ))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .fold(BTreeMap::new(), |mut map, (user_id, problem_id, point)| {
+            map.entry(user_id)
+                .or_insert_with(BTreeMap::new)
+                .insert(problem_id, point);
+            map
+        })
+        .into_iter()
+        .map(|(user_id, set)| {
+            let sum = set.into_values().sum::<i64>();
+            (user_id, sum)
+        })
+        .collect();
+    Ok(rated_point_sum)
+}
+
+pub(crate) async fn save_rated_point_sum(
+    conn: &mut PgConnection,
+    rated_point_sum: &[(&str, i64)],
+) -> Result<()> {
+    for chunk in rated_point_sum.chunks(MAX_INSERT_ROWS) {
+        let (user_ids, point_sums): (Vec<&str>, Vec<i64>) = chunk.iter().copied().unzip();
+        sqlx::query(
+            r"
+            INSERT INTO rated_point_sum (user_id, point_sum)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::BIGINT[])
+            )
+            ON CONFLICT (user_id)
+            DO UPDATE SET point_sum = EXCLUDED.point_sum
+            ",
+        )
+        .bind(user_ids)
+        .bind(point_sums)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use crate::models::UserRatingHistory;
+use crate::{PgPool, PgRow};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use sqlx::Row;
+
+#[async_trait]
+pub trait RatingHistoryClient: Sync {
+    async fn update_user_rating_history(
+        &self,
+        user_id: &str,
+        history: &[UserRatingHistory],
+    ) -> Result<()>;
+    async fn load_user_rating_history(&self, _user_id: &str) -> Result<Vec<UserRatingHistory>> {
+        Err(anyhow!("load_user_rating_history is not supported"))
+    }
+    async fn get_users_rating(&self, _user_id: &str) -> Result<Option<i32>> {
+        Err(anyhow!("get_users_rating is not supported"))
+    }
+}
+
+#[async_trait]
+impl RatingHistoryClient for PgPool {
+    async fn update_user_rating_history(
+        &self,
+        user_id: &str,
+        history: &[UserRatingHistory],
+    ) -> Result<()> {
+        let (
+            contest_ids,
+            is_rated,
+            ranks,
+            performances,
+            old_ratings,
+            new_ratings,
+            end_epoch_seconds,
+        ) = history.iter().fold(
+            (vec![], vec![], vec![], vec![], vec![], vec![], vec![]),
+            |(
+                mut contest_ids,
+                mut is_rated,
+                mut ranks,
+                mut performances,
+                mut old_ratings,
+                mut new_ratings,
+                mut end_epoch_seconds,
+            ),
+             cur| {
+                contest_ids.push(cur.contest_id.as_str());
+                is_rated.push(cur.is_rated);
+                ranks.push(cur.rank);
+                performances.push(cur.performance);
+                old_ratings.push(cur.old_rating);
+                new_ratings.push(cur.new_rating);
+                end_epoch_seconds.push(cur.end_epoch_second);
+                (
+                    contest_ids,
+                    is_rated,
+                    ranks,
+                    performances,
+                    old_ratings,
+                    new_ratings,
+                    end_epoch_seconds,
+                )
+            },
+        );
+
+        sqlx::query(
+            r"
+            INSERT INTO user_rating_history
+            (user_id, contest_id, is_rated, rank, performance, old_rating, new_rating, end_epoch_second)
+            VALUES (
+                $1,
+                UNNEST($2::VARCHAR(255)[]),
+                UNNEST($3::BOOLEAN[]),
+                UNNEST($4::INTEGER[]),
+                UNNEST($5::INTEGER[]),
+                UNNEST($6::INTEGER[]),
+                UNNEST($7::INTEGER[]),
+                UNNEST($8::BIGINT[])
+            )
+            ON CONFLICT (user_id, contest_id)
+            DO UPDATE SET
+                is_rated = EXCLUDED.is_rated,
+                rank = EXCLUDED.rank,
+                performance = EXCLUDED.performance,
+                old_rating = EXCLUDED.old_rating,
+                new_rating = EXCLUDED.new_rating,
+                end_epoch_second = EXCLUDED.end_epoch_second
+            ",
+        )
+        .bind(user_id)
+        .bind(contest_ids)
+        .bind(is_rated)
+        .bind(ranks)
+        .bind(performances)
+        .bind(old_ratings)
+        .bind(new_ratings)
+        .bind(end_epoch_seconds)
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_user_rating_history(&self, user_id: &str) -> Result<Vec<UserRatingHistory>> {
+        let history = sqlx::query_as(
+            r"
+            SELECT * FROM user_rating_history
+            WHERE LOWER(user_id) = LOWER($1)
+            ORDER BY end_epoch_second
+            ",
+        )
+        .bind(user_id)
+        .fetch_all(self)
+        .await?;
+        Ok(history)
+    }
+
+    async fn get_users_rating(&self, user_id: &str) -> Result<Option<i32>> {
+        let rating = sqlx::query(
+            r"
+            SELECT new_rating FROM user_rating_history
+            WHERE LOWER(user_id) = LOWER($1)
+            AND is_rated
+            ORDER BY end_epoch_second DESC
+            LIMIT 1
+            ",
+        )
+        .bind(user_id)
+        .try_map(|row: PgRow| row.try_get::<i32, _>("new_rating"))
+        .fetch_optional(self)
+        .await?;
+        Ok(rating)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::models::{ContestRevision, Problem, ProblemRevision};
+use crate::PgPool;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// History of the changes of contests and problems which have been crawled again.
+#[async_trait]
+pub trait RevisionClient {
+    async fn insert_contest_revisions(&self, revisions: &[ContestRevision]) -> Result<()>;
+
+    /// Overwrites the revised problems, recording the revisions at the same time.
+    /// The limits of the problems are kept if they are `None`.
+    async fn revise_problems(
+        &self,
+        problems: &[Problem],
+        revisions: &[ProblemRevision],
+    ) -> Result<()>;
+
+    async fn load_contest_revisions(&self, contest_id: &str) -> Result<Vec<ContestRevision>>;
+    async fn load_problem_revisions(&self, problem_id: &str) -> Result<Vec<ProblemRevision>>;
+}
+
+#[async_trait]
+impl RevisionClient for PgPool {
+    async fn insert_contest_revisions(&self, revisions: &[ContestRevision]) -> Result<()> {
+        let mut tx = self.begin().await?;
+        for revision in revisions {
+            sqlx::query(
+                r"
+                INSERT INTO contest_revisions
+                (contest_id, field, old_value, new_value, detected_epoch_second)
+                VALUES ($1, $2, $3, $4, $5)
+                ",
+            )
+            .bind(&revision.contest_id)
+            .bind(&revision.field)
+            .bind(&revision.old_value)
+            .bind(&revision.new_value)
+            .bind(revision.detected_epoch_second)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn revise_problems(
+        &self,
+        problems: &[Problem],
+        revisions: &[ProblemRevision],
+    ) -> Result<()> {
+        let mut tx = self.begin().await?;
+        for revision in revisions {
+            sqlx::query(
+                r"
+                INSERT INTO problem_revisions
+                (problem_id, field, old_value, new_value, detected_epoch_second)
+                VALUES ($1, $2, $3, $4, $5)
+                ",
+            )
+            .bind(&revision.problem_id)
+            .bind(&revision.field)
+            .bind(&revision.old_value)
+            .bind(&revision.new_value)
+            .bind(revision.detected_epoch_second)
+            .execute(&mut tx)
+            .await?;
+        }
+        for problem in problems {
+            sqlx::query(
+                r"
+                UPDATE problems
+                SET
+                    name = $2,
+                    title = $3,
+                    time_limit_ms = COALESCE($4, time_limit_ms),
+                    memory_limit_kb = COALESCE($5, memory_limit_kb)
+                WHERE id = $1
+                ",
+            )
+            .bind(&problem.id)
+            .bind(&problem.name)
+            .bind(&problem.title)
+            .bind(problem.time_limit_ms)
+            .bind(problem.memory_limit_kb)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_contest_revisions(&self, contest_id: &str) -> Result<Vec<ContestRevision>> {
+        let revisions = sqlx::query_as(
+            r"
+            SELECT * FROM contest_revisions
+            WHERE contest_id = $1
+            ORDER BY id
+            ",
+        )
+        .bind(contest_id)
+        .fetch_all(self)
+        .await?;
+        Ok(revisions)
+    }
+
+    async fn load_problem_revisions(&self, problem_id: &str) -> Result<Vec<ProblemRevision>> {
+        let revisions = sqlx::query_as(
+            r"
+            SELECT * FROM problem_revisions
+            WHERE problem_id = $1
+            ORDER BY id
+            ",
+        )
+        .bind(problem_id)
+        .fetch_all(self)
+        .await?;
+        Ok(revisions)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::PgPool;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::Row;
+
+/// Stores the cookies of AtCoder login sessions so that crawlers don't have to log in every time.
+#[async_trait]
+pub trait SessionClient {
+    async fn save_atcoder_session(&self, username: &str, session: &str) -> Result<()>;
+    async fn load_atcoder_session(&self, username: &str) -> Result<Option<String>>;
+}
+
+#[async_trait]
+impl SessionClient for PgPool {
+    async fn save_atcoder_session(&self, username: &str, session: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO atcoder_sessions (username, session, updated_epoch_second)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (username)
+            DO UPDATE SET
+                session = EXCLUDED.session,
+                updated_epoch_second = EXCLUDED.updated_epoch_second
+            ",
+        )
+        .bind(username)
+        .bind(session)
+        .bind(Utc::now().timestamp())
+        .execute(self)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_atcoder_session(&self, username: &str) -> Result<Option<String>> {
+        let session = sqlx::query("SELECT session FROM atcoder_sessions WHERE username = $1")
+            .bind(username)
+            .fetch_optional(self)
+            .await?
+            .map(|row| row.try_get::<String, _>("session"))
+            .transpose()?;
+        Ok(session)
+    }
+}
@@ -1,57 +1,103 @@
 use crate::models::{Contest, Problem};
 use crate::PgPool;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 
 #[async_trait]
-pub trait SimpleClient {
-    async fn insert_contests(&self, values: &[Contest]) -> Result<usize>;
-    async fn insert_problems(&self, values: &[Problem]) -> Result<usize>;
-    async fn load_problems(&self) -> Result<Vec<Problem>>;
-    async fn load_contests(&self) -> Result<Vec<Contest>>;
+pub trait SimpleClient: Sync {
+    async fn insert_contests(&self, _values: &[Contest]) -> Result<usize> {
+        Err(anyhow!("insert_contests is not supported"))
+    }
+    async fn insert_problems(&self, _values: &[Problem]) -> Result<usize> {
+        Err(anyhow!("insert_problems is not supported"))
+    }
+    async fn load_problems(&self) -> Result<Vec<Problem>> {
+        Err(anyhow!("load_problems is not supported"))
+    }
+    async fn load_contests(&self) -> Result<Vec<Contest>> {
+        Err(anyhow!("load_contests is not supported"))
+    }
 }
 
 #[async_trait]
 impl SimpleClient for PgPool {
     async fn insert_contests(&self, values: &[Contest]) -> Result<usize> {
-        let (ids, start_epoch_seconds, duration_seconds, titles, rate_changes) =
-            values.iter().fold(
-                (vec![], vec![], vec![], vec![], vec![]),
-                |(
-                    mut ids,
-                    mut start_epoch_seconds,
-                    mut duration_seconds,
-                    mut titles,
-                    mut rate_changes,
-                ),
-                 cur| {
-                    ids.push(cur.id.as_str());
-                    start_epoch_seconds.push(cur.start_epoch_second);
-                    duration_seconds.push(cur.duration_second);
-                    titles.push(cur.title.as_str());
-                    rate_changes.push(cur.rate_change.as_str());
-                    (
-                        ids,
-                        start_epoch_seconds,
-                        duration_seconds,
-                        titles,
-                        rate_changes,
-                    )
-                },
-            );
+        let (
+            ids,
+            start_epoch_seconds,
+            duration_seconds,
+            titles,
+            rate_changes,
+            categories,
+            rated_mins,
+            rated_maxes,
+        ) = values.iter().fold(
+            (
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+            ),
+            |(
+                mut ids,
+                mut start_epoch_seconds,
+                mut duration_seconds,
+                mut titles,
+                mut rate_changes,
+                mut categories,
+                mut rated_mins,
+                mut rated_maxes,
+            ),
+             cur| {
+                ids.push(cur.id.as_str());
+                start_epoch_seconds.push(cur.start_epoch_second);
+                duration_seconds.push(cur.duration_second);
+                titles.push(cur.title.as_str());
+                rate_changes.push(cur.rate_change.as_str());
+                categories.push(cur.category.as_str());
+                rated_mins.push(cur.rated_min);
+                rated_maxes.push(cur.rated_max);
+                (
+                    ids,
+                    start_epoch_seconds,
+                    duration_seconds,
+                    titles,
+                    rate_changes,
+                    categories,
+                    rated_mins,
+                    rated_maxes,
+                )
+            },
+        );
 
+        // Upcoming contests may be rescheduled or renamed before they start, so they are updated.
         let result = sqlx::query(
             r"
             INSERT INTO contests
-            (id, start_epoch_second, duration_second, title, rate_change)
+            (id, start_epoch_second, duration_second, title, rate_change, category, rated_min, rated_max)
             VALUES (
                 UNNEST($1::VARCHAR(255)[]),
                 UNNEST($2::BIGINT[]),
                 UNNEST($3::BIGINT[]),
                 UNNEST($4::VARCHAR(255)[]),
-                UNNEST($5::VARCHAR(255)[])
+                UNNEST($5::VARCHAR(255)[]),
+                UNNEST($6::VARCHAR(255)[]),
+                UNNEST($7::INT[]),
+                UNNEST($8::INT[])
             )
-            ON CONFLICT DO NOTHING
+            ON CONFLICT (id)
+            DO UPDATE SET
+                start_epoch_second = EXCLUDED.start_epoch_second,
+                duration_second = EXCLUDED.duration_second,
+                title = EXCLUDED.title,
+                rate_change = EXCLUDED.rate_change,
+                category = EXCLUDED.category,
+                rated_min = EXCLUDED.rated_min,
+                rated_max = EXCLUDED.rated_max
             ",
         )
         .bind(ids)
@@ -59,6 +105,9 @@ impl SimpleClient for PgPool {
         .bind(duration_seconds)
         .bind(titles)
         .bind(rate_changes)
+        .bind(categories)
+        .bind(rated_mins)
+        .bind(rated_maxes)
         .execute(self)
         .await?;
 
@@ -66,30 +115,57 @@ impl SimpleClient for PgPool {
     }
 
     async fn insert_problems(&self, values: &[Problem]) -> Result<usize> {
-        let (ids, contest_ids, problem_indexes, names, titles) = values.iter().fold(
-            (vec![], vec![], vec![], vec![], vec![]),
-            |(mut ids, mut contest_ids, mut problem_indexes, mut names, mut titles), cur| {
-                ids.push(cur.id.as_str());
-                contest_ids.push(cur.contest_id.as_str());
-                problem_indexes.push(cur.problem_index.as_str());
-                names.push(cur.name.as_str());
-                titles.push(cur.title.as_str());
-                (ids, contest_ids, problem_indexes, names, titles)
-            },
-        );
+        let (ids, contest_ids, problem_indexes, names, titles, time_limits, memory_limits) =
+            values.iter().fold(
+                (vec![], vec![], vec![], vec![], vec![], vec![], vec![]),
+                |(
+                    mut ids,
+                    mut contest_ids,
+                    mut problem_indexes,
+                    mut names,
+                    mut titles,
+                    mut time_limits,
+                    mut memory_limits,
+                ),
+                 cur| {
+                    ids.push(cur.id.as_str());
+                    contest_ids.push(cur.contest_id.as_str());
+                    problem_indexes.push(cur.problem_index.as_str());
+                    names.push(cur.name.as_str());
+                    titles.push(cur.title.as_str());
+                    time_limits.push(cur.time_limit_ms);
+                    memory_limits.push(cur.memory_limit_kb);
+                    (
+                        ids,
+                        contest_ids,
+                        problem_indexes,
+                        names,
+                        titles,
+                        time_limits,
+                        memory_limits,
+                    )
+                },
+            );
 
+        // The limits are filled for the problems stored before they were scraped,
+        // while the other fields are updated only through the revisions.
         let result = sqlx::query(
             r"
             INSERT INTO problems
-            (id, contest_id, problem_index, name, title)
+            (id, contest_id, problem_index, name, title, time_limit_ms, memory_limit_kb)
             VALUES (
                 UNNEST($1::VARCHAR(255)[]),
                 UNNEST($2::VARCHAR(255)[]),
                 UNNEST($3::VARCHAR(255)[]),
                 UNNEST($4::VARCHAR(255)[]),
-                UNNEST($5::VARCHAR(255)[])
+                UNNEST($5::VARCHAR(255)[]),
+                UNNEST($6::INTEGER[]),
+                UNNEST($7::INTEGER[])
             )
-            ON CONFLICT DO NOTHING
+            ON CONFLICT (id)
+            DO UPDATE SET
+                time_limit_ms = COALESCE(EXCLUDED.time_limit_ms, problems.time_limit_ms),
+                memory_limit_kb = COALESCE(EXCLUDED.memory_limit_kb, problems.memory_limit_kb)
             ",
         )
         .bind(ids)
@@ -97,6 +173,8 @@ impl SimpleClient for PgPool {
         .bind(problem_indexes)
         .bind(names)
         .bind(titles)
+        .bind(time_limits)
+        .bind(memory_limits)
         .execute(self)
         .await?;
 
@@ -104,10 +182,14 @@ impl SimpleClient for PgPool {
     }
 
     async fn load_problems(&self) -> Result<Vec<Problem>> {
-        let problems =
-            sqlx::query_as("SELECT id, contest_id, problem_index, name, title FROM problems")
-                .fetch_all(self)
-                .await?;
+        let problems = sqlx::query_as(
+            r"
+            SELECT id, contest_id, problem_index, name, title, time_limit_ms, memory_limit_kb
+            FROM problems
+            ",
+        )
+        .fetch_all(self)
+        .await?;
         Ok(problems)
     }
 
@@ -119,7 +201,10 @@ impl SimpleClient for PgPool {
                     start_epoch_second,
                     duration_second,
                     title,
-                    rate_change
+                    rate_change,
+                    category,
+                    rated_min,
+                    rated_max
                  FROM contests
                  ",
         )
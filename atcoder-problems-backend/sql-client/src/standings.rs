@@ -0,0 +1,235 @@
+use crate::models::{ContestStanding, ContestStandingTaskResult};
+use crate::{PgPool, PgRow, MAX_INSERT_ROWS};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use sqlx::Row;
+
+#[async_trait]
+pub trait StandingsClient: Sync {
+    async fn update_contest_standings(
+        &self,
+        contest_id: &str,
+        standings: &[ContestStanding],
+        task_results: &[ContestStandingTaskResult],
+    ) -> Result<()>;
+    async fn load_contest_standings(&self, _contest_id: &str) -> Result<Vec<ContestStanding>> {
+        Err(anyhow!("load_contest_standings is not supported"))
+    }
+    async fn load_contest_standings_task_results(
+        &self,
+        _contest_id: &str,
+    ) -> Result<Vec<ContestStandingTaskResult>> {
+        Err(anyhow!(
+            "load_contest_standings_task_results is not supported"
+        ))
+    }
+    async fn load_user_standings(&self, _user_id: &str) -> Result<Vec<ContestStanding>> {
+        Err(anyhow!("load_user_standings is not supported"))
+    }
+    async fn load_standings_contest_ids(&self) -> Result<Vec<String>>;
+}
+
+#[async_trait]
+impl StandingsClient for PgPool {
+    async fn update_contest_standings(
+        &self,
+        contest_id: &str,
+        standings: &[ContestStanding],
+        task_results: &[ContestStandingTaskResult],
+    ) -> Result<()> {
+        let mut tx = self.begin().await?;
+
+        // Standings are replaced as a whole, since a rejudge may change every row.
+        sqlx::query("DELETE FROM contest_standings WHERE contest_id = $1")
+            .bind(contest_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM contest_standings_task_results WHERE contest_id = $1")
+            .bind(contest_id)
+            .execute(&mut tx)
+            .await?;
+
+        for chunk in standings.chunks(MAX_INSERT_ROWS) {
+            let (
+                user_ids,
+                ranks,
+                is_rated,
+                old_ratings,
+                new_ratings,
+                scores,
+                penalties,
+                elapsed_seconds,
+            ) = chunk.iter().fold(
+                (
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                ),
+                |(
+                    mut user_ids,
+                    mut ranks,
+                    mut is_rated,
+                    mut old_ratings,
+                    mut new_ratings,
+                    mut scores,
+                    mut penalties,
+                    mut elapsed_seconds,
+                ),
+                 cur| {
+                    user_ids.push(cur.user_id.as_str());
+                    ranks.push(cur.rank);
+                    is_rated.push(cur.is_rated);
+                    old_ratings.push(cur.old_rating);
+                    new_ratings.push(cur.new_rating);
+                    scores.push(cur.score);
+                    penalties.push(cur.penalty);
+                    elapsed_seconds.push(cur.elapsed_second);
+                    (
+                        user_ids,
+                        ranks,
+                        is_rated,
+                        old_ratings,
+                        new_ratings,
+                        scores,
+                        penalties,
+                        elapsed_seconds,
+                    )
+                },
+            );
+
+            sqlx::query(
+                r"
+                INSERT INTO contest_standings
+                (contest_id, user_id, rank, is_rated, old_rating, new_rating, score, penalty, elapsed_second)
+                VALUES (
+                    $1,
+                    UNNEST($2::VARCHAR(255)[]),
+                    UNNEST($3::INTEGER[]),
+                    UNNEST($4::BOOLEAN[]),
+                    UNNEST($5::INTEGER[]),
+                    UNNEST($6::INTEGER[]),
+                    UNNEST($7::DOUBLE PRECISION[]),
+                    UNNEST($8::INTEGER[]),
+                    UNNEST($9::BIGINT[])
+                )
+                ",
+            )
+            .bind(contest_id)
+            .bind(user_ids)
+            .bind(ranks)
+            .bind(is_rated)
+            .bind(old_ratings)
+            .bind(new_ratings)
+            .bind(scores)
+            .bind(penalties)
+            .bind(elapsed_seconds)
+            .execute(&mut tx)
+            .await?;
+        }
+
+        for chunk in task_results.chunks(MAX_INSERT_ROWS) {
+            let (user_ids, problem_ids, scores, penalties, elapsed_seconds) = chunk.iter().fold(
+                (vec![], vec![], vec![], vec![], vec![]),
+                |(
+                    mut user_ids,
+                    mut problem_ids,
+                    mut scores,
+                    mut penalties,
+                    mut elapsed_seconds,
+                ),
+                 cur| {
+                    user_ids.push(cur.user_id.as_str());
+                    problem_ids.push(cur.problem_id.as_str());
+                    scores.push(cur.score);
+                    penalties.push(cur.penalty);
+                    elapsed_seconds.push(cur.elapsed_second);
+                    (user_ids, problem_ids, scores, penalties, elapsed_seconds)
+                },
+            );
+
+            sqlx::query(
+                r"
+                INSERT INTO contest_standings_task_results
+                (contest_id, user_id, problem_id, score, penalty, elapsed_second)
+                VALUES (
+                    $1,
+                    UNNEST($2::VARCHAR(255)[]),
+                    UNNEST($3::VARCHAR(255)[]),
+                    UNNEST($4::DOUBLE PRECISION[]),
+                    UNNEST($5::INTEGER[]),
+                    UNNEST($6::BIGINT[])
+                )
+                ",
+            )
+            .bind(contest_id)
+            .bind(user_ids)
+            .bind(problem_ids)
+            .bind(scores)
+            .bind(penalties)
+            .bind(elapsed_seconds)
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_contest_standings(&self, contest_id: &str) -> Result<Vec<ContestStanding>> {
+        let standings = sqlx::query_as(
+            r"
+            SELECT * FROM contest_standings
+            WHERE contest_id = $1
+            ORDER BY rank, user_id
+            ",
+        )
+        .bind(contest_id)
+        .fetch_all(self)
+        .await?;
+        Ok(standings)
+    }
+
+    async fn load_contest_standings_task_results(
+        &self,
+        contest_id: &str,
+    ) -> Result<Vec<ContestStandingTaskResult>> {
+        let task_results = sqlx::query_as(
+            r"
+            SELECT * FROM contest_standings_task_results
+            WHERE contest_id = $1
+            ORDER BY user_id, problem_id
+            ",
+        )
+        .bind(contest_id)
+        .fetch_all(self)
+        .await?;
+        Ok(task_results)
+    }
+
+    async fn load_user_standings(&self, user_id: &str) -> Result<Vec<ContestStanding>> {
+        let standings = sqlx::query_as(
+            r"
+            SELECT * FROM contest_standings
+            WHERE LOWER(user_id) = LOWER($1)
+            ORDER BY contest_id
+            ",
+        )
+        .bind(user_id)
+        .fetch_all(self)
+        .await?;
+        Ok(standings)
+    }
+
+    async fn load_standings_contest_ids(&self) -> Result<Vec<String>> {
+        let contest_ids = sqlx::query("SELECT DISTINCT contest_id FROM contest_standings")
+            .try_map(|row: PgRow| row.try_get::<String, _>("contest_id"))
+            .fetch_all(self)
+            .await?;
+        Ok(contest_ids)
+    }
+}
@@ -3,7 +3,7 @@ use crate::{PgPool, MAX_INSERT_ROWS};
 use anyhow::Result;
 use async_trait::async_trait;
 
-use sqlx::Row;
+use sqlx::{PgConnection, Row};
 
 use chrono::Duration;
 use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
@@ -74,57 +74,68 @@ impl StreakClient for PgPool {
     }
 
     async fn update_streak_count(&self, ac_submissions: &[Submission]) -> Result<()> {
-        let mut submissions = ac_submissions
-            .iter()
-            .map(|s| {
-                (
-                    Utc.timestamp(s.epoch_second, 0),
-                    s.user_id.as_str(),
-                    s.problem_id.as_str(),
-                )
-            })
-            .collect::<Vec<_>>();
-        submissions.sort_by_key(|&(timestamp, _, _)| timestamp);
-        let first_ac_map = submissions.into_iter().fold(
-            BTreeMap::new(),
-            |mut map, (epoch_second, user_id, problem_id)| {
-                map.entry(user_id)
-                    .or_insert_with(BTreeMap::new)
-                    .entry(problem_id)
-                    .or_insert(epoch_second);
-                map
-            },
-        );
-
-        let user_max_streak = first_ac_map
-            .into_iter()
-            .map(|(user_id, m)| {
-                let max_streak = get_max_streak(m.into_values().collect());
-                (user_id, max_streak)
-            })
-            .collect::<Vec<_>>();
-
-        for chunk in user_max_streak.chunks(MAX_INSERT_ROWS) {
-            let (user_ids, max_streaks): (Vec<&str>, Vec<i64>) = chunk.iter().copied().unzip();
-            sqlx::query(
-                r"
-                INSERT INTO max_streaks (user_id, streak)
-                VALUES (
-                    UNNEST($1::VARCHAR(255)[]),
-                    UNNEST($2::BIGINT[])
-                )
-                ON CONFLICT (user_id)
-                DO UPDATE SET streak = EXCLUDED.streak
-                ",
+        let user_max_streak = max_streaks(ac_submissions);
+        let mut conn = self.acquire().await?;
+        save_max_streaks(&mut conn, &user_max_streak).await
+    }
+}
+
+/// Calculates the longest streak of each user, counting only the first accepted submission of each problem.
+pub(crate) fn max_streaks(ac_submissions: &[Submission]) -> Vec<(&str, i64)> {
+    let mut submissions = ac_submissions
+        .iter()
+        .map(|s| {
+            (
+                Utc.timestamp(s.epoch_second, 0),
+                s.user_id.as_str(),
+                s.problem_id.as_str(),
             )
-            .bind(user_ids)
-            .bind(max_streaks)
-            .execute(self)
-            .await?;
-        }
+        })
+        .collect::<Vec<_>>();
+    submissions.sort_by_key(|&(timestamp, _, _)| timestamp);
+    let first_ac_map = submissions.into_iter().fold(
+        BTreeMap::new(),
+        |mut map, (epoch_second, user_id, problem_id)| {
+            map.entry(user_id)
+                .or_insert_with(BTreeMap::new)
+                .entry(problem_id)
+                .or_insert(epoch_second);
+            map
+        },
+    );
+
+    first_ac_map
+        .into_iter()
+        .map(|(user_id, m)| {
+            let max_streak = get_max_streak(m.into_values().collect());
+            (user_id, max_streak)
+        })
+        .collect()
+}
 
-        Ok(())
+pub(crate) async fn save_max_streaks(
+    conn: &mut PgConnection,
+    user_max_streak: &[(&str, i64)],
+) -> Result<()> {
+    for chunk in user_max_streak.chunks(MAX_INSERT_ROWS) {
+        let (user_ids, max_streaks): (Vec<&str>, Vec<i64>) = chunk.iter().copied().unzip();
+        sqlx::query(
+            r"
+            INSERT INTO max_streaks (user_id, streak)
+            VALUES (
+                UNNEST($1::VARCHAR(255)[]),
+                UNNEST($2::BIGINT[])
+            )
+            ON CONFLICT (user_id)
+            DO UPDATE SET streak = EXCLUDED.streak
+            ",
+        )
+        .bind(user_ids)
+        .bind(max_streaks)
+        .execute(&mut *conn)
+        .await?;
     }
+    Ok(())
 }
 
 fn get_max_streak<Tz: TimeZone>(mut v: Vec<DateTime<Tz>>) -> i64 {
@@ -1,6 +1,6 @@
-use crate::models::Submission;
+use crate::models::{Submission, SubmissionDetail, SubmissionTestcase};
 use crate::PgPool;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use sqlx::Row;
 use std::ops::Range;
@@ -11,6 +11,9 @@ pub enum SubmissionRequest<'a> {
     UserAll {
         user_id: &'a str,
     },
+    ContestAll {
+        contest_id: &'a str,
+    },
     UsersAccepted {
         user_ids: &'a [&'a str],
     },
@@ -29,8 +32,10 @@ pub enum SubmissionRequest<'a> {
     RecentAll {
         count: i64,
     },
+    /// Submissions whose results are none of `final_results`.
     InvalidResult {
         from_second: i64,
+        final_results: &'a [String],
     },
     AllAccepted,
     ByIds {
@@ -44,10 +49,19 @@ pub enum SubmissionRequest<'a> {
     },
 }
 
+/// The methods fail unless a client implements them, e.g. the mocks of crawler tests.
 #[async_trait]
-pub trait SubmissionClient {
-    async fn get_submissions<'a>(&self, request: SubmissionRequest<'a>) -> Result<Vec<Submission>>;
-    async fn update_submissions(&self, values: &[Submission]) -> Result<usize>;
+pub trait SubmissionClient: Sync {
+    async fn get_submissions<'a>(
+        &self,
+        _request: SubmissionRequest<'a>,
+    ) -> Result<Vec<Submission>> {
+        Err(anyhow!("get_submissions is not supported"))
+    }
+    /// Stores the submissions, recording the problems they reference if they are not known yet.
+    async fn update_submissions(&self, _values: &[Submission]) -> Result<usize> {
+        Err(anyhow!("update_submissions is not supported"))
+    }
     async fn count_stored_submissions(&self, ids: &[i64]) -> Result<usize> {
         let submissions = self
             .get_submissions(SubmissionRequest::ByIds { ids })
@@ -55,7 +69,26 @@ pub trait SubmissionClient {
         Ok(submissions.len())
     }
 
-    async fn get_user_submission_count(&self, user_id: &str, range: Range<i64>) -> Result<usize>;
+    async fn get_user_submission_count(&self, _user_id: &str, _range: Range<i64>) -> Result<usize> {
+        Err(anyhow!("get_user_submission_count is not supported"))
+    }
+
+    async fn update_submission_detail(
+        &self,
+        _detail: &SubmissionDetail,
+        _testcases: &[SubmissionTestcase],
+    ) -> Result<()> {
+        Err(anyhow!("update_submission_detail is not supported"))
+    }
+    async fn get_submission_detail(&self, _submission_id: i64) -> Result<Option<SubmissionDetail>> {
+        Err(anyhow!("get_submission_detail is not supported"))
+    }
+    async fn get_submission_testcases(
+        &self,
+        _submission_ids: &[i64],
+    ) -> Result<Vec<SubmissionTestcase>> {
+        Err(anyhow!("get_submission_testcases is not supported"))
+    }
 }
 
 #[async_trait]
@@ -70,6 +103,14 @@ impl SubmissionClient for PgPool {
             )
             .bind(user_id)
             .fetch_all(self),
+            SubmissionRequest::ContestAll { contest_id } => sqlx::query_as(
+                r"
+                    SELECT * FROM submissions
+                    WHERE contest_id = $1
+                    ",
+            )
+            .bind(contest_id)
+            .fetch_all(self),
             SubmissionRequest::FromTime { from_second, count } => sqlx::query_as(
                 r"
                          SELECT * FROM submissions
@@ -133,19 +174,21 @@ impl SubmissionClient for PgPool {
                     ",
             )
             .fetch_all(self),
-            SubmissionRequest::InvalidResult { from_second } => sqlx::query_as(
+            SubmissionRequest::InvalidResult {
+                from_second,
+                final_results,
+            } => sqlx::query_as(
                 r"
                     SELECT * FROM submissions
                     WHERE
-                        result != ALL(
-                            ARRAY['AC', 'WA', 'TLE', 'CE', 'RE', 'MLE', 'OLE', 'QLE', 'IE', 'NG']
-                        )
+                        result != ALL($2)
                     AND
                         epoch_second >= $1
                     ORDER BY id DESC
                     ",
             )
             .bind(from_second)
+            .bind(final_results)
             .fetch_all(self),
             SubmissionRequest::ByIds { ids } => sqlx::query_as(
                 r"
@@ -193,6 +236,7 @@ impl SubmissionClient for PgPool {
             lengths,
             results,
             execution_times,
+            memories,
         ) = values.iter().fold(
             (
                 vec![],
@@ -205,6 +249,7 @@ impl SubmissionClient for PgPool {
                 vec![],
                 vec![],
                 vec![],
+                vec![],
             ),
             |(
                 mut ids,
@@ -217,6 +262,7 @@ impl SubmissionClient for PgPool {
                 mut lengths,
                 mut results,
                 mut execution_times,
+                mut memories,
             ),
              cur| {
                 ids.push(cur.id);
@@ -229,6 +275,7 @@ impl SubmissionClient for PgPool {
                 lengths.push(cur.length);
                 results.push(cur.result.as_str());
                 execution_times.push(cur.execution_time);
+                memories.push(cur.memory);
 
                 (
                     ids,
@@ -241,6 +288,7 @@ impl SubmissionClient for PgPool {
                     lengths,
                     results,
                     execution_times,
+                    memories,
                 )
             },
         );
@@ -257,7 +305,8 @@ impl SubmissionClient for PgPool {
                 point,
                 length,
                 result,
-                execution_time
+                execution_time,
+                memory
             )
             VALUES (
                 UNNEST($1::BIGINT[]),
@@ -269,14 +318,16 @@ impl SubmissionClient for PgPool {
                 UNNEST($7::FLOAT8[]),
                 UNNEST($8::INTEGER[]),
                 UNNEST($9::VARCHAR(255)[]),
-                UNNEST($10::INTEGER[])
+                UNNEST($10::INTEGER[]),
+                UNNEST($11::INTEGER[])
             )
             ON CONFLICT (id)
             DO UPDATE SET
                 user_id = EXCLUDED.user_id,
                 result = EXCLUDED.result,
                 point = EXCLUDED.point,
-                execution_time = EXCLUDED.execution_time
+                execution_time = EXCLUDED.execution_time,
+                memory = EXCLUDED.memory
             ",
         )
         .bind(ids)
@@ -289,6 +340,7 @@ impl SubmissionClient for PgPool {
         .bind(lengths)
         .bind(results)
         .bind(execution_times)
+        .bind(memories)
         .execute(self)
         .await?;
         Ok(count.rows_affected() as usize)
@@ -311,4 +363,96 @@ impl SubmissionClient for PgPool {
         .await?;
         Ok(count as usize)
     }
+    async fn update_submission_detail(
+        &self,
+        detail: &SubmissionDetail,
+        testcases: &[SubmissionTestcase],
+    ) -> Result<()> {
+        let (case_names, results, execution_times, memories) = testcases.iter().fold(
+            (vec![], vec![], vec![], vec![]),
+            |(mut case_names, mut results, mut execution_times, mut memories), cur| {
+                case_names.push(cur.case_name.as_str());
+                results.push(cur.result.as_str());
+                execution_times.push(cur.execution_time);
+                memories.push(cur.memory);
+                (case_names, results, execution_times, memories)
+            },
+        );
+
+        let mut tx = self.begin().await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO submission_details (submission_id, compile_error)
+            VALUES ($1, $2)
+            ON CONFLICT (submission_id)
+            DO UPDATE SET compile_error = EXCLUDED.compile_error
+            ",
+        )
+        .bind(detail.submission_id)
+        .bind(detail.compile_error.as_deref())
+        .execute(&mut tx)
+        .await?;
+
+        // Testcases are replaced as a whole, since a rejudge may rename or drop some of them.
+        sqlx::query("DELETE FROM submission_testcases WHERE submission_id = $1")
+            .bind(detail.submission_id)
+            .execute(&mut tx)
+            .await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO submission_testcases
+            (submission_id, case_name, result, execution_time, memory)
+            VALUES (
+                $1,
+                UNNEST($2::VARCHAR(255)[]),
+                UNNEST($3::VARCHAR(255)[]),
+                UNNEST($4::INTEGER[]),
+                UNNEST($5::INTEGER[])
+            )
+            ",
+        )
+        .bind(detail.submission_id)
+        .bind(case_names)
+        .bind(results)
+        .bind(execution_times)
+        .bind(memories)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_submission_detail(&self, submission_id: i64) -> Result<Option<SubmissionDetail>> {
+        let detail = sqlx::query_as(
+            r"
+            SELECT submission_id, compile_error FROM submission_details
+            WHERE submission_id = $1
+            ",
+        )
+        .bind(submission_id)
+        .fetch_optional(self)
+        .await?;
+        Ok(detail)
+    }
+
+    async fn get_submission_testcases(
+        &self,
+        submission_ids: &[i64],
+    ) -> Result<Vec<SubmissionTestcase>> {
+        let testcases = sqlx::query_as(
+            r"
+            SELECT submission_id, case_name, result, execution_time, memory
+            FROM submission_testcases
+            WHERE submission_id = ANY($1)
+            ORDER BY submission_id, case_name
+            ",
+        )
+        .bind(submission_ids)
+        .fetch_all(self)
+        .await?;
+        Ok(testcases)
+    }
 }
@@ -0,0 +1,171 @@
+use crate::models::{Submission, SubmissionChange};
+use crate::PgPool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+/// The verdict or the point of a submission has changed after it was judged.
+pub const SUBMISSION_REJUDGED: &str = "rejudged";
+/// The submission has disappeared from AtCoder.
+pub const SUBMISSION_VANISHED: &str = "vanished";
+/// The vanished submission has been put back.
+pub const SUBMISSION_RESTORED: &str = "restored";
+
+const SUBMISSION_COLUMNS: &str = "id, epoch_second, problem_id, contest_id, user_id, language, point, length, result, execution_time, memory";
+
+/// History of the changes of stored submissions, which are found by reconciling them with AtCoder.
+#[async_trait]
+pub trait SubmissionHistoryClient: Sync {
+    async fn insert_submission_changes(&self, changes: &[SubmissionChange]) -> Result<()>;
+
+    /// Moves submissions which have disappeared from AtCoder to `vanished_submissions`,
+    /// and records them in the history with `SUBMISSION_VANISHED`.
+    /// Their details are kept, so that they can be restored as they were.
+    async fn remove_vanished_submissions(
+        &self,
+        submissions: &[Submission],
+        epoch_second: i64,
+    ) -> Result<()>;
+
+    /// Puts the vanished submissions back, e.g. when they have been removed by mistake,
+    /// records them in the history with `SUBMISSION_RESTORED`, and returns them.
+    async fn restore_vanished_submissions(
+        &self,
+        _submission_ids: &[i64],
+        _epoch_second: i64,
+    ) -> Result<Vec<Submission>> {
+        Err(anyhow!("restore_vanished_submissions is not supported"))
+    }
+
+    /// Returns the changes of a submission in the order they have been detected.
+    async fn load_submission_history(&self, _submission_id: i64) -> Result<Vec<SubmissionChange>> {
+        Err(anyhow!("load_submission_history is not supported"))
+    }
+}
+
+#[async_trait]
+impl SubmissionHistoryClient for PgPool {
+    async fn insert_submission_changes(&self, changes: &[SubmissionChange]) -> Result<()> {
+        let mut tx = self.begin().await?;
+        for change in changes {
+            insert_change(change).execute(&mut tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn remove_vanished_submissions(
+        &self,
+        submissions: &[Submission],
+        epoch_second: i64,
+    ) -> Result<()> {
+        let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+        let mut tx = self.begin().await?;
+        for submission in submissions {
+            let change = SubmissionChange::vanished(submission, epoch_second);
+            insert_change(&change).execute(&mut tx).await?;
+        }
+        let query = format!(
+            r"
+            INSERT INTO vanished_submissions ({columns}, vanished_epoch_second)
+            SELECT {columns}, $2 FROM submissions WHERE id = ANY($1)
+            ON CONFLICT (id) DO UPDATE
+            SET vanished_epoch_second = EXCLUDED.vanished_epoch_second
+            ",
+            columns = SUBMISSION_COLUMNS
+        );
+        sqlx::query(&query)
+            .bind(&ids)
+            .bind(epoch_second)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM submissions WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn restore_vanished_submissions(
+        &self,
+        submission_ids: &[i64],
+        epoch_second: i64,
+    ) -> Result<Vec<Submission>> {
+        // The ones which have been crawled again in the meantime are left as they are.
+        let mut tx = self.begin().await?;
+        let query = format!(
+            r"
+            WITH restored AS (
+                DELETE FROM vanished_submissions WHERE id = ANY($1)
+                RETURNING {columns}
+            )
+            INSERT INTO submissions ({columns})
+            SELECT {columns} FROM restored
+            ON CONFLICT (id) DO NOTHING
+            RETURNING {columns}
+            ",
+            columns = SUBMISSION_COLUMNS
+        );
+        let submissions: Vec<Submission> = sqlx::query_as(&query)
+            .bind(submission_ids)
+            .fetch_all(&mut tx)
+            .await?;
+        for submission in submissions.iter() {
+            let change = SubmissionChange {
+                kind: SUBMISSION_RESTORED.to_string(),
+                new_result: Some(submission.result.clone()),
+                new_point: Some(submission.point),
+                ..SubmissionChange::vanished(submission, epoch_second)
+            };
+            insert_change(&change).execute(&mut tx).await?;
+        }
+        tx.commit().await?;
+        Ok(submissions)
+    }
+
+    async fn load_submission_history(&self, submission_id: i64) -> Result<Vec<SubmissionChange>> {
+        let changes = sqlx::query_as(
+            r"
+            SELECT * FROM submission_history
+            WHERE submission_id = $1
+            ORDER BY id
+            ",
+        )
+        .bind(submission_id)
+        .fetch_all(self)
+        .await?;
+        Ok(changes)
+    }
+}
+
+fn insert_change(
+    change: &SubmissionChange,
+) -> sqlx::query::Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    sqlx::query(
+        r"
+        INSERT INTO submission_history (
+            submission_id,
+            kind,
+            contest_id,
+            problem_id,
+            user_id,
+            old_result,
+            new_result,
+            old_point,
+            new_point,
+            detected_epoch_second
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ",
+    )
+    .bind(change.submission_id)
+    .bind(change.kind.as_str())
+    .bind(change.contest_id.as_str())
+    .bind(change.problem_id.as_str())
+    .bind(change.user_id.as_str())
+    .bind(change.old_result.as_str())
+    .bind(change.new_result.as_deref())
+    .bind(change.old_point)
+    .bind(change.new_point)
+    .bind(change.detected_epoch_second)
+}
@@ -0,0 +1,60 @@
+use crate::accepted_count::{count_accepted, save_accepted_count};
+use crate::language_count::{count_languages, save_language_count};
+use crate::models::Submission;
+use crate::rated_point_sum::{load_rated_problem_ids, save_rated_point_sum, sum_rated_points};
+use crate::streak::{max_streaks, save_max_streaks};
+use crate::PgPool;
+use anyhow::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait UserStatisticsClient {
+    /// Replaces the accepted count, the rated point sum, the language count and the streak
+    /// of the users with the ones calculated from `ac_submissions`, all the accepted submissions
+    /// of the users, so that none of them is left stale when some accepted problems are lost.
+    /// The old statistics are deleted in the same transaction as the new ones are written,
+    /// so that they are never seen missing nor lost on a failure.
+    async fn replace_user_statistics(
+        &self,
+        user_ids: &[&str],
+        ac_submissions: &[Submission],
+    ) -> Result<()>;
+}
+
+#[async_trait]
+impl UserStatisticsClient for PgPool {
+    async fn replace_user_statistics(
+        &self,
+        user_ids: &[&str],
+        ac_submissions: &[Submission],
+    ) -> Result<()> {
+        let rated_problem_ids = load_rated_problem_ids(self).await?;
+        let rated_point_sum = sum_rated_points(ac_submissions, &rated_problem_ids)?;
+        let accepted_count = count_accepted(ac_submissions);
+        let language_count = count_languages(ac_submissions);
+        let max_streaks = max_streaks(ac_submissions);
+
+        let mut tx = self.begin().await?;
+        for table in [
+            "accepted_count",
+            "rated_point_sum",
+            "language_count",
+            "max_streaks",
+        ] {
+            let query = format!(
+                r"
+                DELETE FROM {}
+                WHERE LOWER(user_id) = ANY(SELECT LOWER(u) FROM UNNEST($1) AS a(u))
+                ",
+                table
+            );
+            sqlx::query(&query).bind(user_ids).execute(&mut tx).await?;
+        }
+        save_accepted_count(&mut tx, &accepted_count).await?;
+        save_rated_point_sum(&mut tx, &rated_point_sum).await?;
+        save_language_count(&mut tx, language_count).await?;
+        save_max_streaks(&mut tx, &max_streaks).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
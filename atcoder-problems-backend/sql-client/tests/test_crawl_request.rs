@@ -0,0 +1,71 @@
+use sql_client::crawl_request::{
+    CrawlRequestClient, CRAWL_REQUEST_FAILED, CRAWL_REQUEST_FINISHED, CRAWL_REQUEST_QUEUED,
+    CRAWL_REQUEST_RUNNING,
+};
+
+mod utils;
+
+#[tokio::test]
+async fn test_crawl_request() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert_eq!(pool.get_latest_crawl_request("user1").await.unwrap(), None);
+    assert_eq!(pool.pop_crawl_request().await.unwrap(), None);
+
+    let request1 = pool.insert_crawl_request("user1").await.unwrap();
+    assert_eq!(request1.status, CRAWL_REQUEST_QUEUED);
+    let request2 = pool.insert_crawl_request("user2").await.unwrap();
+    // The pending request is returned instead of queuing another one.
+    assert_eq!(pool.insert_crawl_request("USER1").await.unwrap(), request1);
+    assert_eq!(
+        pool.get_latest_crawl_request("USER1").await.unwrap(),
+        Some(request1.clone())
+    );
+
+    let popped = pool.pop_crawl_request().await.unwrap().unwrap();
+    assert_eq!(popped.id, request1.id);
+    assert_eq!(popped.status, CRAWL_REQUEST_RUNNING);
+    assert!(popped.started_epoch_second.is_some());
+
+    pool.finish_crawl_request(popped.id, None).await.unwrap();
+    let request = pool
+        .get_latest_crawl_request("user1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(request.status, CRAWL_REQUEST_FINISHED);
+    assert!(request.finished_epoch_second.is_some());
+
+    let popped = pool.pop_crawl_request().await.unwrap().unwrap();
+    assert_eq!(popped.id, request2.id);
+    pool.finish_crawl_request(popped.id, Some("error"))
+        .await
+        .unwrap();
+    let request = pool
+        .get_latest_crawl_request("user2")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(request.status, CRAWL_REQUEST_FAILED);
+    assert_eq!(request.error.as_deref(), Some("error"));
+
+    assert_eq!(pool.pop_crawl_request().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_requeue_stale_crawl_requests() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let request = pool.insert_crawl_request("user1").await.unwrap();
+    let popped = pool.pop_crawl_request().await.unwrap().unwrap();
+    assert_eq!(popped.id, request.id);
+    assert_eq!(pool.requeue_stale_crawl_requests(3600).await.unwrap(), 0);
+
+    // The worker has crashed an hour ago.
+    sqlx::query("UPDATE crawl_requests SET started_epoch_second = started_epoch_second - 3601")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(pool.requeue_stale_crawl_requests(3600).await.unwrap(), 1);
+    let popped = pool.pop_crawl_request().await.unwrap().unwrap();
+    assert_eq!(popped.id, request.id);
+    assert_eq!(popped.status, CRAWL_REQUEST_RUNNING);
+}
@@ -0,0 +1,70 @@
+use sql_client::crawl_state::CrawlStateClient;
+
+mod utils;
+
+#[tokio::test]
+async fn test_crawl_state() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert!(pool.load_crawl_states("whole").await.unwrap().is_empty());
+
+    pool.save_crawl_state("whole", "abc001", 1, 10, None)
+        .await
+        .unwrap();
+    pool.save_crawl_state("whole", "abc001", 2, 11, Some(100))
+        .await
+        .unwrap();
+    pool.save_crawl_state("whole", "abc002", 5, 5, None)
+        .await
+        .unwrap();
+    pool.save_crawl_state("recent", "abc001", 3, 11, Some(50))
+        .await
+        .unwrap();
+
+    let state = pool
+        .load_crawl_state("whole", "abc001")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (state.last_page, state.max_page, state.before_id),
+        (2, 11, Some(100))
+    );
+    assert_eq!(pool.load_crawl_states("whole").await.unwrap().len(), 2);
+
+    pool.delete_crawl_state("whole", "abc001").await.unwrap();
+    assert_eq!(
+        pool.load_crawl_state("whole", "abc001").await.unwrap(),
+        None
+    );
+    assert!(pool
+        .load_crawl_state("recent", "abc001")
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_last_crawled() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert!(pool.load_last_crawled("recent").await.unwrap().is_empty());
+
+    pool.save_last_crawled("recent", "abc001", 100)
+        .await
+        .unwrap();
+    pool.save_last_crawled("recent", "abc001", 200)
+        .await
+        .unwrap();
+    pool.save_last_crawled("recent", "abc002", 150)
+        .await
+        .unwrap();
+    pool.save_last_crawled("whole", "abc001", 300)
+        .await
+        .unwrap();
+
+    let mut last_crawled = pool.load_last_crawled("recent").await.unwrap();
+    last_crawled.sort();
+    assert_eq!(
+        last_crawled,
+        vec![("abc001".to_string(), 200), ("abc002".to_string(), 150)]
+    );
+}
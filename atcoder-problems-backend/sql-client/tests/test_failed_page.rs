@@ -0,0 +1,70 @@
+use sql_client::failed_page::FailedPageClient;
+use sql_client::models::FailedPage;
+
+mod utils;
+
+#[tokio::test]
+async fn test_failed_page() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert!(pool.load_failed_pages().await.unwrap().is_empty());
+
+    pool.record_failed_page("whole_contest", "abc001", 3, Some(100), "timeout")
+        .await
+        .unwrap();
+    pool.record_failed_page("recent", "abc002", 1, None, "parse error")
+        .await
+        .unwrap();
+    pool.record_failed_page("failed_page", "abc001", 3, None, "HTTP 503")
+        .await
+        .unwrap();
+
+    let pages = pool.load_failed_pages().await.unwrap();
+    assert_eq!(pages.len(), 2);
+    let page = pages.iter().find(|p| p.contest_id == "abc001").unwrap();
+    assert_eq!(page.page, 3);
+    assert_eq!(page.attempts, 2);
+    assert_eq!(page.crawler, "whole_contest");
+    assert_eq!(page.error, "HTTP 503");
+    assert_eq!(page.before_id, Some(100));
+
+    pool.delete_failed_page("abc001", 3).await.unwrap();
+    let pages = pool.load_failed_pages().await.unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].contest_id, "abc002");
+
+    // Merging keeps the larger attempts and the anchor of the page which has failed here.
+    pool.merge_failed_pages(&[
+        FailedPage {
+            contest_id: "abc002".to_string(),
+            page: 1,
+            crawler: "whole_contest".to_string(),
+            error: "timeout".to_string(),
+            attempts: 5,
+            first_failed_epoch_second: 0,
+            last_failed_epoch_second: 0,
+            before_id: Some(200),
+        },
+        FailedPage {
+            contest_id: "abc003".to_string(),
+            page: 2,
+            crawler: "recent".to_string(),
+            error: "timeout".to_string(),
+            attempts: 3,
+            first_failed_epoch_second: 0,
+            last_failed_epoch_second: 0,
+            before_id: Some(300),
+        },
+    ])
+    .await
+    .unwrap();
+    let pages = pool.load_failed_pages().await.unwrap();
+    assert_eq!(pages.len(), 2);
+    let page = pages.iter().find(|p| p.contest_id == "abc002").unwrap();
+    assert_eq!(page.attempts, 5);
+    assert_eq!(page.crawler, "recent");
+    assert_eq!(page.error, "parse error");
+    assert_eq!(page.before_id, Some(200));
+    assert_eq!(page.first_failed_epoch_second, 0);
+    let page = pages.iter().find(|p| p.contest_id == "abc003").unwrap();
+    assert_eq!((page.attempts, page.before_id), (3, Some(300)));
+}
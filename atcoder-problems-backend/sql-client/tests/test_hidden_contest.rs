@@ -0,0 +1,43 @@
+use sql_client::hidden_contest::HiddenContestClient;
+use sql_client::models::HiddenContest;
+
+mod utils;
+
+fn hidden_contest(id: &str, title: &str) -> HiddenContest {
+    HiddenContest {
+        id: id.to_string(),
+        start_epoch_second: 1600000000,
+        duration_second: 7200,
+        title: title.to_string(),
+        rate_change: "-".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_hidden_contests() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert!(pool.load_hidden_contests().await.unwrap().is_empty());
+
+    pool.update_hidden_contests(&[
+        hidden_contest("contest2", "Contest 2"),
+        hidden_contest("contest1", "Contest 1"),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(
+        pool.load_hidden_contests().await.unwrap(),
+        vec![
+            hidden_contest("contest1", "Contest 1"),
+            hidden_contest("contest2", "Contest 2")
+        ]
+    );
+
+    pool.update_hidden_contests(&[hidden_contest("contest1", "Renamed")])
+        .await
+        .unwrap();
+    pool.delete_hidden_contest("contest2").await.unwrap();
+    assert_eq!(
+        pool.load_hidden_contests().await.unwrap(),
+        vec![hidden_contest("contest1", "Renamed")]
+    );
+}
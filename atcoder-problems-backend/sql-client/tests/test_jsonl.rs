@@ -0,0 +1,219 @@
+use sql_client::contest_problem::ContestProblemClient;
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::jsonl::{JsonlClient, SUBMISSIONS_FILE};
+use sql_client::models::{Contest, ContestProblem, FailedPage, Problem, Submission};
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use std::env;
+
+fn create_client() -> JsonlClient {
+    let dir = env::temp_dir().join(format!("jsonl-{}", uuid::Uuid::new_v4()));
+    JsonlClient::new(dir).unwrap()
+}
+
+fn submission(id: i64, contest_id: &str, result: &str) -> Submission {
+    Submission {
+        id,
+        contest_id: contest_id.to_string(),
+        user_id: "user".to_string(),
+        result: result.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_jsonl_submissions() {
+    let client = create_client();
+    client
+        .update_submissions(&[
+            submission(1, "contest1", "WJ"),
+            submission(2, "contest2", "AC"),
+        ])
+        .await
+        .unwrap();
+    client
+        .update_submissions(&[submission(1, "contest1", "WA")])
+        .await
+        .unwrap();
+
+    // Every line is kept, but the last one of each submission wins.
+    assert_eq!(
+        client.read::<Submission>(SUBMISSIONS_FILE).unwrap().len(),
+        3
+    );
+    let submissions = client
+        .get_submissions(SubmissionRequest::ContestAll {
+            contest_id: "contest1",
+        })
+        .await
+        .unwrap();
+    assert_eq!(submissions, vec![submission(1, "contest1", "WA")]);
+    assert_eq!(
+        client.count_stored_submissions(&[1, 2, 3]).await.unwrap(),
+        2
+    );
+    assert!(client
+        .get_submissions(SubmissionRequest::AllAccepted)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_jsonl_contests() {
+    let client = create_client();
+    assert!(client.load_contests().await.unwrap().is_empty());
+    let contest = Contest {
+        id: "contest1".to_string(),
+        title: "Contest 1".to_string(),
+        ..Default::default()
+    };
+    client
+        .insert_contests(std::slice::from_ref(&contest))
+        .await
+        .unwrap();
+    assert_eq!(client.load_contests().await.unwrap(), vec![contest]);
+}
+
+#[tokio::test]
+async fn test_jsonl_states() {
+    let client = create_client();
+    client
+        .save_crawl_state("crawler", "contest1", 1, 3, None)
+        .await
+        .unwrap();
+    client
+        .save_crawl_state("crawler", "contest1", 2, 3, Some(100))
+        .await
+        .unwrap();
+    let state = client
+        .load_crawl_state("crawler", "contest1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (state.last_page, state.max_page, state.before_id),
+        (2, 3, Some(100))
+    );
+    client
+        .delete_crawl_state("crawler", "contest1")
+        .await
+        .unwrap();
+    assert!(client
+        .load_crawl_states("crawler")
+        .await
+        .unwrap()
+        .is_empty());
+
+    client
+        .record_failed_page("crawler", "contest1", 2, Some(100), "timeout")
+        .await
+        .unwrap();
+    client
+        .record_failed_page("failed_page", "contest1", 2, None, "HTTP 503")
+        .await
+        .unwrap();
+    let pages = client.load_failed_pages().await.unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(
+        (pages[0].attempts, pages[0].error.as_str()),
+        (2, "HTTP 503")
+    );
+    assert_eq!(
+        (pages[0].crawler.as_str(), pages[0].before_id),
+        ("crawler", Some(100))
+    );
+    client
+        .merge_failed_pages(&[FailedPage {
+            contest_id: "contest1".to_string(),
+            page: 2,
+            crawler: "recent".to_string(),
+            attempts: 5,
+            before_id: Some(200),
+            ..Default::default()
+        }])
+        .await
+        .unwrap();
+    let pages = client.load_failed_pages().await.unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(
+        (
+            pages[0].attempts,
+            pages[0].error.as_str(),
+            pages[0].before_id
+        ),
+        (5, "HTTP 503", Some(100))
+    );
+    client.delete_failed_page("contest1", 2).await.unwrap();
+    assert!(client.load_failed_pages().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_jsonl_sessions_are_not_written() {
+    let client = create_client();
+    client
+        .save_atcoder_session("user", "REVEL_SESSION=secret")
+        .await
+        .unwrap();
+    assert_eq!(client.load_atcoder_session("user").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_jsonl_orphans() {
+    let client = create_client();
+    client
+        .update_submissions(&[
+            Submission {
+                problem_id: "problem1".to_string(),
+                ..submission(1, "contest1", "AC")
+            },
+            Submission {
+                problem_id: "problem2".to_string(),
+                ..submission(2, "contest1", "AC")
+            },
+        ])
+        .await
+        .unwrap();
+    client
+        .insert_contests(&[Contest {
+            id: "contest1".to_string(),
+            ..Default::default()
+        }])
+        .await
+        .unwrap();
+    client
+        .insert_problems(&[Problem {
+            id: "problem1".to_string(),
+            contest_id: "contest1".to_string(),
+            problem_index: "A".to_string(),
+            name: "".to_string(),
+            title: "".to_string(),
+            time_limit_ms: None,
+            memory_limit_kb: None,
+        }])
+        .await
+        .unwrap();
+    client
+        .insert_contest_problem(&[ContestProblem {
+            contest_id: "contest1".to_string(),
+            problem_id: "problem1".to_string(),
+            problem_index: "A".to_string(),
+        }])
+        .await
+        .unwrap();
+
+    let orphans = client.load_orphans().await.unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].problem_id, "problem2");
+    assert!(orphans[0].problem_missing);
+    assert!(!orphans[0].contest_missing);
+    assert!(orphans[0].contest_problem_missing);
+
+    let batch = [Submission {
+        problem_id: "problem1".to_string(),
+        ..submission(3, "contest1", "AC")
+    }];
+    assert_eq!(client.record_submission_orphans(&batch).await.unwrap(), 0);
+}
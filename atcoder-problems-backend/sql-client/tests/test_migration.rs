@@ -0,0 +1,114 @@
+use sql_client::migration::{MigrationClient, MigrationState, MIGRATIONS};
+use sql_client::PgPool;
+use sqlx::Executor;
+
+mod utils;
+
+/// Describes the columns, indexes and constraints regardless of the order of their creation.
+async fn describe_schema(pool: &PgPool) -> Vec<String> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        r"
+        SELECT CONCAT_WS(' ', table_name, column_name, data_type, character_maximum_length, is_nullable, column_default)
+        FROM information_schema.columns WHERE table_schema = 'public'
+        UNION ALL
+        SELECT CONCAT_WS(' ', tablename, REGEXP_REPLACE(indexdef, 'INDEX \S+ ON', 'INDEX ON'))
+        FROM pg_indexes WHERE schemaname = 'public'
+        UNION ALL
+        SELECT CONCAT_WS(' ', conrelid::regclass, contype, pg_get_constraintdef(oid))
+        FROM pg_constraint WHERE connamespace = 'public'::regnamespace
+        ORDER BY 1
+        ",
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap();
+    rows.into_iter().map(|(row,)| row).collect()
+}
+
+#[tokio::test]
+async fn test_migration() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let applied = pool.load_applied_migrations().await.unwrap();
+    assert_eq!(applied.len(), MIGRATIONS.len());
+    assert_eq!(applied[0].checksum, MIGRATIONS[0].checksum());
+    assert!(pool.ensure_schema_up_to_date().await.is_ok());
+    assert!(pool.migrate_up().await.unwrap().is_empty());
+    assert!(pool.mark_baseline_applied().await.is_err());
+
+    sqlx::query("UPDATE schema_migrations SET checksum = 'edited' WHERE version = 1")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let statuses = pool.migration_status().await.unwrap();
+    assert_eq!(statuses[0].state, MigrationState::Modified);
+    assert!(pool.ensure_schema_up_to_date().await.is_err());
+    assert!(pool.migrate_up().await.is_err());
+}
+
+#[tokio::test]
+async fn test_migration_baseline() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    pool.execute("DROP TABLE schema_migrations").await.unwrap();
+    assert!(pool.load_applied_migrations().await.unwrap().is_empty());
+    let statuses = pool.migration_status().await.unwrap();
+    assert!(statuses
+        .iter()
+        .all(|status| status.state == MigrationState::Pending));
+    assert!(pool.ensure_schema_up_to_date().await.is_err());
+
+    pool.mark_baseline_applied().await.unwrap();
+    let applied = pool.load_applied_migrations().await.unwrap();
+    assert_eq!(applied.len(), 1);
+    assert_eq!(applied[0].version, 1);
+}
+
+#[tokio::test]
+async fn test_migration_from_baseline() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let fresh = describe_schema(&pool).await;
+
+    pool.execute("DROP SCHEMA public CASCADE; CREATE SCHEMA public;")
+        .await
+        .unwrap();
+    pool.execute(MIGRATIONS[0].sql).await.unwrap();
+    pool.execute(
+        r"
+        INSERT INTO submissions
+            (id, epoch_second, problem_id, contest_id, user_id, language, point, length, result, execution_time)
+        VALUES (1, 0, 'abc001_a', 'abc001', 'user', 'Rust', 100, 10, 'AC', 5);
+        INSERT INTO contests (id, start_epoch_second, duration_second, title, rate_change)
+        VALUES ('abc001', 0, 6000, 'ABC 001', ' ~ 1999');
+        INSERT INTO problems (id, contest_id, problem_index, name, title)
+        VALUES ('abc001_a', 'abc001', 'A', 'Problem', 'A. Problem');
+        INSERT INTO internal_users (internal_user_id, atcoder_user_id) VALUES ('internal', 'user');
+        ",
+    )
+    .await
+    .unwrap();
+
+    // Running the baseline again would drop the existing tables.
+    assert!(pool.migrate_up().await.is_err());
+    pool.mark_baseline_applied().await.unwrap();
+    let versions = pool.migrate_up().await.unwrap();
+    let expected = MIGRATIONS[1..]
+        .iter()
+        .map(|m| m.version)
+        .collect::<Vec<_>>();
+    assert_eq!(versions, expected);
+    assert!(pool.ensure_schema_up_to_date().await.is_ok());
+    assert_eq!(describe_schema(&pool).await, fresh);
+
+    let (category, rated_min, rated_max, memory): (String, Option<i32>, Option<i32>, Option<i32>) =
+        sqlx::query_as(
+            r"
+            SELECT contests.category, contests.rated_min, contests.rated_max, submissions.memory
+            FROM submissions JOIN contests ON contests.id = submissions.contest_id
+            ",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(category, "Other");
+    assert_eq!((rated_min, rated_max), (Some(0), Some(1999)));
+    assert_eq!(memory, None);
+}
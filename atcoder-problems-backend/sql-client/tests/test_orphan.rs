@@ -0,0 +1,160 @@
+use sql_client::contest_problem::ContestProblemClient;
+use sql_client::models::{Contest, ContestProblem, Problem, Submission};
+use sql_client::orphan::OrphanClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::SubmissionClient;
+
+mod utils;
+
+fn submission(id: i64, contest_id: &str, problem_id: &str) -> Submission {
+    Submission {
+        id,
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_orphan() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    pool.insert_contests(&[Contest {
+        id: "contest1".to_string(),
+        ..Default::default()
+    }])
+    .await
+    .unwrap();
+    pool.insert_problems(&[Problem {
+        id: "problem1".to_string(),
+        contest_id: "contest1".to_string(),
+        problem_index: "A".to_string(),
+        name: "".to_string(),
+        title: "".to_string(),
+        time_limit_ms: None,
+        memory_limit_kb: None,
+    }])
+    .await
+    .unwrap();
+    pool.insert_contest_problem(&[ContestProblem {
+        contest_id: "contest1".to_string(),
+        problem_id: "problem1".to_string(),
+        problem_index: "A".to_string(),
+    }])
+    .await
+    .unwrap();
+
+    pool.update_submissions(&[
+        submission(1, "contest1", "problem1"),
+        submission(2, "contest1", "problem2"),
+        submission(3, "contest1", "problem2"),
+        submission(4, "contest2", "problem1"),
+    ])
+    .await
+    .unwrap();
+    assert!(pool.load_orphans().await.unwrap().is_empty());
+
+    assert_eq!(pool.record_orphans(1).await.unwrap(), 0);
+    assert_eq!(pool.record_orphans(0).await.unwrap(), 2);
+    let orphans = pool.load_orphans().await.unwrap();
+    assert_eq!(orphans.len(), 2);
+    assert_eq!(
+        (
+            orphans[0].contest_id.as_str(),
+            orphans[0].problem_id.as_str()
+        ),
+        ("contest1", "problem2")
+    );
+    assert!(orphans[0].problem_missing);
+    assert!(!orphans[0].contest_missing);
+    assert!(orphans[0].contest_problem_missing);
+    assert_eq!(
+        (
+            orphans[1].contest_id.as_str(),
+            orphans[1].problem_id.as_str()
+        ),
+        ("contest2", "problem1")
+    );
+    assert!(!orphans[1].problem_missing);
+    assert!(orphans[1].contest_missing);
+    assert!(orphans[1].contest_problem_missing);
+
+    assert_eq!(pool.resolve_orphans().await.unwrap(), 0);
+    pool.insert_problems(&[Problem {
+        id: "problem2".to_string(),
+        contest_id: "contest1".to_string(),
+        problem_index: "B".to_string(),
+        name: "".to_string(),
+        title: "".to_string(),
+        time_limit_ms: None,
+        memory_limit_kb: None,
+    }])
+    .await
+    .unwrap();
+    pool.insert_contest_problem(&[ContestProblem {
+        contest_id: "contest1".to_string(),
+        problem_id: "problem2".to_string(),
+        problem_index: "B".to_string(),
+    }])
+    .await
+    .unwrap();
+    assert_eq!(pool.resolve_orphans().await.unwrap(), 1);
+
+    let orphans = pool.load_orphans().await.unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].contest_id, "contest2");
+    assert_eq!(orphans[0].attempts, 2);
+}
+
+#[tokio::test]
+async fn test_record_submission_orphans() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    pool.insert_contests(&[Contest {
+        id: "contest1".to_string(),
+        ..Default::default()
+    }])
+    .await
+    .unwrap();
+    pool.insert_problems(&[Problem {
+        id: "problem1".to_string(),
+        contest_id: "contest1".to_string(),
+        problem_index: "A".to_string(),
+        name: "".to_string(),
+        title: "".to_string(),
+        time_limit_ms: None,
+        memory_limit_kb: None,
+    }])
+    .await
+    .unwrap();
+    pool.insert_contest_problem(&[ContestProblem {
+        contest_id: "contest1".to_string(),
+        problem_id: "problem1".to_string(),
+        problem_index: "A".to_string(),
+    }])
+    .await
+    .unwrap();
+    pool.update_submissions(&[submission(1, "contest2", "problem1")])
+        .await
+        .unwrap();
+
+    // Only the submissions of the batch are looked into.
+    let batch = [
+        submission(2, "contest1", "problem1"),
+        submission(3, "contest1", "problem2"),
+        submission(4, "contest1", "problem2"),
+    ];
+    pool.update_submissions(&batch).await.unwrap();
+    assert_eq!(pool.record_submission_orphans(&batch).await.unwrap(), 1);
+    assert_eq!(pool.record_submission_orphans(&[]).await.unwrap(), 0);
+
+    let orphans = pool.load_orphans().await.unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(
+        (
+            orphans[0].contest_id.as_str(),
+            orphans[0].problem_id.as_str()
+        ),
+        ("contest1", "problem2")
+    );
+    assert!(orphans[0].problem_missing);
+    assert!(!orphans[0].contest_missing);
+}
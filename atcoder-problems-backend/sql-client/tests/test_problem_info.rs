@@ -77,6 +77,16 @@ async fn test_update_problem_solver_count() {
     .unwrap();
     pool.update_solver_count().await.unwrap();
     assert_eq!(get_solver(&pool).await, vec![("problem".to_string(), 3)]);
+
+    // The problem nobody has solved any more is counted as zero.
+    sqlx::query("UPDATE submissions SET result = 'WA'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.update_solver_count_of_problems(&["problem"])
+        .await
+        .unwrap();
+    assert_eq!(get_solver(&pool).await, vec![("problem".to_string(), 0)]);
 }
 
 #[tokio::test]
@@ -89,9 +99,11 @@ async fn test_update_problem_points() {
         id: contest_id.to_string(),
         start_epoch_second: 1468670400,
         rate_change: "All".to_string(),
+        rated_min: Some(0),
 
         duration_second: 0,
         title: "".to_string(),
+        ..Default::default()
     }])
     .await
     .unwrap();
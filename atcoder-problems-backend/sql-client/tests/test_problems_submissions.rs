@@ -10,6 +10,7 @@ enum Table {
     First,
     Shortest,
     Fastest,
+    LeastMemory,
 }
 
 async fn get_from(pool: &PgPool, table: Table) -> Vec<(String, String, i64)> {
@@ -17,6 +18,7 @@ async fn get_from(pool: &PgPool, table: Table) -> Vec<(String, String, i64)> {
         Table::First => "first",
         Table::Shortest => "shortest",
         Table::Fastest => "fastest",
+        Table::LeastMemory => "least_memory",
     };
     let query = format!(
         "SELECT contest_id, problem_id, submission_id FROM {}",
@@ -60,6 +62,7 @@ async fn test_problem_info_aggrefator() {
         epoch_second: 0,
         length: 1,
         execution_time: Some(1),
+        memory: Some(1),
         result: "AC".to_owned(),
         ..Default::default()
     }];
@@ -70,6 +73,7 @@ async fn test_problem_info_aggrefator() {
         epoch_second: 10,
         length: 20,
         execution_time: Some(10),
+        memory: Some(2000),
         result: "AC".to_owned(),
         ..Default::default()
     }];
@@ -80,6 +84,7 @@ async fn test_problem_info_aggrefator() {
         epoch_second: 10,
         length: 10,
         execution_time: Some(10),
+        memory: Some(1000),
         result: "AC".to_owned(),
         ..Default::default()
     }];
@@ -168,4 +173,57 @@ async fn test_problem_info_aggrefator() {
         assert_eq!(fastest[0].1, submissions1[0].problem_id);
         assert_eq!(fastest[0].2, submissions1[0].id);
     }
+
+    {
+        let pool = setup_contests().await;
+
+        pool.update_submissions(&submissions1).await.unwrap();
+        pool.update_submissions_of_problems().await.unwrap();
+        let least_memory = get_from(&pool, Table::LeastMemory).await;
+        assert_eq!(least_memory.len(), 1);
+        assert_eq!(least_memory[0].0, submissions1[0].contest_id);
+        assert_eq!(least_memory[0].1, submissions1[0].problem_id);
+        assert_eq!(least_memory[0].2, submissions1[0].id);
+
+        pool.update_submissions(&submissions2).await.unwrap();
+        pool.update_submissions_of_problems().await.unwrap();
+        let least_memory = get_from(&pool, Table::LeastMemory).await;
+        assert_eq!(least_memory.len(), 1);
+        assert_eq!(least_memory[0].0, submissions2[0].contest_id);
+        assert_eq!(least_memory[0].1, submissions2[0].problem_id);
+        assert_eq!(least_memory[0].2, submissions2[0].id);
+    }
+}
+
+#[tokio::test]
+async fn test_delete_submissions_of_problems() {
+    let pool = setup_contests().await;
+    let submission = |id: i64, problem_id: &str| Submission {
+        id,
+        problem_id: problem_id.to_owned(),
+        contest_id: "contest1".to_owned(),
+        epoch_second: 10,
+        execution_time: Some(10),
+        memory: Some(1000),
+        result: "AC".to_owned(),
+        ..Default::default()
+    };
+    pool.update_submissions(&[submission(1, "problem1"), submission(2, "problem2")])
+        .await
+        .unwrap();
+    pool.update_submissions_of_problems().await.unwrap();
+
+    pool.delete_submissions_of_problems(&[1]).await.unwrap();
+    for table in [
+        Table::First,
+        Table::Shortest,
+        Table::Fastest,
+        Table::LeastMemory,
+    ] {
+        let records = get_from(&pool, table).await;
+        assert_eq!(
+            records,
+            vec![("contest1".to_owned(), "problem2".to_owned(), 2)]
+        );
+    }
 }
@@ -26,6 +26,8 @@ async fn setup_contests(pool: &PgPool) {
             duration_second: 1000,
             title: "Rated Contest".to_string(),
             rate_change: "All".to_string(),
+            rated_min: Some(0),
+            ..Default::default()
         },
         Contest {
             id: UNRATED_CONTEST1.to_string(),
@@ -33,6 +35,8 @@ async fn setup_contests(pool: &PgPool) {
             duration_second: 1000,
             title: "Unrated Old Contest".to_string(),
             rate_change: "All".to_string(),
+            rated_min: Some(0),
+            ..Default::default()
         },
         Contest {
             id: UNRATED_CONTEST2.to_string(),
@@ -40,6 +44,7 @@ async fn setup_contests(pool: &PgPool) {
             duration_second: 1000,
             title: "Unrated New Contest".to_string(),
             rate_change: UNRATED_STATE.to_string(),
+            ..Default::default()
         },
         Contest {
             id: SAME_CONTEST_RATED.to_string(),
@@ -47,6 +52,8 @@ async fn setup_contests(pool: &PgPool) {
             duration_second: 1000,
             title: "Unrated New Contest".to_string(),
             rate_change: "All".to_string(),
+            rated_min: Some(0),
+            ..Default::default()
         },
         Contest {
             id: SAME_CONTEST_UNRATED.to_string(),
@@ -54,6 +61,7 @@ async fn setup_contests(pool: &PgPool) {
             duration_second: 1000,
             title: "Unrated New Contest".to_string(),
             rate_change: UNRATED_STATE.to_string(),
+            ..Default::default()
         },
         Contest {
             id: HEURISTIC_CONTEST.to_string(),
@@ -61,14 +69,16 @@ async fn setup_contests(pool: &PgPool) {
             duration_second: 1000,
             title: "Heuristic Contest".to_string(),
             rate_change: "All".to_string(),
+            rated_min: Some(0),
+            ..Default::default()
         },
     ];
     for contest in contests {
         sqlx::query(
             r"
             INSERT INTO contests
-            (id, start_epoch_second, duration_second, title, rate_change)
-            VALUES ($1, $2, $3, $4, $5)
+            (id, start_epoch_second, duration_second, title, rate_change, rated_min)
+            VALUES ($1, $2, $3, $4, $5, $6)
             ",
         )
         .bind(contest.id)
@@ -76,6 +86,7 @@ async fn setup_contests(pool: &PgPool) {
         .bind(contest.duration_second)
         .bind(contest.title)
         .bind(contest.rate_change)
+        .bind(contest.rated_min)
         .execute(pool)
         .await
         .unwrap();
@@ -0,0 +1,62 @@
+use sql_client::models::UserRatingHistory;
+use sql_client::rating_history::RatingHistoryClient;
+
+mod utils;
+
+fn history(
+    contest_id: &str,
+    is_rated: bool,
+    new_rating: i32,
+    end_epoch_second: i64,
+) -> UserRatingHistory {
+    UserRatingHistory {
+        user_id: "user1".to_string(),
+        contest_id: contest_id.to_string(),
+        is_rated,
+        rank: 100,
+        performance: 1200,
+        old_rating: 0,
+        new_rating,
+        end_epoch_second,
+    }
+}
+
+#[tokio::test]
+async fn test_user_rating_history() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert_eq!(pool.get_users_rating("user1").await.unwrap(), None);
+
+    pool.update_user_rating_history(
+        "user1",
+        &[
+            history("abc002", true, 800, 200),
+            history("abc001", true, 400, 100),
+        ],
+    )
+    .await
+    .unwrap();
+    let loaded = pool.load_user_rating_history("USER1").await.unwrap();
+    assert_eq!(
+        loaded,
+        vec![
+            history("abc001", true, 400, 100),
+            history("abc002", true, 800, 200)
+        ]
+    );
+    assert_eq!(pool.get_users_rating("user1").await.unwrap(), Some(800));
+
+    pool.update_user_rating_history(
+        "user1",
+        &[
+            history("abc002", true, 900, 200),
+            history("agc001", false, 900, 300),
+        ],
+    )
+    .await
+    .unwrap();
+    let loaded = pool.load_user_rating_history("user1").await.unwrap();
+    assert_eq!(loaded.len(), 3);
+    assert_eq!(loaded[1].new_rating, 900);
+    assert_eq!(pool.get_users_rating("user1").await.unwrap(), Some(900));
+    assert_eq!(pool.get_users_rating("user2").await.unwrap(), None);
+}
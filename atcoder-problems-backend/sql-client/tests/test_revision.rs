@@ -0,0 +1,83 @@
+use sql_client::models::{ContestRevision, Problem, ProblemRevision};
+use sql_client::revision::RevisionClient;
+use sql_client::simple_client::SimpleClient;
+
+mod utils;
+
+fn problem(title: &str, time_limit_ms: Option<i32>) -> Problem {
+    Problem {
+        id: "problem1".to_string(),
+        contest_id: "contest1".to_string(),
+        problem_index: "A".to_string(),
+        name: "".to_string(),
+        title: title.to_string(),
+        time_limit_ms,
+        memory_limit_kb: Some(1048576),
+    }
+}
+
+#[tokio::test]
+async fn test_contest_revisions() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert!(pool
+        .load_contest_revisions("contest1")
+        .await
+        .unwrap()
+        .is_empty());
+
+    let revision = |field: &str, old_value: &str, new_value: &str| ContestRevision {
+        contest_id: "contest1".to_string(),
+        field: field.to_string(),
+        old_value: Some(old_value.to_string()),
+        new_value: Some(new_value.to_string()),
+        detected_epoch_second: 100,
+        ..Default::default()
+    };
+    pool.insert_contest_revisions(&[
+        revision("rate_change", " ~ 1999", "-"),
+        revision("duration_second", "6000", "7200"),
+    ])
+    .await
+    .unwrap();
+
+    let revisions = pool.load_contest_revisions("contest1").await.unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].field, "rate_change");
+    assert_eq!(revisions[0].old_value.as_deref(), Some(" ~ 1999"));
+    assert_eq!(revisions[1].field, "duration_second");
+    assert!(pool
+        .load_contest_revisions("contest2")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_revise_problems() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    pool.insert_problems(&[problem("A. Title", Some(2000))])
+        .await
+        .unwrap();
+
+    pool.revise_problems(
+        &[problem("A. Retitled", None)],
+        &[ProblemRevision {
+            problem_id: "problem1".to_string(),
+            field: "title".to_string(),
+            old_value: Some("A. Title".to_string()),
+            new_value: Some("A. Retitled".to_string()),
+            detected_epoch_second: 100,
+            ..Default::default()
+        }],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        pool.load_problems().await.unwrap(),
+        vec![problem("A. Retitled", Some(2000))]
+    );
+    let revisions = pool.load_problem_revisions("problem1").await.unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].new_value.as_deref(), Some("A. Retitled"));
+}
@@ -0,0 +1,22 @@
+use sql_client::session::SessionClient;
+
+mod utils;
+
+#[tokio::test]
+async fn test_atcoder_session() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert_eq!(pool.load_atcoder_session("user").await.unwrap(), None);
+
+    pool.save_atcoder_session("user", "session1").await.unwrap();
+    assert_eq!(
+        pool.load_atcoder_session("user").await.unwrap(),
+        Some("session1".to_string())
+    );
+
+    pool.save_atcoder_session("user", "session2").await.unwrap();
+    assert_eq!(
+        pool.load_atcoder_session("user").await.unwrap(),
+        Some("session2".to_string())
+    );
+    assert_eq!(pool.load_atcoder_session("other").await.unwrap(), None);
+}
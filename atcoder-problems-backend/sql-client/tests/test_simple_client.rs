@@ -13,6 +13,7 @@ async fn test_insert_contests() {
         duration_second: 0,
         title: "".to_string(),
         rate_change: "".to_string(),
+        ..Default::default()
     }])
     .await
     .unwrap();
@@ -20,15 +21,20 @@ async fn test_insert_contests() {
     let contests = pool.load_contests().await.unwrap();
     assert_eq!(contests[0].id, "contest1");
 
-    pool.insert_contests(&[Contest {
+    let rescheduled = Contest {
         id: "contest1".to_string(),
-        start_epoch_second: 0,
-        duration_second: 0,
-        title: "".to_string(),
-        rate_change: "".to_string(),
-    }])
-    .await
-    .unwrap();
+        start_epoch_second: 100,
+        duration_second: 6000,
+        title: "AtCoder Beginner Contest 1".to_string(),
+        rate_change: " ~ 1999".to_string(),
+        category: "ABC".to_string(),
+        rated_min: Some(0),
+        rated_max: Some(1999),
+    };
+    pool.insert_contests(std::slice::from_ref(&rescheduled))
+        .await
+        .unwrap();
+    assert_eq!(pool.load_contests().await.unwrap(), vec![rescheduled]);
 }
 
 #[tokio::test]
@@ -41,12 +47,16 @@ async fn test_insert_problems() {
         problem_index: "".to_string(),
         name: "".to_string(),
         title: "".to_string(),
+        time_limit_ms: Some(2000),
+        memory_limit_kb: Some(1048576),
     }])
     .await
     .unwrap();
 
     let problems = pool.load_problems().await.unwrap();
     assert_eq!(problems[0].id, "problem1");
+    assert_eq!(problems[0].time_limit_ms, Some(2000));
+    assert_eq!(problems[0].memory_limit_kb, Some(1048576));
 
     pool.insert_problems(&[Problem {
         id: "problem1".to_string(),
@@ -54,7 +64,27 @@ async fn test_insert_problems() {
         problem_index: "".to_string(),
         name: "".to_string(),
         title: "".to_string(),
+        time_limit_ms: Some(2000),
+        memory_limit_kb: Some(1048576),
     }])
     .await
     .unwrap();
+
+    // The limits of a problem stored without them are filled, but never cleared.
+    let problem = |time_limit_ms: Option<i32>| Problem {
+        id: "problem2".to_string(),
+        contest_id: "contest2".to_string(),
+        problem_index: "A".to_string(),
+        name: "Problem".to_string(),
+        title: "A. Problem".to_string(),
+        time_limit_ms,
+        memory_limit_kb: time_limit_ms.map(|_| 1024),
+    };
+    pool.insert_problems(&[problem(None)]).await.unwrap();
+    pool.insert_problems(&[problem(Some(3000))]).await.unwrap();
+    pool.insert_problems(&[problem(None)]).await.unwrap();
+    let problems = pool.load_problems().await.unwrap();
+    let stored = problems.iter().find(|p| p.id == "problem2").unwrap();
+    assert_eq!(stored.time_limit_ms, Some(3000));
+    assert_eq!(stored.memory_limit_kb, Some(1024));
 }
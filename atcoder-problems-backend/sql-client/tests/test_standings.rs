@@ -0,0 +1,103 @@
+use sql_client::models::{ContestStanding, ContestStandingTaskResult};
+use sql_client::standings::StandingsClient;
+
+mod utils;
+
+fn standing(contest_id: &str, user_id: &str, rank: i32) -> ContestStanding {
+    ContestStanding {
+        contest_id: contest_id.to_string(),
+        user_id: user_id.to_string(),
+        rank,
+        is_rated: true,
+        old_rating: 1200,
+        new_rating: 1300,
+        score: 600.0,
+        penalty: 1,
+        elapsed_second: 1800,
+    }
+}
+
+fn task_result(contest_id: &str, user_id: &str, problem_id: &str) -> ContestStandingTaskResult {
+    ContestStandingTaskResult {
+        contest_id: contest_id.to_string(),
+        user_id: user_id.to_string(),
+        problem_id: problem_id.to_string(),
+        score: 300.0,
+        penalty: 0,
+        elapsed_second: 600,
+    }
+}
+
+#[tokio::test]
+async fn test_contest_standings() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+
+    pool.update_contest_standings(
+        "abc001",
+        &[
+            standing("abc001", "user1", 2),
+            standing("abc001", "user2", 1),
+        ],
+        &[
+            task_result("abc001", "user1", "abc001_a"),
+            task_result("abc001", "user1", "abc001_b"),
+            task_result("abc001", "user2", "abc001_a"),
+        ],
+    )
+    .await
+    .unwrap();
+    pool.update_contest_standings(
+        "abc002",
+        &[standing("abc002", "user1", 1)],
+        &[task_result("abc002", "user1", "abc002_a")],
+    )
+    .await
+    .unwrap();
+
+    let standings = pool.load_contest_standings("abc001").await.unwrap();
+    assert_eq!(
+        standings,
+        vec![
+            standing("abc001", "user2", 1),
+            standing("abc001", "user1", 2)
+        ]
+    );
+    let task_results = pool
+        .load_contest_standings_task_results("abc001")
+        .await
+        .unwrap();
+    assert_eq!(task_results.len(), 3);
+    assert_eq!(task_results[0], task_result("abc001", "user1", "abc001_a"));
+
+    let standings = pool.load_user_standings("USER1").await.unwrap();
+    assert_eq!(standings.len(), 2);
+    assert_eq!(standings[0].contest_id, "abc001");
+    assert_eq!(standings[1].contest_id, "abc002");
+
+    let mut contest_ids = pool.load_standings_contest_ids().await.unwrap();
+    contest_ids.sort();
+    assert_eq!(contest_ids, vec!["abc001", "abc002"]);
+
+    pool.update_contest_standings(
+        "abc001",
+        &[standing("abc001", "user3", 1)],
+        &[task_result("abc001", "user3", "abc001_a")],
+    )
+    .await
+    .unwrap();
+    let standings = pool.load_contest_standings("abc001").await.unwrap();
+    assert_eq!(standings, vec![standing("abc001", "user3", 1)]);
+    let task_results = pool
+        .load_contest_standings_task_results("abc001")
+        .await
+        .unwrap();
+    assert_eq!(
+        task_results,
+        vec![task_result("abc001", "user3", "abc001_a")]
+    );
+    let task_results = pool
+        .load_contest_standings_task_results("abc002")
+        .await
+        .unwrap();
+    assert_eq!(task_results.len(), 1);
+}
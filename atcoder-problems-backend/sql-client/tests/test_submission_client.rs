@@ -1,4 +1,4 @@
-use sql_client::models::Submission;
+use sql_client::models::{Submission, SubmissionDetail, SubmissionTestcase};
 use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
 
 mod utils;
@@ -35,6 +35,18 @@ async fn test_submission_client() {
     let submissions = pool.get_submissions(request).await.unwrap();
     assert!(submissions.is_empty());
 
+    let request = SubmissionRequest::ContestAll {
+        contest_id: "contest1",
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert_eq!(submissions.len(), 6);
+
+    let request = SubmissionRequest::ContestAll {
+        contest_id: "contest2",
+    };
+    let submissions = pool.get_submissions(request).await.unwrap();
+    assert!(submissions.is_empty());
+
     let request = SubmissionRequest::RecentAccepted { count: 0 };
     let submissions = pool.get_submissions(request).await.unwrap();
     assert!(submissions.is_empty());
@@ -119,11 +131,21 @@ async fn test_submission_client() {
     assert_eq!(pool.count_stored_submissions(&[1]).await.unwrap(), 1);
     assert_eq!(pool.count_stored_submissions(&[9]).await.unwrap(), 0);
 
-    let request = SubmissionRequest::InvalidResult { from_second: 1 };
+    let final_results = [
+        "AC", "WA", "TLE", "CE", "RE", "MLE", "OLE", "QLE", "IE", "NG",
+    ]
+    .map(String::from);
+    let request = SubmissionRequest::InvalidResult {
+        from_second: 1,
+        final_results: &final_results,
+    };
     let submissions = pool.get_submissions(request).await.unwrap();
     assert_eq!(submissions.len(), 2);
 
-    let request = SubmissionRequest::InvalidResult { from_second: 2 };
+    let request = SubmissionRequest::InvalidResult {
+        from_second: 2,
+        final_results: &final_results,
+    };
     let submissions = pool.get_submissions(request).await.unwrap();
     assert_eq!(submissions.len(), 1);
 }
@@ -137,6 +159,7 @@ async fn test_update_submissions() {
         result: "WJ".to_owned(),
         point: 0.0,
         execution_time: None,
+        memory: None,
         ..Default::default()
     }])
     .await
@@ -153,6 +176,7 @@ async fn test_update_submissions() {
     assert_eq!(submissions[0].result, "WJ");
     assert_eq!(submissions[0].point, 0.0);
     assert_eq!(submissions[0].execution_time, None);
+    assert_eq!(submissions[0].memory, None);
 
     let submissions = pool
         .get_submissions(SubmissionRequest::UserAll {
@@ -168,6 +192,7 @@ async fn test_update_submissions() {
         result: "AC".to_owned(),
         point: 100.0,
         execution_time: Some(1),
+        memory: Some(3588),
         ..Default::default()
     }])
     .await
@@ -192,6 +217,7 @@ async fn test_update_submissions() {
     assert_eq!(submissions[0].result, "AC");
     assert_eq!(submissions[0].point, 100.0);
     assert_eq!(submissions[0].execution_time, Some(1));
+    assert_eq!(submissions[0].memory, Some(3588));
 }
 
 #[sqlx::test]
@@ -253,3 +279,69 @@ async fn case_insensitive() {
 
     assert_eq!(submissions, &[submission.clone()]);
 }
+
+#[tokio::test]
+async fn test_submission_detail() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    assert_eq!(pool.get_submission_detail(1).await.unwrap(), None);
+
+    let detail = SubmissionDetail {
+        submission_id: 1,
+        compile_error: None,
+    };
+    let testcases = vec![
+        SubmissionTestcase {
+            submission_id: 1,
+            case_name: "sample_01.txt".to_owned(),
+            result: "AC".to_owned(),
+            execution_time: Some(7),
+            memory: Some(3588),
+        },
+        SubmissionTestcase {
+            submission_id: 1,
+            case_name: "subtask_1_02.txt".to_owned(),
+            result: "WJ".to_owned(),
+            execution_time: None,
+            memory: None,
+        },
+    ];
+    pool.update_submission_detail(&detail, &testcases)
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.get_submission_detail(1).await.unwrap(),
+        Some(detail.clone())
+    );
+    assert_eq!(
+        pool.get_submission_testcases(&[1]).await.unwrap(),
+        testcases
+    );
+
+    let rejudged = vec![SubmissionTestcase {
+        submission_id: 1,
+        case_name: "subtask_1_02.txt".to_owned(),
+        result: "WA".to_owned(),
+        execution_time: Some(343),
+        memory: Some(4800),
+    }];
+    pool.update_submission_detail(&detail, &rejudged)
+        .await
+        .unwrap();
+    assert_eq!(pool.get_submission_testcases(&[1]).await.unwrap(), rejudged);
+
+    let compile_error = SubmissionDetail {
+        submission_id: 2,
+        compile_error: Some("./Main.cpp:3:3: error".to_owned()),
+    };
+    pool.update_submission_detail(&compile_error, &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        pool.get_submission_detail(2).await.unwrap(),
+        Some(compile_error)
+    );
+    assert_eq!(
+        pool.get_submission_testcases(&[1, 2]).await.unwrap(),
+        rejudged
+    );
+}
@@ -0,0 +1,101 @@
+use sql_client::models::{Submission, SubmissionChange, SubmissionDetail};
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use sql_client::submission_history::{
+    SubmissionHistoryClient, SUBMISSION_REJUDGED, SUBMISSION_RESTORED, SUBMISSION_VANISHED,
+};
+
+mod utils;
+
+#[tokio::test]
+async fn test_submission_history() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let submission = |id: i64, result: &str| Submission {
+        id,
+        contest_id: "contest1".to_owned(),
+        problem_id: "problem1".to_owned(),
+        user_id: "user1".to_owned(),
+        result: result.to_owned(),
+        point: 100.0,
+        ..Default::default()
+    };
+    pool.update_submissions(&[submission(1, "AC"), submission(2, "AC")])
+        .await
+        .unwrap();
+    pool.update_submission_detail(
+        &SubmissionDetail {
+            submission_id: 2,
+            compile_error: None,
+        },
+        &[],
+    )
+    .await
+    .unwrap();
+    assert!(pool.load_submission_history(1).await.unwrap().is_empty());
+
+    let rejudged = Submission {
+        point: 0.0,
+        ..submission(1, "WA")
+    };
+    pool.insert_submission_changes(&[SubmissionChange::rejudged(
+        &submission(1, "AC"),
+        &rejudged,
+        1000,
+    )])
+    .await
+    .unwrap();
+    pool.remove_vanished_submissions(&[submission(2, "AC")], 2000)
+        .await
+        .unwrap();
+
+    let history = pool.load_submission_history(1).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, SUBMISSION_REJUDGED);
+    assert_eq!(history[0].old_result, "AC");
+    assert_eq!(history[0].new_result.as_deref(), Some("WA"));
+    assert_eq!(
+        (history[0].old_point, history[0].new_point),
+        (100.0, Some(0.0))
+    );
+    assert_eq!(history[0].detected_epoch_second, 1000);
+
+    let history = pool.load_submission_history(2).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, SUBMISSION_VANISHED);
+    assert_eq!(history[0].user_id, "user1");
+    assert_eq!(history[0].new_result, None);
+
+    let stored = pool
+        .get_submissions(SubmissionRequest::ContestAll {
+            contest_id: "contest1",
+        })
+        .await
+        .unwrap();
+    assert_eq!(stored.iter().map(|s| s.id).collect::<Vec<_>>(), vec![1]);
+    let (vanished_epoch_second,): (i64,) =
+        sqlx::query_as("SELECT vanished_epoch_second FROM vanished_submissions WHERE id = 2")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(vanished_epoch_second, 2000);
+
+    // The removal is undone with the details kept.
+    let restored = pool.restore_vanished_submissions(&[2], 3000).await.unwrap();
+    assert_eq!(restored, vec![submission(2, "AC")]);
+    let stored = pool
+        .get_submissions(SubmissionRequest::ContestAll {
+            contest_id: "contest1",
+        })
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 2);
+    assert!(pool.get_submission_detail(2).await.unwrap().is_some());
+    let history = pool.load_submission_history(2).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].kind, SUBMISSION_RESTORED);
+    assert_eq!(history[1].new_result.as_deref(), Some("AC"));
+    assert!(pool
+        .restore_vanished_submissions(&[2], 4000)
+        .await
+        .unwrap()
+        .is_empty());
+}
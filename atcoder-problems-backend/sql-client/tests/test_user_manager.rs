@@ -53,4 +53,13 @@ async fn test_user_manager() {
         },
         "`get_internal_user_info` after `atcoder_user_id` was set returned an unexpected value."
     );
+
+    assert!(!pool.is_admin(internal_user_id).await.unwrap());
+    sqlx::query("UPDATE internal_users SET is_admin = TRUE WHERE internal_user_id = $1")
+        .bind(internal_user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(pool.is_admin(internal_user_id).await.unwrap());
+    assert!(!pool.is_admin("unregistered").await.unwrap());
 }
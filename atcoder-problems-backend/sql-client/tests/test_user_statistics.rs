@@ -0,0 +1,45 @@
+use sql_client::accepted_count::AcceptedCountClient;
+use sql_client::language_count::LanguageCountClient;
+use sql_client::models::Submission;
+use sql_client::streak::StreakClient;
+use sql_client::user_statistics::UserStatisticsClient;
+
+mod utils;
+
+#[tokio::test]
+async fn test_replace_user_statistics() {
+    let pool = utils::initialize_and_connect_to_test_sql().await;
+    let submission = |id: i64, user_id: &str, problem_id: &str| Submission {
+        id,
+        user_id: user_id.to_owned(),
+        problem_id: problem_id.to_owned(),
+        language: "Rust".to_owned(),
+        result: "AC".to_owned(),
+        ..Default::default()
+    };
+    let submissions = vec![
+        submission(1, "user1", "problem1"),
+        submission(2, "user1", "problem2"),
+        submission(3, "user2", "problem1"),
+        submission(4, "user3", "problem1"),
+    ];
+    pool.update_accepted_count(&submissions).await.unwrap();
+    pool.update_language_count(&submissions, &[]).await.unwrap();
+    pool.update_streak_count(&submissions).await.unwrap();
+
+    // user1 has lost an accepted problem and user2 has lost all of them.
+    pool.replace_user_statistics(&["USER1", "user2"], &[submission(1, "user1", "problem1")])
+        .await
+        .unwrap();
+    assert_eq!(pool.get_users_accepted_count("user1").await, Some(1));
+    assert_eq!(pool.get_users_streak_count("user1").await, Some(1));
+    assert_eq!(pool.get_users_accepted_count("user2").await, None);
+    assert_eq!(pool.get_users_streak_count("user2").await, None);
+    assert!(pool
+        .load_users_language_count("user2")
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(pool.get_users_accepted_count("user3").await, Some(1));
+    assert_eq!(pool.get_users_streak_count("user3").await, Some(1));
+}
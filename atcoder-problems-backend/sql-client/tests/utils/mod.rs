@@ -1,8 +1,7 @@
+use sql_client::migration::MigrationClient;
 use sql_client::PgPool;
 use sqlx::Executor;
-use tokio::fs;
 
-const SQL_FILE: &str = "../../config/database-definition.sql";
 const SQL_URL_ENV_KEY: &str = "SQL_URL";
 
 #[cfg(test)]
@@ -29,7 +28,9 @@ pub async fn initialize_and_connect_to_test_sql() -> PgPool {
 }
 
 async fn initialize(pool: &PgPool) {
-    let sql = fs::read_to_string(SQL_FILE).await.unwrap();
     let mut conn = pool.acquire().await.unwrap();
-    conn.execute(sql.as_str()).await.unwrap();
+    conn.execute("DROP SCHEMA public CASCADE; CREATE SCHEMA public;")
+        .await
+        .unwrap();
+    pool.migrate_up().await.unwrap();
 }
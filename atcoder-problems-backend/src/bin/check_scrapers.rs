@@ -0,0 +1,30 @@
+use atcoder_client::transport::ReplayTransport;
+use atcoder_client::{AtCoderClient, RateLimitConfig};
+use atcoder_problems_backend::scraper_check::check_scrapers;
+use chrono::Utc;
+use std::env;
+
+/// Prints a JSON report to stdout, so logs are not initialized.
+#[actix_web::main]
+async fn main() {
+    let fixture_dir = env::var("SCRAPER_FIXTURE_DIR").unwrap_or_else(|_| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/atcoder-client/test_resources/replay"
+        )
+        .to_string()
+    });
+
+    let live = AtCoderClient::without_login(RateLimitConfig::default())
+        .expect("Failed to create a client");
+    let fixture = AtCoderClient::with_transport(
+        ReplayTransport::new(fixture_dir),
+        RateLimitConfig::default(),
+    );
+
+    let report = check_scrapers(&live, &fixture, Utc::now().timestamp() as u64).await;
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    if !report.passed {
+        std::process::exit(1);
+    }
+}
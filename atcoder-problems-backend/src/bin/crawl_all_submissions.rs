@@ -1,73 +1,163 @@
 use actix_web::rt::time;
-use anyhow::Result;
-use atcoder_client::AtCoderClient;
-use atcoder_problems_backend::crawler::WholeContestCrawler;
+use anyhow::{ensure, Context, Result};
+use atcoder_client::{AtCoderClient, RateLimitConfig};
+use atcoder_problems_backend::crawler::ParallelContestCrawler;
+use atcoder_problems_backend::session::{self, Credential};
+use atcoder_problems_backend::sink::Sink;
 use atcoder_problems_backend::utils::init_log_config;
-use log::{error, info};
+use chrono::Utc;
+use log::{error, info, warn};
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
 use sql_client::initialize_pool;
-use sql_client::models::Contest;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
 use sql_client::simple_client::SimpleClient;
-use std::{env, time::Duration};
+use sql_client::submission_client::SubmissionClient;
+use std::{env, fs, time::Duration};
 
 #[actix_web::main]
 async fn main() {
     init_log_config().unwrap();
     info!("Started");
-    let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args).expect("Invalid arguments");
 
-    let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
-    let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let mut credentials = load_credentials().expect("Failed to load the AtCoder accounts");
+    let concurrency = env::var("CRAWL_CONCURRENCY")
+        .ok()
+        .and_then(|concurrency| concurrency.parse().ok())
+        .unwrap_or(credentials.len());
+    if concurrency > credentials.len() {
+        warn!(
+            "Concurrency is limited to {}, the number of the accounts",
+            credentials.len()
+        );
+    }
+    credentials.truncate(concurrency.max(1));
+
+    // The request budget is shared by all the workers, since they all send requests to AtCoder.
+    let mut config = RateLimitConfig::default();
+    if let Some(requests_per_second) =
+        load_requests_per_second().expect("Invalid CRAWL_REQUESTS_PER_SECOND")
+    {
+        config.requests_per_second = requests_per_second;
+    }
 
     loop {
         info!("Start new loop");
-
-        match load_contest(&url).await {
-            Ok(contests) => {
-                for contest in contests {
-                    finish_one_contest(&url, &contest.id, &username, &password).await;
-                }
-            }
-            Err(e) => {
-                error!("Failed to load the contests: {:?}", e);
-                sleep_1sec().await;
-            }
+        if let Err(e) = crawl_all_contests(&sink, &credentials, &config).await {
+            error!("Failed to crawl the contests: {:?}", e);
+            sleep_1sec().await;
         }
     }
 }
 
-async fn finish_one_contest(url: &str, contest_id: &str, username: &str, password: &str) {
-    loop {
-        info!("Starting {}", contest_id);
-        match crawl_one_contest(url, contest_id, username, password).await {
-            Ok(_) => {
-                info!("Finished {}", contest_id);
-                return;
-            }
-            Err(e) => {
-                error!("Error while crawling {}: {:?}", contest_id, e);
-                sleep_1sec().await;
-            }
-        }
+/// Loads the accounts from `ATCODER_ACCOUNTS_FILE` if it is set,
+/// or the account of `ATCODER_USERNAME` and `ATCODER_PASSWORD`.
+fn load_credentials() -> Result<Vec<Credential>> {
+    if let Ok(path) = env::var("ATCODER_ACCOUNTS_FILE") {
+        let text = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path))?;
+        let credentials = session::parse_credentials(&text)?;
+        anyhow::ensure!(!credentials.is_empty(), "No account is written in {}", path);
+        return Ok(credentials);
     }
+    let username = env::var("ATCODER_USERNAME").context("ATCODER_USERNAME is not set.")?;
+    let password = env::var("ATCODER_PASSWORD").context("ATCODER_PASSWORD is not set.")?;
+    Ok(vec![Credential { username, password }])
+}
+
+/// Loads `CRAWL_REQUESTS_PER_SECOND` if it is set, which must be a positive number.
+fn load_requests_per_second() -> Result<Option<f64>> {
+    let value = match env::var("CRAWL_REQUESTS_PER_SECOND") {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    let requests_per_second = value
+        .trim()
+        .parse::<f64>()
+        .with_context(|| format!("{:?} is not a number", value))?;
+    ensure!(
+        requests_per_second.is_finite() && requests_per_second > 0.0,
+        "{:?} is not a positive number of requests per second",
+        value
+    );
+    Ok(Some(requests_per_second))
 }
 
-async fn crawl_one_contest(
-    url: &str,
-    contest_id: &str,
-    username: &str,
-    password: &str,
+async fn crawl_all_contests(
+    sink: &Sink,
+    credentials: &[Credential],
+    config: &RateLimitConfig,
 ) -> Result<()> {
-    let db = initialize_pool(url).await?;
-    let client = AtCoderClient::new(username, password).await?;
-    let crawler = WholeContestCrawler::new(db, client.clone(), contest_id);
-    crawler.crawl().await?;
+    match sink {
+        Sink::Postgres => {
+            let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+            crawl_contests(initialize_pool(&url).await?, credentials, config).await
+        }
+        Sink::Jsonl(dir) => crawl_contests(JsonlClient::new(dir)?, credentials, config).await,
+    }
+}
+
+/// Crawls every contest which has started, and crawls the failed ones again until all of them finish.
+async fn crawl_contests<C>(
+    db: C,
+    credentials: &[Credential],
+    config: &RateLimitConfig,
+) -> Result<()>
+where
+    C: SubmissionClient
+        + OrphanClient
+        + SimpleClient
+        + CrawlStateClient
+        + FailedPageClient
+        + SessionClient
+        + Clone
+        + Sync,
+{
+    let mut pending = load_contest_ids(&db).await?;
+    let base = AtCoderClient::without_login(config.clone())?;
+    let mut clients = vec![];
+    for credential in credentials {
+        let client =
+            session::login_sharing_limit(&db, &base, &credential.username, &credential.password)
+                .await?;
+        clients.push(client);
+    }
+    while !pending.is_empty() {
+        // Costs no request unless AtCoder has refused the session in the previous round.
+        for (client, credential) in clients.iter().zip(credentials) {
+            session::refresh(&db, client, &credential.username, &credential.password).await?;
+        }
+
+        let contest_ids = pending.iter().map(String::as_str).collect::<Vec<_>>();
+        let failures = ParallelContestCrawler::new(db.clone(), clients.clone())
+            .crawl(&contest_ids)
+            .await;
+        pending = failures.into_iter().map(|(id, _)| id).collect();
+        if !pending.is_empty() {
+            info!("Crawling {} failed contests again", pending.len());
+            sleep_1sec().await;
+        }
+    }
     Ok(())
 }
 
-async fn load_contest(url: &str) -> Result<Vec<Contest>> {
-    let db = initialize_pool(url).await?;
-    let contests = db.load_contests().await?;
-    Ok(contests)
+async fn load_contest_ids<C: SimpleClient>(db: &C) -> Result<Vec<String>> {
+    let now = Utc::now().timestamp();
+    let contest_ids = db
+        .load_contests()
+        .await?
+        .into_iter()
+        .filter(|c| c.start_epoch_second <= now)
+        .map(|c| c.id)
+        .collect::<Vec<_>>();
+    ensure!(
+        !contest_ids.is_empty(),
+        "No contest is known, which crawl_problems stores."
+    );
+    Ok(contest_ids)
 }
 
 async fn sleep_1sec() {
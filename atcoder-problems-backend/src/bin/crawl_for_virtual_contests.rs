@@ -1,7 +1,8 @@
 use actix_web::rt::time;
 use anyhow::Result;
-use atcoder_client::AtCoderClient;
 use atcoder_problems_backend::crawler::{FixCrawler, VirtualContestCrawler};
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
 use atcoder_problems_backend::utils::init_log_config;
 use chrono::Utc;
 use rand::{thread_rng, Rng};
@@ -16,7 +17,7 @@ const FIX_RANGE_SECOND: i64 = 10 * 60;
 async fn crawl<R: Rng>(url: &str, rng: &mut R, username: &str, password: &str) -> Result<()> {
     log::info!("Start crawling...");
     let pg_pool = initialize_pool(&url).await?;
-    let client = AtCoderClient::new(username, password).await?;
+    let client = session::login(&pg_pool, username, password).await?;
     let mut crawler = VirtualContestCrawler::new(pg_pool.clone(), client.clone(), rng);
     crawler.crawl().await?;
     log::info!("Finished crawling");
@@ -33,6 +34,12 @@ async fn crawl<R: Rng>(url: &str, rng: &mut R, username: &str, password: &str) -
 #[actix_web::main]
 async fn main() {
     init_log_config().unwrap();
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    if Sink::from_args(&mut args).expect("Invalid arguments") != Sink::Postgres {
+        panic!(
+            "Only --sink postgres is supported, since the virtual contests are in the database."
+        );
+    }
     let url = env::var("SQL_URL").expect("SQL_URL must be set.");
     let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
     let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
@@ -1,23 +1,50 @@
 use actix_web::rt::time;
-use anyhow::Result;
-use atcoder_client::AtCoderClient;
+use anyhow::{ensure, Result};
 use atcoder_problems_backend::crawler::WholeContestCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
 use atcoder_problems_backend::utils::init_log_config;
+use chrono::Utc;
 use log::info;
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
 use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
 use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::SubmissionClient;
 use std::{env, time::Duration};
 
 const NEW_CONTEST_NUM: usize = 5;
 
-async fn iteration(url: &str, username: &str, password: &str) -> Result<()> {
-    let db = initialize_pool(&url).await?;
-    let client = AtCoderClient::new(username, password).await?;
-    let mut contests = db.load_contests().await?;
+async fn crawl<C>(db: C, username: &str, password: &str) -> Result<()>
+where
+    C: SubmissionClient
+        + OrphanClient
+        + SimpleClient
+        + CrawlStateClient
+        + FailedPageClient
+        + SessionClient
+        + Clone
+        + Sync,
+{
+    let client = session::login(&db, username, password).await?;
+    let now = Utc::now().timestamp();
+    let mut contests = db
+        .load_contests()
+        .await?
+        .into_iter()
+        .filter(|c| c.start_epoch_second <= now)
+        .collect::<Vec<_>>();
+    ensure!(
+        !contests.is_empty(),
+        "No contest is known, which crawl_problems stores."
+    );
     contests.sort_by_key(|c| c.start_epoch_second);
     contests.reverse();
 
-    for contest in &contests[0..NEW_CONTEST_NUM] {
+    for contest in contests.iter().take(NEW_CONTEST_NUM) {
         info!("Starting {}", contest.id);
         let crawler = WholeContestCrawler::new(db.clone(), client.clone(), &contest.id);
         crawler.crawl().await?;
@@ -25,17 +52,28 @@ async fn iteration(url: &str, username: &str, password: &str) -> Result<()> {
     Ok(())
 }
 
+async fn iteration(sink: &Sink, username: &str, password: &str) -> Result<()> {
+    match sink {
+        Sink::Postgres => {
+            let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+            crawl(initialize_pool(&url).await?, username, password).await
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?, username, password).await,
+    }
+}
+
 #[actix_web::main]
-async fn main() {
-    init_log_config().unwrap();
+async fn main() -> Result<()> {
+    init_log_config()?;
     info!("Started");
-    let url = env::var("SQL_URL").expect("SQL_URL is not set.");
     let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
     let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args)?;
 
     loop {
         info!("Start new loop");
-        if let Err(e) = iteration(&url, &username, &password).await {
+        if let Err(e) = iteration(&sink, &username, &password).await {
             log::error!("{:?}", e);
             time::sleep(Duration::from_secs(1)).await;
         }
@@ -1,23 +1,55 @@
-use atcoder_client::AtCoderClient;
-use atcoder_problems_backend::crawler::ProblemCrawler;
+use anyhow::Result;
+use atcoder_problems_backend::crawler::{HiddenContestSource, ProblemCrawler};
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
 use atcoder_problems_backend::utils::init_log_config;
+use sql_client::contest_problem::ContestProblemClient;
+use sql_client::hidden_contest::HiddenContestClient;
 use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::revision::RevisionClient;
+use sql_client::session::SessionClient;
+use sql_client::simple_client::SimpleClient;
 use std::env;
 
-#[actix_web::main]
-async fn main() {
-    init_log_config().unwrap();
-    log::info!("Started");
-    let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+async fn crawl<C>(db: C, backfill_limits: bool) -> Result<()>
+where
+    C: SimpleClient
+        + ContestProblemClient
+        + HiddenContestClient
+        + OrphanClient
+        + RevisionClient
+        + SessionClient,
+{
     let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
     let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let client = session::login(&db, &username, &password).await?;
+    let crawler =
+        ProblemCrawler::new(db, client).with_hidden_contests(HiddenContestSource::from_env());
+    if backfill_limits {
+        crawler.backfill_limits().await
+    } else {
+        crawler.crawl().await
+    }
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    log::info!("Started");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args)?;
+    let backfill_limits = args.iter().any(|arg| arg == "--backfill-limits");
 
-    let db = initialize_pool(&url).await.unwrap();
-    let client = AtCoderClient::new(&username, &password)
-        .await
-        .expect("AtCoder authentication failure");
-    let crawler = ProblemCrawler::new(db, client);
-    crawler.crawl().await.expect("Failed to crawl");
+    match sink {
+        Sink::Postgres => {
+            let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+            crawl(initialize_pool(&url).await?, backfill_limits).await?;
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?, backfill_limits).await?,
+    }
 
     log::info!("Finished");
+    Ok(())
 }
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use atcoder_problems_backend::crawler::RatingHistoryCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
+use atcoder_problems_backend::utils::init_log_config;
+use log::info;
+use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::rating_history::RatingHistoryClient;
+use sql_client::session::SessionClient;
+use sql_client::submission_client::SubmissionClient;
+use std::env;
+
+async fn crawl<C>(db: C) -> Result<()>
+where
+    C: SubmissionClient + RatingHistoryClient + SessionClient,
+{
+    let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
+    let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let client = session::login(&db, &username, &password).await?;
+    RatingHistoryCrawler::new(db, client).crawl().await
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    info!("Started");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    match Sink::from_args(&mut args)? {
+        Sink::Postgres => {
+            let url =
+                env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.");
+            crawl(initialize_pool(&url).await?).await?;
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?).await?,
+    }
+    info!("Finished");
+    Ok(())
+}
@@ -1,29 +1,58 @@
 use actix_web::rt::time;
 use anyhow::Result;
-use atcoder_client::AtCoderClient;
 use atcoder_problems_backend::crawler::RecentCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
 use atcoder_problems_backend::utils::init_log_config;
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
 use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::SubmissionClient;
 use std::{env, time::Duration};
 
-async fn crawl(url: &str, username: &str, password: &str) -> Result<()> {
-    let db = initialize_pool(url).await?;
-    let client = AtCoderClient::new(username, password).await?;
-    let crawler = RecentCrawler::new(db, client);
-    crawler.crawl().await
+async fn crawl<C>(db: C, username: &str, password: &str) -> Result<()>
+where
+    C: SubmissionClient
+        + OrphanClient
+        + SimpleClient
+        + CrawlStateClient
+        + FailedPageClient
+        + SessionClient
+        + Sync,
+{
+    let client = session::login(&db, username, password).await?;
+    RecentCrawler::new(db, client)
+        .with_page_budget_from_env()
+        .crawl()
+        .await
+}
+
+async fn iteration(sink: &Sink, username: &str, password: &str) -> Result<()> {
+    match sink {
+        Sink::Postgres => {
+            let url = env::var("SQL_URL").expect("SQL_URL must be set.");
+            crawl(initialize_pool(&url).await?, username, password).await
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?, username, password).await,
+    }
 }
 
 #[actix_web::main]
-async fn main() {
-    init_log_config().unwrap();
+async fn main() -> Result<()> {
+    init_log_config()?;
     log::info!("Started");
-    let url = env::var("SQL_URL").expect("SQL_URL must be set.");
     let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
     let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args)?;
 
     loop {
         log::info!("Start new loop");
-        if let Err(e) = crawl(&url, &username, &password).await {
+        if let Err(e) = iteration(&sink, &username, &password).await {
             log::error!("{:?}", e);
             time::sleep(Duration::from_secs(1)).await;
         }
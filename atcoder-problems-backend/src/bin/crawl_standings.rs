@@ -0,0 +1,45 @@
+use anyhow::Result;
+use atcoder_problems_backend::crawler::StandingsCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
+use atcoder_problems_backend::utils::init_log_config;
+use log::info;
+use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::session::SessionClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::standings::StandingsClient;
+use std::env;
+
+async fn crawl<C>(db: C, contest_id: Option<&str>) -> Result<()>
+where
+    C: SimpleClient + StandingsClient + SessionClient,
+{
+    let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
+    let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let client = session::login(&db, &username, &password).await?;
+    let crawler = StandingsCrawler::new(db, client);
+    match contest_id {
+        Some(contest_id) => crawler.crawl_contest(contest_id).await,
+        None => crawler.crawl().await,
+    }
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    info!("Started");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args)?;
+    let contest_id = args.first().map(String::as_str);
+    match sink {
+        Sink::Postgres => {
+            let url =
+                env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.");
+            crawl(initialize_pool(&url).await?, contest_id).await?;
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?, contest_id).await?,
+    }
+    info!("Finished");
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::{ensure, Result};
+use atcoder_problems_backend::crawler::UserSubmissionCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
+use atcoder_problems_backend::utils::init_log_config;
+use log::info;
+use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::SubmissionClient;
+use std::env;
+
+async fn crawl<C>(db: C, user_id: &str, contest_ids: &[String]) -> Result<()>
+where
+    C: SubmissionClient + OrphanClient + SimpleClient + SessionClient + Sync,
+{
+    ensure!(
+        !contest_ids.is_empty() || !db.load_contests().await?.is_empty(),
+        "No contest is known, which crawl_problems stores. Give the contest ids instead."
+    );
+    let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
+    let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let client = session::login(&db, &username, &password).await?;
+    let crawler = UserSubmissionCrawler::new(db, client, user_id);
+    if contest_ids.is_empty() {
+        crawler.crawl().await?;
+    } else {
+        let contest_ids = contest_ids.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+        crawler.crawl_contests(&contest_ids).await?;
+    }
+    Ok(())
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    info!("Started");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args)?;
+    let mut args = args.into_iter();
+    let user_id = args.next().expect(
+        "user_id is not set.\nUsage: cargo run --bin crawl_user_submissions <user_id> [<contest_id>...] [--sink <postgres|jsonl>] [--out <dir>]",
+    );
+    let contest_ids = args.collect::<Vec<_>>();
+
+    match sink {
+        Sink::Postgres => {
+            let url =
+                env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.");
+            let db = initialize_pool(&url).await?;
+            crawl(db, &user_id, &contest_ids).await
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?, &user_id, &contest_ids).await,
+    }
+}
@@ -1,24 +1,72 @@
-use anyhow::Result;
-use atcoder_client::AtCoderClient;
+use anyhow::{bail, Result};
 use atcoder_problems_backend::crawler::WholeContestCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
+use atcoder_problems_backend::updater::reconciliation_update;
 use atcoder_problems_backend::utils::init_log_config;
 use log::info;
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
 use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
+use sql_client::submission_client::SubmissionClient;
 use std::env;
 
+const USAGE: &str = "Usage: cargo run --bin crawl_whole_contest <contest_id> [--reconcile] [--sink <postgres|jsonl>] [--out <dir>]";
+
+async fn crawl<C>(db: C, contest_id: &str, username: &str, password: &str) -> Result<()>
+where
+    C: SubmissionClient + OrphanClient + CrawlStateClient + FailedPageClient + SessionClient + Sync,
+{
+    let client = session::login(&db, username, password).await?;
+    WholeContestCrawler::new(db, client, contest_id)
+        .crawl()
+        .await
+}
+
 #[actix_web::main]
 async fn main() -> Result<()> {
     init_log_config()?;
     info!("Started");
-    let url = env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.");
     let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
     let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
-    let contest_id = env::args()
-        .nth(1)
-        .expect("contest_id is not set.\nUsage: cargo run --bin crawl_whole_contest <contest_id>");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let sink = Sink::from_args(&mut args)?;
+    let reconcile = args.iter().any(|arg| arg == "--reconcile");
+    let contest_id = args
+        .into_iter()
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| panic!("contest_id is not set.\n{}", USAGE));
+
+    let url = match sink {
+        Sink::Jsonl(_) if reconcile => bail!("--reconcile needs the database.\n{}", USAGE),
+        Sink::Jsonl(dir) => {
+            let db = JsonlClient::new(&dir)?;
+            crawl(db, &contest_id, &username, &password).await?;
+            info!("Wrote the submissions of {} to {:?}", contest_id, dir);
+            return Ok(());
+        }
+        Sink::Postgres => {
+            env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.")
+        }
+    };
     let db = initialize_pool(&url).await?;
-    let client = AtCoderClient::new(&username, &password).await?;
-    let crawler = WholeContestCrawler::new(db, client, contest_id);
-    crawler.crawl().await?;
+    if !reconcile {
+        return crawl(db, &contest_id, &username, &password).await;
+    }
+
+    let client = session::login(&db, &username, &password).await?;
+    let crawler = WholeContestCrawler::new(db.clone(), client, contest_id);
+    let reconciliation = crawler.reconcile().await?;
+    info!(
+        "{} rejudged, {} vanished",
+        reconciliation.rejudged.len(),
+        reconciliation.vanished.len()
+    );
+    if !reconciliation.is_empty() {
+        reconciliation_update(&db, &reconciliation).await?;
+    }
     Ok(())
 }
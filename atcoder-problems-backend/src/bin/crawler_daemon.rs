@@ -0,0 +1,221 @@
+use anyhow::{ensure, Result};
+use atcoder_client::AtCoderClient;
+use atcoder_problems_backend::crawler::{
+    CrawlRequestWorker, FailedPageCrawler, FixCrawler, HiddenContestSource, ProblemCrawler,
+    RecentCrawler, VirtualContestCrawler, WholeContestCrawler,
+};
+use atcoder_problems_backend::scheduler::{run_status_server, JobConfig, Scheduler};
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::updater::{batch_update, delta_update, reconciliation_update};
+use atcoder_problems_backend::utils::init_log_config;
+use chrono::Utc;
+use futures_util::future::LocalBoxFuture;
+use log::{error, info};
+use rand::thread_rng;
+use sql_client::simple_client::SimpleClient;
+use sql_client::{initialize_pool, PgPool};
+use std::env;
+use std::rc::Rc;
+use std::time::Duration;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const NEW_CONTEST_NUM: usize = 5;
+
+type Job = fn(Rc<Context>) -> LocalBoxFuture<'static, Result<()>>;
+
+/// Shared by all the jobs, so that they use one connection pool and one AtCoder session.
+struct Context {
+    db: PgPool,
+    client: AtCoderClient,
+    username: String,
+    password: String,
+}
+
+impl Context {
+    /// Logs in again if the session has expired while the daemon is running.
+    async fn refresh_session(&self) -> Result<()> {
+        session::refresh(&self.db, &self.client, &self.username, &self.password).await
+    }
+}
+
+async fn crawl_virtual_contests(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    let mut rng = thread_rng();
+    let mut crawler = VirtualContestCrawler::new(ctx.db.clone(), ctx.client.clone(), &mut rng);
+    crawler.crawl().await?;
+
+    let from = Utc::now().timestamp() - 10 * MINUTE as i64;
+    FixCrawler::new(ctx.db.clone(), ctx.client.clone(), from)
+        .crawl()
+        .await
+}
+
+async fn crawl_recent_submissions(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    RecentCrawler::new(ctx.db.clone(), ctx.client.clone())
+        .with_page_budget_from_env()
+        .crawl()
+        .await
+}
+
+async fn crawl_new_contests(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    let now = Utc::now().timestamp();
+    let mut contests = ctx
+        .db
+        .load_contests()
+        .await?
+        .into_iter()
+        .filter(|c| c.start_epoch_second <= now)
+        .collect::<Vec<_>>();
+    contests.sort_by_key(|c| std::cmp::Reverse(c.start_epoch_second));
+    for contest in contests.iter().take(NEW_CONTEST_NUM) {
+        WholeContestCrawler::new(ctx.db.clone(), ctx.client.clone(), &contest.id)
+            .crawl()
+            .await?;
+    }
+    Ok(())
+}
+
+async fn reconcile_new_contests(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    let now = Utc::now().timestamp();
+    let mut contests = ctx
+        .db
+        .load_contests()
+        .await?
+        .into_iter()
+        .filter(|c| c.start_epoch_second <= now)
+        .collect::<Vec<_>>();
+    contests.sort_by_key(|c| std::cmp::Reverse(c.start_epoch_second));
+
+    // A contest failing to be reconciled doesn't keep the others from being reconciled.
+    let mut failed = 0;
+    for contest in contests.iter().take(NEW_CONTEST_NUM) {
+        if let Err(e) = reconcile_contest(&ctx, &contest.id).await {
+            error!("Failed to reconcile {}: {:?}", contest.id, e);
+            failed += 1;
+        }
+    }
+    ensure!(failed == 0, "Failed to reconcile {} contests", failed);
+    Ok(())
+}
+
+async fn reconcile_contest(ctx: &Context, contest_id: &str) -> Result<()> {
+    let reconciliation = WholeContestCrawler::new(ctx.db.clone(), ctx.client.clone(), contest_id)
+        .reconcile()
+        .await?;
+    if !reconciliation.is_empty() {
+        reconciliation_update(&ctx.db, &reconciliation).await?;
+    }
+    Ok(())
+}
+
+async fn retry_failed_pages(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    FailedPageCrawler::new(ctx.db.clone(), ctx.client.clone())
+        .crawl()
+        .await?;
+    Ok(())
+}
+
+async fn crawl_problems(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    ProblemCrawler::new(ctx.db.clone(), ctx.client.clone())
+        .with_hidden_contests(HiddenContestSource::from_env())
+        .crawl()
+        .await
+}
+
+async fn fix_invalid_submissions(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    let from = Utc::now().timestamp() - DAY as i64;
+    FixCrawler::new(ctx.db.clone(), ctx.client.clone(), from)
+        .crawl()
+        .await
+}
+
+async fn process_crawl_requests(ctx: Rc<Context>) -> Result<()> {
+    ctx.refresh_session().await?;
+    let processed = CrawlRequestWorker::new(ctx.db.clone(), ctx.client.clone())
+        .drain()
+        .await?;
+    if processed > 0 {
+        info!("Processed {} crawl requests", processed);
+    }
+    Ok(())
+}
+
+#[actix_web::main]
+async fn main() {
+    init_log_config().unwrap();
+    info!("Started");
+    let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+    let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
+    let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let port = env::var("STATUS_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(8081);
+    let max_concurrent_jobs = env::var("MAX_CONCURRENT_JOBS")
+        .ok()
+        .and_then(|jobs| jobs.parse().ok())
+        .unwrap_or(3);
+
+    let db = initialize_pool(&url).await.unwrap();
+    let client = session::login(&db, &username, &password)
+        .await
+        .expect("AtCoder authentication failure");
+    let ctx = Rc::new(Context {
+        db,
+        client,
+        username,
+        password,
+    });
+
+    let mut scheduler = Scheduler::new().with_max_concurrent_jobs(max_concurrent_jobs);
+    let mut add_job = |name: &str, interval: u64, priority: u32, job: Job| {
+        let config = JobConfig::from_env(
+            name,
+            JobConfig::new(Duration::from_secs(interval), priority),
+        );
+        let ctx = ctx.clone();
+        scheduler.add_job(name, config, move || job(ctx.clone()));
+    };
+    add_job("crawl_requests", 10, 70, |ctx| {
+        Box::pin(process_crawl_requests(ctx))
+    });
+    add_job("virtual_contests", 10, 60, |ctx| {
+        Box::pin(crawl_virtual_contests(ctx))
+    });
+    add_job("recent_submissions", MINUTE, 50, |ctx| {
+        Box::pin(crawl_recent_submissions(ctx))
+    });
+    add_job("new_contests", MINUTE, 40, |ctx| {
+        Box::pin(crawl_new_contests(ctx))
+    });
+    add_job("failed_pages", 10 * MINUTE, 35, |ctx| {
+        Box::pin(retry_failed_pages(ctx))
+    });
+    add_job("delta_update", 10 * MINUTE, 30, |ctx| {
+        Box::pin(async move { delta_update(&ctx.db).await })
+    });
+    add_job("problems", HOUR, 20, |ctx| Box::pin(crawl_problems(ctx)));
+    add_job("fix_invalid_submissions", HOUR, 10, |ctx| {
+        Box::pin(fix_invalid_submissions(ctx))
+    });
+    add_job("reconcile_contests", DAY, 5, |ctx| {
+        Box::pin(reconcile_new_contests(ctx))
+    });
+    add_job("batch_update", DAY, 0, |ctx| {
+        Box::pin(async move { batch_update(&ctx.db).await })
+    });
+
+    let status = scheduler.status();
+    actix_web::rt::spawn(scheduler.run());
+    run_status_server(status, port)
+        .await
+        .expect("Failed to run the status server");
+}
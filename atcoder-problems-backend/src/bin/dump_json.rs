@@ -127,6 +127,8 @@ async fn main() -> Result<()> {
                 problems.problem_index AS merged_problem_index,
                 problems.name AS merged_problem_name,
                 problems.title AS merged_problem_title,
+                problems.time_limit_ms AS merged_time_limit_ms,
+                problems.memory_limit_kb AS merged_memory_limit_kb,
 
                 shortest.submission_id AS shortest_submission_id,
                 shortest.contest_id AS shortest_contest_id,
@@ -140,8 +142,13 @@ async fn main() -> Result<()> {
                 first.contest_id AS first_contest_id,
                 first_submissions.user_id AS first_user_id,
 
+                least_memory.submission_id AS least_memory_submission_id,
+                least_memory.contest_id AS least_memory_contest_id,
+                least_memory_submissions.user_id AS least_memory_user_id,
+
                 shortest_submissions.length AS source_code_length,
                 fastest_submissions.execution_time AS execution_time,
+                least_memory_submissions.memory AS memory,
                 points.point,
                 solver.user_count AS solver_count
             FROM
@@ -149,9 +156,11 @@ async fn main() -> Result<()> {
                 LEFT JOIN shortest ON shortest.problem_id = problems.id
                 LEFT JOIN fastest ON fastest.problem_id = problems.id
                 LEFT JOIN first ON first.problem_id = problems.id
+                LEFT JOIN least_memory ON least_memory.problem_id = problems.id
                 LEFT JOIN submissions AS shortest_submissions ON shortest.submission_id = shortest_submissions.id
                 LEFT JOIN submissions AS fastest_submissions ON fastest.submission_id = fastest_submissions.id
                 LEFT JOIN submissions AS first_submissions ON first.submission_id = first_submissions.id
+                LEFT JOIN submissions AS least_memory_submissions ON least_memory.submission_id = least_memory_submissions.id
                 LEFT JOIN points ON points.problem_id = problems.id
                 LEFT JOIN solver ON solver.problem_id = problems.id
                 ORDER BY problems.id;
@@ -163,6 +172,8 @@ async fn main() -> Result<()> {
         let problem_index: String = row.get("merged_problem_index");
         let name: String = row.get("merged_problem_name");
         let title: String = row.get("merged_problem_title");
+        let time_limit_ms: Option<i32> = row.get("merged_time_limit_ms");
+        let memory_limit_kb: Option<i32> = row.get("merged_memory_limit_kb");
 
         let shortest_submission_id: Option<i64> = row.get("shortest_submission_id");
         let shortest_contest_id: Option<String> = row.get("shortest_contest_id");
@@ -176,8 +187,13 @@ async fn main() -> Result<()> {
         let first_contest_id: Option<String> = row.get("first_contest_id");
         let first_user_id: Option<String> = row.get("first_user_id");
 
+        let least_memory_submission_id: Option<i64> = row.get("least_memory_submission_id");
+        let least_memory_contest_id: Option<String> = row.get("least_memory_contest_id");
+        let least_memory_user_id: Option<String> = row.get("least_memory_user_id");
+
         let source_code_length: Option<i32> = row.get("source_code_length");
         let execution_time: Option<i32> = row.get("execution_time");
+        let memory: Option<i32> = row.get("memory");
         let point: Option<f64> = row.get("point");
         let solver_count: Option<i32> = row.get("solver_count");
 
@@ -188,6 +204,8 @@ async fn main() -> Result<()> {
             name,
 
             title,
+            time_limit_ms,
+            memory_limit_kb,
             shortest_submission_id,
             shortest_contest_id,
             shortest_user_id,
@@ -197,8 +215,12 @@ async fn main() -> Result<()> {
             first_submission_id,
             first_contest_id,
             first_user_id,
+            least_memory_submission_id,
+            least_memory_contest_id,
+            least_memory_user_id,
             source_code_length,
             execution_time,
+            memory,
             point,
             solver_count
         }
@@ -246,6 +268,8 @@ struct MergedProblem {
     problem_index: String,
     name: String,
     title: String,
+    time_limit_ms: Option<i32>,
+    memory_limit_kb: Option<i32>,
     shortest_submission_id: Option<i64>,
     shortest_contest_id: Option<String>,
     shortest_user_id: Option<String>,
@@ -255,8 +279,12 @@ struct MergedProblem {
     first_submission_id: Option<i64>,
     first_contest_id: Option<String>,
     first_user_id: Option<String>,
+    least_memory_submission_id: Option<i64>,
+    least_memory_contest_id: Option<String>,
+    least_memory_user_id: Option<String>,
     source_code_length: Option<i32>,
     execution_time: Option<i32>,
+    memory: Option<i32>,
     point: Option<f64>,
     solver_count: Option<i32>,
 }
@@ -1,27 +1,44 @@
-use atcoder_client::AtCoderClient;
+use anyhow::Result;
 use atcoder_problems_backend::crawler::FixCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
 use atcoder_problems_backend::utils::init_log_config;
 use chrono::Utc;
 use log::info;
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
 use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
+use sql_client::submission_client::SubmissionClient;
 use std::env;
 
 const ONE_DAY: i64 = 24 * 3600;
 
-#[actix_web::main]
-async fn main() {
-    init_log_config().unwrap();
-    info!("Started");
-    let url = env::var("SQL_URL").expect("SQL_URL must be set.");
+async fn crawl<C>(db: C) -> Result<()>
+where
+    C: SubmissionClient + OrphanClient + CrawlStateClient + FailedPageClient + SessionClient + Sync,
+{
     let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
     let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
-
-    let db = initialize_pool(&url).await.unwrap();
     let now = Utc::now().timestamp();
-    let client = AtCoderClient::new(&username, &password)
-        .await
-        .expect("AtCoder authentication failure");
-    let crawler = FixCrawler::new(db, client, now - ONE_DAY);
-    crawler.crawl().await.expect("Failed to crawl");
+    let client = session::login(&db, &username, &password).await?;
+    FixCrawler::new(db, client, now - ONE_DAY).crawl().await
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    info!("Started");
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    match Sink::from_args(&mut args)? {
+        Sink::Postgres => {
+            let url = env::var("SQL_URL").expect("SQL_URL must be set.");
+            crawl(initialize_pool(&url).await?).await?;
+        }
+        Sink::Jsonl(dir) => crawl(JsonlClient::new(dir)?).await?,
+    }
     info!("Finished fixing.");
+    Ok(())
 }
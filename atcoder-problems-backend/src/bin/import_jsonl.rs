@@ -0,0 +1,106 @@
+use anyhow::Result;
+use atcoder_problems_backend::utils::init_log_config;
+use log::info;
+use sql_client::contest_problem::ContestProblemClient;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::initialize_pool;
+use sql_client::jsonl::{
+    JsonlClient, CONTESTS_FILE, CONTEST_PROBLEM_FILE, CONTEST_REVISIONS_FILE, FAILED_PAGES_FILE,
+    PROBLEMS_FILE, PROBLEM_REVISIONS_FILE, RATING_HISTORY_FILE, STANDINGS_FILE,
+    STANDINGS_TASK_RESULTS_FILE, SUBMISSIONS_FILE,
+};
+use sql_client::models::{
+    Contest, ContestProblem, ContestRevision, ContestStanding, ContestStandingTaskResult,
+    FailedPage, Problem, ProblemRevision, Submission, UserRatingHistory,
+};
+use sql_client::orphan::OrphanClient;
+use sql_client::rating_history::RatingHistoryClient;
+use sql_client::revision::RevisionClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::standings::StandingsClient;
+use sql_client::submission_client::SubmissionClient;
+use std::collections::BTreeMap;
+use std::env;
+
+const CHUNK_SIZE: usize = 10_000;
+
+/// Loads the records written by the crawlers with `--sink jsonl` into the database.
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    info!("Started");
+    let url = env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.");
+    let dir = env::args()
+        .nth(1)
+        .expect("dir is not set.\nUsage: cargo run --bin import_jsonl <dir>");
+    let jsonl = JsonlClient::new(&dir)?;
+    let db = initialize_pool(&url).await?;
+
+    let contests = jsonl.read_latest(CONTESTS_FILE, |c: &Contest| c.id.clone())?;
+    for chunk in contests.chunks(CHUNK_SIZE) {
+        db.insert_contests(chunk).await?;
+    }
+    info!("Imported {} contests", contests.len());
+
+    let problems = jsonl.read_latest(PROBLEMS_FILE, |p: &Problem| p.id.clone())?;
+    for chunk in problems.chunks(CHUNK_SIZE) {
+        db.insert_problems(chunk).await?;
+    }
+    info!("Imported {} problems", problems.len());
+
+    let contest_problem = jsonl.read::<ContestProblem>(CONTEST_PROBLEM_FILE)?;
+    for chunk in contest_problem.chunks(CHUNK_SIZE) {
+        db.insert_contest_problem(chunk).await?;
+    }
+    info!("Imported {} contest problems", contest_problem.len());
+
+    let contest_revisions = jsonl.read::<ContestRevision>(CONTEST_REVISIONS_FILE)?;
+    db.insert_contest_revisions(&contest_revisions).await?;
+    let problem_revisions = jsonl.read::<ProblemRevision>(PROBLEM_REVISIONS_FILE)?;
+    // The problems have been imported as revised, so that only the revisions are recorded.
+    db.revise_problems(&[], &problem_revisions).await?;
+    info!(
+        "Imported {} revisions",
+        contest_revisions.len() + problem_revisions.len()
+    );
+
+    let submissions = jsonl.read_latest(SUBMISSIONS_FILE, |s: &Submission| s.id)?;
+    for chunk in submissions.chunks(CHUNK_SIZE) {
+        db.update_submissions(chunk).await?;
+        db.record_submission_orphans(chunk).await?;
+    }
+    info!("Imported {} submissions", submissions.len());
+
+    let history = jsonl.read_latest(RATING_HISTORY_FILE, |h: &UserRatingHistory| {
+        (h.user_id.clone(), h.contest_id.clone())
+    })?;
+    let mut user_history = BTreeMap::<_, Vec<_>>::new();
+    for h in history {
+        user_history.entry(h.user_id.clone()).or_default().push(h);
+    }
+    for (user_id, history) in user_history.iter() {
+        db.update_user_rating_history(user_id, history).await?;
+    }
+    info!(
+        "Imported the rating history of {} users",
+        user_history.len()
+    );
+
+    let mut standings = BTreeMap::<_, (Vec<_>, Vec<_>)>::new();
+    for s in jsonl.read::<ContestStanding>(STANDINGS_FILE)? {
+        standings.entry(s.contest_id.clone()).or_default().0.push(s);
+    }
+    for r in jsonl.read::<ContestStandingTaskResult>(STANDINGS_TASK_RESULTS_FILE)? {
+        standings.entry(r.contest_id.clone()).or_default().1.push(r);
+    }
+    for (contest_id, (standings, task_results)) in standings.iter() {
+        db.update_contest_standings(contest_id, standings, task_results)
+            .await?;
+    }
+    info!("Imported the standings of {} contests", standings.len());
+
+    let failed_pages = jsonl.read::<FailedPage>(FAILED_PAGES_FILE)?;
+    db.merge_failed_pages(&failed_pages).await?;
+    info!("Imported {} failed pages", failed_pages.len());
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anyhow::{bail, Result};
+use atcoder_problems_backend::utils::init_log_config;
+use log::info;
+use sql_client::initialize_pool;
+use sql_client::migration::{verify_statuses, MigrationClient};
+use std::env;
+
+const USAGE: &str = "Usage: cargo run --bin migrate <up|status|verify|baseline>";
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    let url = env::var("SQL_URL").expect("SQL_URL should be set as environmental variable.");
+    let command = env::args().nth(1).expect(USAGE);
+    let db = initialize_pool(&url).await?;
+
+    match command.as_str() {
+        "up" => {
+            let versions = db.migrate_up().await?;
+            if versions.is_empty() {
+                info!("The database schema is up to date");
+            }
+            for version in versions {
+                info!("Applied migration {}", version);
+            }
+        }
+        "status" => {
+            for status in db.migration_status().await? {
+                println!(
+                    "{:04} {:<32} {:?}",
+                    status.version, status.name, status.state
+                );
+            }
+        }
+        "verify" => {
+            verify_statuses(&db.migration_status().await?)?;
+            info!("The applied migrations match this build");
+        }
+        "baseline" => {
+            db.mark_baseline_applied().await?;
+            info!("Marked the baseline as applied");
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(())
+}
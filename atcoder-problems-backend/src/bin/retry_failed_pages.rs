@@ -0,0 +1,42 @@
+use anyhow::Result;
+use atcoder_problems_backend::crawler::FailedPageCrawler;
+use atcoder_problems_backend::session;
+use atcoder_problems_backend::sink::Sink;
+use atcoder_problems_backend::utils::init_log_config;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::initialize_pool;
+use sql_client::jsonl::JsonlClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::session::SessionClient;
+use sql_client::submission_client::SubmissionClient;
+use std::env;
+
+/// Returns whether some pages have been given up.
+async fn retry<C>(db: C) -> Result<bool>
+where
+    C: SubmissionClient + OrphanClient + FailedPageClient + SessionClient + Sync,
+{
+    let username = env::var("ATCODER_USERNAME").expect("ATCODER_USERNAME is not set.");
+    let password = env::var("ATCODER_PASSWORD").expect("ATCODER_PASSWORD is not set.");
+    let client = session::login(&db, &username, &password).await?;
+    let summary = FailedPageCrawler::new(db, client).crawl().await?;
+    Ok(!summary.given_up.is_empty())
+}
+
+/// Exits with 1 if some pages have been given up, so that someone looks into them.
+#[actix_web::main]
+async fn main() -> Result<()> {
+    init_log_config()?;
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let given_up = match Sink::from_args(&mut args)? {
+        Sink::Postgres => {
+            let url = env::var("SQL_URL").expect("SQL_URL is not set.");
+            retry(initialize_pool(&url).await?).await?
+        }
+        Sink::Jsonl(dir) => retry(JsonlClient::new(dir)?).await?,
+    };
+    if given_up {
+        std::process::exit(1);
+    }
+    Ok(())
+}
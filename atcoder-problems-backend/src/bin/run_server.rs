@@ -3,6 +3,7 @@ use std::env;
 use atcoder_problems_backend::server::middleware::github_auth::GithubClient;
 use atcoder_problems_backend::server::run_server;
 use atcoder_problems_backend::utils::init_log_config;
+use sql_client::migration::MigrationClient;
 
 #[actix_web::main]
 async fn main() {
@@ -16,6 +17,10 @@ async fn main() {
     let pg_pool = sql_client::initialize_pool(&database_url)
         .await
         .expect("Failed to initialize the connection pool");
+    pg_pool
+        .ensure_schema_up_to_date()
+        .await
+        .expect("Run `cargo run --bin migrate up` before starting the server");
     let github = GithubClient::new(
         &client_id,
         &client_secret,
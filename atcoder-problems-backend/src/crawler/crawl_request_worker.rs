@@ -0,0 +1,242 @@
+use crate::crawler::{AtCoderFetcher, UserSubmissionCrawler};
+use anyhow::Result;
+use chrono::Utc;
+use log::{info, warn};
+use sql_client::crawl_request::CrawlRequestClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Contests which have started within this period are crawled for every request.
+const RECENT_CONTEST_SECOND: i64 = 7 * 24 * 3600;
+/// Requests running for longer than this are regarded as abandoned by a crashed worker.
+const RUNNING_TIMEOUT_SECOND: i64 = 3600;
+const DEFAULT_PAGE_BUDGET: u32 = 100;
+
+/// Processes `crawl_requests` by crawling the submissions of each requested user.
+pub struct CrawlRequestWorker<C, F> {
+    db: C,
+    fetcher: F,
+    page_budget: u32,
+}
+
+impl<C, F> CrawlRequestWorker<C, F>
+where
+    C: CrawlRequestClient + SubmissionClient + OrphanClient + SimpleClient + Clone + Sync,
+    F: AtCoderFetcher + Clone,
+{
+    pub fn new(db: C, fetcher: F) -> Self {
+        Self {
+            db,
+            fetcher,
+            page_budget: DEFAULT_PAGE_BUDGET,
+        }
+    }
+
+    /// Sets the maximum number of pages fetched for a request.
+    pub fn with_page_budget(self, page_budget: u32) -> Self {
+        Self {
+            page_budget,
+            ..self
+        }
+    }
+
+    /// Processes queued requests until the queue gets empty, and returns how many it has processed.
+    pub async fn drain(&self) -> Result<usize> {
+        let requeued = self
+            .db
+            .requeue_stale_crawl_requests(RUNNING_TIMEOUT_SECOND)
+            .await?;
+        if requeued > 0 {
+            warn!("Requeued {} stale crawl requests", requeued);
+        }
+
+        let mut processed = 0;
+        while let Some(request) = self.db.pop_crawl_request().await? {
+            info!(
+                "Processing crawl request {} of {}",
+                request.id, request.atcoder_user_id
+            );
+            let error = self
+                .crawl_user(&request.atcoder_user_id)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            if let Some(error) = error.as_ref() {
+                warn!("Crawl request {} failed: {}", request.id, error);
+            }
+            self.db
+                .finish_crawl_request(request.id, error.as_deref())
+                .await?;
+            processed += 1;
+        }
+        Ok(processed)
+    }
+
+    /// Crawls the submission lists filtered by the user, within the page budget.
+    /// Contests which are recent or still open come first, then the contests in which
+    /// the user has submitted more recently, then the other contests from the newest one.
+    async fn crawl_user(&self, user_id: &str) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let mut latest_submissions = BTreeMap::new();
+        for submission in self
+            .db
+            .get_submissions(SubmissionRequest::UserAll { user_id })
+            .await?
+        {
+            let latest = latest_submissions
+                .entry(submission.contest_id)
+                .or_insert(submission.id);
+            *latest = submission.id.max(*latest);
+        }
+
+        let mut contests = self
+            .db
+            .load_contests()
+            .await?
+            .into_iter()
+            .filter(|c| c.start_epoch_second <= now)
+            .collect::<Vec<_>>();
+        contests.sort_by_key(|c| {
+            let recent = c.start_epoch_second >= now - RECENT_CONTEST_SECOND
+                || c.start_epoch_second + c.duration_second >= now;
+            let latest_submission = latest_submissions.get(&c.id).copied();
+            Reverse((recent, latest_submission, c.start_epoch_second))
+        });
+
+        let contest_ids = contests.iter().map(|c| c.id.as_str()).collect::<Vec<_>>();
+        UserSubmissionCrawler::new(self.db.clone(), self.fetcher.clone(), user_id)
+            .crawl_new_submissions(&contest_ids, self.page_budget)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::utils::MockFetcher;
+    use anyhow::ensure;
+    use async_trait::async_trait;
+    use sql_client::models::{Contest, CrawlRequest, Submission};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct MockDB {
+        queue: Arc<Mutex<Vec<CrawlRequest>>>,
+        finished: Arc<Mutex<Vec<CrawlRequest>>>,
+        crawled_contests: Arc<Mutex<Vec<String>>>,
+        requeued: Arc<Mutex<bool>>,
+    }
+
+    #[async_trait]
+    impl CrawlRequestClient for MockDB {
+        async fn pop_crawl_request(&self) -> Result<Option<CrawlRequest>> {
+            let mut queue = self.queue.lock().unwrap();
+            Ok(if queue.is_empty() {
+                None
+            } else {
+                Some(queue.remove(0))
+            })
+        }
+        async fn requeue_stale_crawl_requests(&self, timeout_second: i64) -> Result<usize> {
+            assert_eq!(timeout_second, RUNNING_TIMEOUT_SECOND);
+            *self.requeued.lock().unwrap() = true;
+            Ok(0)
+        }
+        async fn finish_crawl_request(&self, id: i64, error: Option<&str>) -> Result<()> {
+            self.finished.lock().unwrap().push(CrawlRequest {
+                id,
+                error: error.map(|e| e.to_string()),
+                ..Default::default()
+            });
+            Ok(())
+        }
+    }
+
+    impl OrphanClient for MockDB {}
+
+    #[async_trait]
+    impl SubmissionClient for MockDB {
+        async fn get_submissions<'a>(
+            &self,
+            request: SubmissionRequest<'a>,
+        ) -> Result<Vec<Submission>> {
+            match request {
+                SubmissionRequest::UserAll { user_id } => {
+                    ensure!(user_id != "broken", "Failed to load submissions");
+                    Ok(vec![Submission {
+                        id: 1,
+                        contest_id: "old".to_string(),
+                        ..Default::default()
+                    }])
+                }
+                SubmissionRequest::ByIds { .. } => Ok(vec![]),
+                _ => unimplemented!(),
+            }
+        }
+        async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
+            let mut crawled_contests = self.crawled_contests.lock().unwrap();
+            crawled_contests.extend(submissions.iter().map(|s| s.contest_id.clone()));
+            Ok(submissions.len())
+        }
+    }
+
+    #[async_trait]
+    impl SimpleClient for MockDB {
+        async fn load_contests(&self) -> Result<Vec<Contest>> {
+            let now = Utc::now().timestamp();
+            let contest = |id: &str, start_epoch_second: i64| Contest {
+                id: id.to_string(),
+                start_epoch_second,
+                ..Default::default()
+            };
+            Ok(vec![
+                contest("ancient", 0),
+                contest("old", 100),
+                contest("newer", 200),
+                contest("recent", now - 3600),
+                contest("upcoming", now + 3600),
+            ])
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_crawl_request_worker() {
+        let db = MockDB::default();
+        *db.queue.lock().unwrap() = vec![
+            CrawlRequest {
+                id: 1,
+                atcoder_user_id: "user".to_string(),
+                ..Default::default()
+            },
+            CrawlRequest {
+                id: 2,
+                atcoder_user_id: "broken".to_string(),
+                ..Default::default()
+            },
+        ];
+        let fetcher = MockFetcher(|contest_id, _| {
+            Ok(vec![Submission {
+                contest_id: contest_id.to_string(),
+                user_id: "user".to_string(),
+                ..Default::default()
+            }])
+        });
+        let worker = CrawlRequestWorker::new(db.clone(), fetcher).with_page_budget(3);
+        assert_eq!(worker.drain().await.unwrap(), 2);
+        assert!(*db.requeued.lock().unwrap());
+
+        // The contest in which the user has submitted comes before the newer one.
+        assert_eq!(
+            *db.crawled_contests.lock().unwrap(),
+            vec!["recent", "old", "newer"]
+        );
+        let finished = db.finished.lock().unwrap();
+        assert_eq!((finished[0].id, finished[0].error.as_ref()), (1, None));
+        assert_eq!(finished[1].id, 2);
+        assert!(finished[1].error.is_some());
+    }
+}
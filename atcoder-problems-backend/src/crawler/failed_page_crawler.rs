@@ -0,0 +1,321 @@
+use crate::crawler::{store_submissions, AtCoderFetcher, FetchFailure};
+use anyhow::{bail, Result};
+use log::{error, info};
+use serde::Serialize;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::models::{FailedPage, Submission};
+use sql_client::orphan::OrphanClient;
+use sql_client::submission_client::SubmissionClient;
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+/// Pages which have failed this many times are no longer retried,
+/// and left in the queue for someone to look into.
+const MAX_ATTEMPTS: i32 = 10;
+/// Maximum number of pages the submissions of a failed page are searched over.
+const MAX_SHIFTED_PAGES: u32 = 10;
+
+/// Result of a call of `FailedPageCrawler::crawl`.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FailedPageSummary {
+    pub retried: usize,
+    pub recovered: usize,
+    pub still_failing: usize,
+    /// Pages which are not retried any more.
+    pub given_up: Vec<FailedPage>,
+}
+
+/// Retries the submission pages in the dead-letter queue, which other crawlers have failed to fetch.
+pub struct FailedPageCrawler<C, F> {
+    db: C,
+    fetcher: F,
+    batch_size: usize,
+}
+
+impl<C, F> FailedPageCrawler<C, F>
+where
+    C: SubmissionClient + OrphanClient + FailedPageClient + Sync,
+    F: AtCoderFetcher,
+{
+    pub fn new(db: C, fetcher: F) -> Self {
+        Self {
+            db,
+            fetcher,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of pages retried in a call of `crawl`.
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self { batch_size, ..self }
+    }
+
+    pub async fn crawl(&self) -> Result<FailedPageSummary> {
+        let (pages, given_up): (Vec<_>, Vec<_>) = self
+            .db
+            .load_failed_pages()
+            .await?
+            .into_iter()
+            .partition(|page| page.attempts < MAX_ATTEMPTS);
+        info!("There are {} failed pages to retry.", pages.len());
+
+        let mut summary = FailedPageSummary {
+            given_up,
+            ..Default::default()
+        };
+        for page in pages.into_iter().take(self.batch_size) {
+            info!("Retrying {}-{} ...", page.contest_id, page.page);
+            summary.retried += 1;
+            let page_number = page.page as u32;
+            match self.fetch_anchored(&page).await {
+                Ok(submissions) => {
+                    store_submissions(&self.db, &submissions).await?;
+                    self.db
+                        .delete_failed_page(&page.contest_id, page_number)
+                        .await?;
+                    summary.recovered += 1;
+                }
+                Err(e) => match FetchFailure::classify(&e) {
+                    FetchFailure::NotFound => {
+                        self.db
+                            .delete_failed_page(&page.contest_id, page_number)
+                            .await?;
+                    }
+                    failure => {
+                        self.db
+                            .record_failed_page(
+                                &page.crawler,
+                                &page.contest_id,
+                                page_number,
+                                page.before_id,
+                                &format!("{:#}", e),
+                            )
+                            .await?;
+                        summary.still_failing += 1;
+                        if failure == FetchFailure::Broken {
+                            // The other pages would fail in the same way.
+                            break;
+                        }
+                    }
+                },
+            }
+        }
+
+        for page in summary.given_up.iter() {
+            error!(
+                "[ALERT] Gave up fetching {}-{} after {} attempts: {}",
+                page.contest_id, page.page, page.attempts, page.error
+            );
+        }
+        info!(
+            "Retried {} failed pages: {} recovered, {} still failing, {} given up",
+            summary.retried,
+            summary.recovered,
+            summary.still_failing,
+            summary.given_up.len()
+        );
+        Ok(summary)
+    }
+
+    /// Fetches the submissions of a failed page.
+    /// New submissions push the older ones to later pages, so the pages are walked from
+    /// the failed one until they reach the anchor, and the page after it is fetched too
+    /// unless it starts at the anchor, since the submissions may have been split into two pages.
+    /// Pages without the anchor, e.g. the first ones, are fetched by their numbers.
+    async fn fetch_anchored(&self, page: &FailedPage) -> Result<Vec<Submission>> {
+        let first_page = page.page as u32;
+        let before_id = match page.before_id {
+            Some(before_id) => before_id,
+            None => {
+                let (submissions, _) = self
+                    .fetcher
+                    .fetch_submissions(&page.contest_id, first_page)
+                    .await?;
+                return Ok(submissions);
+            }
+        };
+
+        let mut submissions = Vec::new();
+        let mut reached = false;
+        for page_number in first_page..first_page + MAX_SHIFTED_PAGES {
+            let (fetched, _) = self
+                .fetcher
+                .fetch_submissions(&page.contest_id, page_number)
+                .await?;
+            if fetched.is_empty() {
+                return Ok(submissions);
+            }
+            let newer = fetched.iter().all(|s| s.id >= before_id);
+            let starts_at_anchor = fetched.iter().all(|s| s.id < before_id);
+            submissions.extend(fetched);
+            if newer {
+                continue;
+            }
+            if reached || starts_at_anchor {
+                return Ok(submissions);
+            }
+            reached = true;
+        }
+        if reached {
+            return Ok(submissions);
+        }
+        bail!(
+            "{}-{} has shifted more than {} pages",
+            page.contest_id,
+            page.page,
+            MAX_SHIFTED_PAGES
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::utils::MockFetcher;
+    use async_trait::async_trait;
+    use atcoder_client::AtCoderError;
+    use sql_client::models::Submission;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockDB {
+        pages: Mutex<Vec<FailedPage>>,
+        stored: Mutex<Vec<i64>>,
+    }
+
+    impl OrphanClient for MockDB {}
+
+    #[async_trait]
+    impl SubmissionClient for MockDB {
+        async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
+            let mut stored = self.stored.lock().unwrap();
+            stored.extend(submissions.iter().map(|s| s.id));
+            Ok(submissions.len())
+        }
+    }
+
+    #[async_trait]
+    impl FailedPageClient for MockDB {
+        async fn record_failed_page(
+            &self,
+            crawler: &str,
+            contest_id: &str,
+            page: u32,
+            _: Option<i64>,
+            _: &str,
+        ) -> Result<()> {
+            let mut pages = self.pages.lock().unwrap();
+            let failed = pages
+                .iter_mut()
+                .find(|p| p.contest_id == contest_id && p.page == page as i32)
+                .unwrap();
+            assert_eq!(crawler, failed.crawler);
+            failed.attempts += 1;
+            Ok(())
+        }
+        async fn load_failed_pages(&self) -> Result<Vec<FailedPage>> {
+            Ok(self.pages.lock().unwrap().clone())
+        }
+        async fn delete_failed_page(&self, contest_id: &str, page: u32) -> Result<()> {
+            let mut pages = self.pages.lock().unwrap();
+            pages.retain(|p| !(p.contest_id == contest_id && p.page == page as i32));
+            Ok(())
+        }
+    }
+
+    fn failed_page(contest_id: &str, page: i32, attempts: i32) -> FailedPage {
+        FailedPage {
+            contest_id: contest_id.to_string(),
+            page,
+            crawler: "recent".to_string(),
+            attempts,
+            ..Default::default()
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_failed_page_crawler() {
+        let db = MockDB::default();
+        *db.pages.lock().unwrap() = vec![
+            failed_page("recovered", 2, 1),
+            failed_page("failing", 3, 1),
+            failed_page("deleted", 1, 1),
+            failed_page("hopeless", 4, MAX_ATTEMPTS),
+        ];
+        let fetcher = MockFetcher(|contest_id, _| match contest_id {
+            "recovered" => Ok(vec![Submission {
+                id: 1,
+                ..Default::default()
+            }]),
+            "failing" => Err(AtCoderError::Http(reqwest::StatusCode::BAD_GATEWAY).into()),
+            "deleted" => Err(AtCoderError::NotFound("deleted".to_string()).into()),
+            _ => unreachable!("{} should not be retried", contest_id),
+        });
+        let crawler = FailedPageCrawler::new(db, fetcher);
+        let summary = crawler.crawl().await.unwrap();
+
+        assert_eq!(summary.retried, 3);
+        assert_eq!(summary.recovered, 1);
+        assert_eq!(summary.still_failing, 1);
+        assert_eq!(
+            summary.given_up,
+            vec![failed_page("hopeless", 4, MAX_ATTEMPTS)]
+        );
+        assert_eq!(*crawler.db.stored.lock().unwrap(), vec![1]);
+
+        let pages = crawler.db.pages.lock().unwrap();
+        assert_eq!(
+            *pages,
+            vec![
+                failed_page("failing", 3, 2),
+                failed_page("hopeless", 4, MAX_ATTEMPTS)
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_failed_page_crawler_batch_size() {
+        let db = MockDB::default();
+        *db.pages.lock().unwrap() = (1..=3)
+            .map(|page| failed_page("contest", page, 1))
+            .collect();
+        let fetcher = MockFetcher(|_, _| Ok(vec![]));
+        let crawler = FailedPageCrawler::new(db, fetcher).with_batch_size(2);
+        let summary = crawler.crawl().await.unwrap();
+        assert_eq!((summary.retried, summary.recovered), (2, 2));
+        assert_eq!(crawler.db.pages.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_failed_page_crawler_follows_shifted_submissions() {
+        // Page 2 held submissions 8..=6 when it failed, and they have shifted to pages 3 and 4.
+        let db = MockDB::default();
+        *db.pages.lock().unwrap() = vec![FailedPage {
+            before_id: Some(9),
+            ..failed_page("contest", 2, 1)
+        }];
+        let fetcher = MockFetcher(|_, page| {
+            let ids: &[i64] = match page {
+                2 => &[12, 11, 10],
+                3 => &[9, 8],
+                4 => &[7, 6, 5],
+                5 => unreachable!("Page 5 is beyond the failed submissions"),
+                _ => &[],
+            };
+            Ok(ids
+                .iter()
+                .map(|&id| Submission {
+                    id,
+                    ..Default::default()
+                })
+                .collect())
+        });
+        let crawler = FailedPageCrawler::new(db, fetcher);
+        let summary = crawler.crawl().await.unwrap();
+        assert_eq!(summary.recovered, 1);
+        assert_eq!(
+            *crawler.db.stored.lock().unwrap(),
+            vec![12, 11, 10, 9, 8, 7, 6, 5]
+        );
+        assert!(crawler.db.pages.lock().unwrap().is_empty());
+    }
+}
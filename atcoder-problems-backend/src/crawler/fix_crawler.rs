@@ -1,8 +1,14 @@
-use crate::crawler::AtCoderFetcher;
+use crate::crawler::{dead_letter, store_submissions, AtCoderFetcher};
 use anyhow::Result;
-use log::info;
+use atcoder_client::JudgeStatus;
+use log::{info, warn};
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::orphan::OrphanClient;
 use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+const CRAWLER_NAME: &str = "fix";
 
 pub struct FixCrawler<C, F> {
     db: C,
@@ -12,7 +18,7 @@ pub struct FixCrawler<C, F> {
 
 impl<C, F> FixCrawler<C, F>
 where
-    C: SubmissionClient,
+    C: SubmissionClient + OrphanClient + CrawlStateClient + FailedPageClient + Sync,
     F: AtCoderFetcher,
 {
     pub fn new(db: C, fetcher: F, current_time_second: i64) -> Self {
@@ -27,35 +33,90 @@ where
             "Pulling invalid submissions after {} ...",
             self.current_time_second
         );
+        let final_results = JudgeStatus::final_statuses();
         let submissions = self
             .db
             .get_submissions(SubmissionRequest::InvalidResult {
                 from_second: self.current_time_second,
+                final_results: &final_results,
             })
             .await?;
 
         info!("There are {} invalid submissions.", submissions.len());
-        let contests = submissions.into_iter().map(|s| (s.contest_id, s.id)).fold(
-            BTreeMap::new(),
-            |mut map, (contest_id, id)| {
-                let cur_id = map.entry(contest_id).or_insert(id);
-                if *cur_id > id {
-                    *cur_id = id;
-                }
-                map
-            },
-        );
+        let mut contests = BTreeMap::<String, BTreeSet<i64>>::new();
+        for submission in submissions {
+            match JudgeStatus::parse(&submission.result) {
+                Some(status) if status.is_final() => continue,
+                Some(_) => {}
+                None => warn!(
+                    "Submission {} has an unknown status {:?}",
+                    submission.id, submission.result
+                ),
+            }
+            contests
+                .entry(submission.contest_id)
+                .or_default()
+                .insert(submission.id);
+        }
+
+        let last_pages = self
+            .db
+            .load_crawl_states(CRAWLER_NAME)
+            .await?
+            .into_iter()
+            .map(|state| (state.contest_id, (state.last_page as u32, state.before_id)))
+            .collect::<BTreeMap<_, _>>();
 
-        for (contest_id, minimum_id) in contests {
-            for page in 1.. {
+        'contests: for (contest_id, mut pending_ids) in contests {
+            let (last_page, mut before_id) = match last_pages.get(&contest_id) {
+                Some(&(last_page, before_id)) => {
+                    info!("Resuming {} from page {}", contest_id, last_page + 1);
+                    (Some(last_page), before_id)
+                }
+                None => (None, None),
+            };
+            for page in last_page.unwrap_or(0) + 1.. {
                 info!("Fetching from {}-{}", contest_id, page);
                 let (submissions, max_page) =
-                    self.fetcher.fetch_submissions(&contest_id, page).await;
-                self.db.update_submissions(&submissions).await?;
+                    match self.fetcher.fetch_submissions(&contest_id, page).await {
+                        Ok(result) => result,
+                        Err(e) => {
+                            // The progress is kept, so the next call resumes from this page.
+                            dead_letter(&self.db, CRAWLER_NAME, &contest_id, page, before_id, &e)
+                                .await?;
+                            continue 'contests;
+                        }
+                    };
+                store_submissions(&self.db, &submissions).await?;
+                before_id = submissions.iter().map(|s| s.id).min();
+                for submission in submissions.iter() {
+                    let status = JudgeStatus::parse(&submission.result);
+                    if status.map_or(false, |s| s.is_final()) {
+                        pending_ids.remove(&submission.id);
+                    }
+                }
+
+                let minimum_id = match pending_ids.first() {
+                    Some(&id) => id,
+                    None => break,
+                };
                 let all_old = submissions.iter().all(|s| s.id <= minimum_id);
                 if all_old || max_page == page {
                     break;
                 }
+                self.db
+                    .save_crawl_state(CRAWLER_NAME, &contest_id, page, max_page, before_id)
+                    .await?;
+            }
+            self.db
+                .delete_crawl_state(CRAWLER_NAME, &contest_id)
+                .await?;
+            if !pending_ids.is_empty() {
+                info!(
+                    "{} submissions in {} are still being judged.",
+                    pending_ids.len(),
+                    contest_id
+                );
             }
         }
 
@@ -68,12 +129,19 @@ mod tests {
     use super::*;
     use crate::crawler::utils::MockFetcher;
     use async_trait::async_trait;
-    use sql_client::models::Submission;
-    use std::ops::Range;
+    use sql_client::models::{CrawlState, Submission};
+    use std::sync::Mutex;
 
     const CURRENT_TIME: i64 = 100;
 
-    struct MockDB;
+    #[derive(Default)]
+    struct MockDB {
+        last_page: Option<i32>,
+        failed_pages: Mutex<Vec<(String, u32)>>,
+        deleted: Mutex<Vec<String>>,
+    }
+
+    impl OrphanClient for MockDB {}
 
     #[async_trait]
     impl SubmissionClient for MockDB {
@@ -82,22 +150,35 @@ mod tests {
             request: SubmissionRequest<'a>,
         ) -> Result<Vec<Submission>> {
             match request {
-                SubmissionRequest::InvalidResult { from_second } => {
+                SubmissionRequest::InvalidResult {
+                    from_second,
+                    final_results,
+                } => {
                     assert_eq!(from_second, CURRENT_TIME);
+                    assert!(final_results.contains(&"AC".to_string()));
                     Ok(vec![
                         Submission {
                             contest_id: "contest1".to_string(),
                             id: 100,
+                            result: "WJ".to_string(),
                             ..Default::default()
                         },
                         Submission {
                             contest_id: "contest1".to_string(),
                             id: 200,
+                            result: "3/20 TLE".to_string(),
                             ..Default::default()
                         },
                         Submission {
                             contest_id: "contest1".to_string(),
                             id: 50,
+                            result: "WR".to_string(),
+                            ..Default::default()
+                        },
+                        Submission {
+                            contest_id: "contest2".to_string(),
+                            id: 10,
+                            result: "AC".to_string(),
                             ..Default::default()
                         },
                     ])
@@ -108,34 +189,150 @@ mod tests {
         async fn update_submissions(&self, _: &[Submission]) -> Result<usize> {
             Ok(0)
         }
+    }
 
-        async fn get_user_submission_count(&self, _: &str, _: Range<i64>) -> Result<usize> {
-            unimplemented!()
+    #[async_trait]
+    impl CrawlStateClient for MockDB {
+        async fn load_crawl_states(&self, _: &str) -> Result<Vec<CrawlState>> {
+            let states = self.last_page.map(|last_page| CrawlState {
+                crawler: CRAWLER_NAME.to_string(),
+                contest_id: "contest1".to_string(),
+                last_page,
+                ..Default::default()
+            });
+            Ok(states.into_iter().collect())
+        }
+        async fn save_crawl_state(
+            &self,
+            _: &str,
+            _: &str,
+            _: u32,
+            _: u32,
+            _: Option<i64>,
+        ) -> Result<()> {
+            Ok(())
+        }
+        async fn delete_crawl_state(&self, _: &str, contest_id: &str) -> Result<()> {
+            self.deleted.lock().unwrap().push(contest_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl FailedPageClient for MockDB {
+        async fn record_failed_page(
+            &self,
+            crawler: &str,
+            contest_id: &str,
+            page: u32,
+            _: Option<i64>,
+            _: &str,
+        ) -> Result<()> {
+            assert_eq!(crawler, CRAWLER_NAME);
+            let mut failed_pages = self.failed_pages.lock().unwrap();
+            failed_pages.push((contest_id.to_string(), page));
+            Ok(())
         }
     }
 
     #[actix_web::test]
     async fn test_fix_crawler_found() {
         let fetcher = MockFetcher(|_, _| {
-            vec![Submission {
+            Ok(vec![Submission {
                 id: 50,
                 ..Default::default()
-            }]
+            }])
         });
-        let crawler = FixCrawler::new(MockDB, fetcher, CURRENT_TIME);
+        let crawler = FixCrawler::new(MockDB::default(), fetcher, CURRENT_TIME);
         assert!(crawler.crawl().await.is_ok());
     }
 
     #[actix_web::test]
     async fn test_fix_crawler_all_old() {
         let fetcher = MockFetcher(|_, _| {
-            vec![Submission {
+            Ok(vec![Submission {
                 id: 30,
                 ..Default::default()
-            }]
+            }])
         });
 
-        let crawler = FixCrawler::new(MockDB, fetcher, CURRENT_TIME);
+        let crawler = FixCrawler::new(MockDB::default(), fetcher, CURRENT_TIME);
         assert!(crawler.crawl().await.is_ok());
     }
+
+    #[actix_web::test]
+    async fn test_fix_crawler_stops_when_all_judged() {
+        let fetcher = MockFetcher(|contest_id, page| {
+            assert_eq!(contest_id, "contest1");
+            let submission = |id: i64, result: &str| Submission {
+                id,
+                result: result.to_string(),
+                ..Default::default()
+            };
+            match page {
+                1 => Ok(vec![submission(300, "WJ"), submission(200, "AC")]),
+                2 => Ok(vec![submission(100, "WA"), submission(50, "RE")]),
+                _ => panic!("All the pending submissions have been judged by page 2"),
+            }
+        });
+        let crawler = FixCrawler::new(MockDB::default(), fetcher, CURRENT_TIME);
+        assert!(crawler.crawl().await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_fix_crawler_keeps_pending() {
+        let fetcher = MockFetcher(|_, page| {
+            let (id, result) = match page {
+                1 => (200, "5/20 WJ"),
+                2 => (100, "AC"),
+                3 => (50, "WR"),
+                _ => panic!("Submissions older than the pending ones are not needed"),
+            };
+            Ok(vec![Submission {
+                id,
+                result: result.to_string(),
+                ..Default::default()
+            }])
+        });
+        let crawler = FixCrawler::new(MockDB::default(), fetcher, CURRENT_TIME);
+        assert!(crawler.crawl().await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_fix_crawler_resumes() {
+        let fetcher = MockFetcher(|_, page| {
+            assert!(page > 2, "Completed pages must not be crawled again");
+            Ok(vec![Submission {
+                id: 50,
+                result: "AC".to_string(),
+                ..Default::default()
+            }])
+        });
+        let db = MockDB {
+            last_page: Some(2),
+            ..Default::default()
+        };
+        let crawler = FixCrawler::new(db, fetcher, CURRENT_TIME);
+        assert!(crawler.crawl().await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_fix_crawler_failed_page() {
+        let fetcher = MockFetcher(|_, page| match page {
+            1 => Ok(vec![Submission {
+                id: 300,
+                result: "AC".to_string(),
+                ..Default::default()
+            }]),
+            _ => Err(anyhow::anyhow!("connection reset")),
+        });
+        let crawler = FixCrawler::new(MockDB::default(), fetcher, CURRENT_TIME);
+        assert!(crawler.crawl().await.is_ok());
+        assert_eq!(
+            *crawler.db.failed_pages.lock().unwrap(),
+            vec![("contest1".to_string(), 2)]
+        );
+        // The progress of contest1 is kept to retry the page next time.
+        assert!(crawler.db.deleted.lock().unwrap().is_empty());
+    }
 }
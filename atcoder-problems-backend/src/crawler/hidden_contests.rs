@@ -0,0 +1,205 @@
+use crate::crawler::{convert_contest, AtCoderFetcher};
+use anyhow::{Context, Result};
+use atcoder_client::AtCoderContest;
+use serde_json::Value;
+use sql_client::hidden_contest::HiddenContestClient;
+use sql_client::models::{Contest, HiddenContest};
+use std::path::PathBuf;
+
+pub const DEFAULT_HIDDEN_CONTESTS_URL: &str =
+    "https://kenkoooo.com/atcoder/static_data/backend/hidden_contests.json";
+
+/// Where to load the contests which don't appear in the contest list of AtCoder.
+/// URLs and files are JSON arrays in the same format as `AtCoderContest`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HiddenContestSource {
+    Url(String),
+    File(PathBuf),
+    /// The `hidden_contests` table, which is managed through the admin API.
+    Database,
+}
+
+impl Default for HiddenContestSource {
+    fn default() -> Self {
+        HiddenContestSource::Url(DEFAULT_HIDDEN_CONTESTS_URL.to_string())
+    }
+}
+
+impl HiddenContestSource {
+    /// Reads `HIDDEN_CONTESTS_SOURCE`, which is a URL, `file:<path>` or `database`.
+    pub fn from_env() -> Self {
+        std::env::var("HIDDEN_CONTESTS_SOURCE")
+            .map(|source| Self::parse(&source))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(source: &str) -> Self {
+        let source = source.trim();
+        if source == "database" {
+            HiddenContestSource::Database
+        } else if let Some(path) = source.strip_prefix("file:") {
+            HiddenContestSource::File(PathBuf::from(path))
+        } else {
+            HiddenContestSource::Url(source.to_string())
+        }
+    }
+
+    /// Loads the entries without validating them. URLs are fetched by `fetcher`.
+    pub async fn load<C, F>(&self, db: &C, fetcher: &F) -> Result<Vec<Value>>
+    where
+        C: HiddenContestClient,
+        F: AtCoderFetcher,
+    {
+        match self {
+            HiddenContestSource::Url(url) => fetcher.fetch_hidden_contests(url).await,
+            HiddenContestSource::File(path) => {
+                let json = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Failed to read {:?}", path))?;
+                let entries = serde_json::from_str(&json)
+                    .with_context(|| format!("Failed to parse hidden contests in {:?}", path))?;
+                Ok(entries)
+            }
+            HiddenContestSource::Database => db
+                .load_hidden_contests()
+                .await?
+                .into_iter()
+                .map(|contest| Ok(serde_json::to_value(contest)?))
+                .collect(),
+        }
+    }
+}
+
+/// Validates hidden contest entries one by one,
+/// and returns the valid contests along with the reasons why the others are malformed.
+pub fn validate_hidden_contests(entries: Vec<Value>) -> (Vec<Contest>, Vec<String>) {
+    let mut contests = Vec::new();
+    let mut errors = Vec::new();
+    for (i, entry) in entries.into_iter().enumerate() {
+        let contest = serde_json::from_value::<AtCoderContest>(entry)
+            .map_err(|e| e.to_string())
+            .and_then(|contest| validate_hidden_contest(&contest).map(|_| contest));
+        match contest {
+            Ok(contest) => contests.push(convert_contest(contest)),
+            Err(reason) => errors.push(format!("entry {}: {}", i, reason)),
+        }
+    }
+    (contests, errors)
+}
+
+pub fn validate_hidden_contest_row(contest: &HiddenContest) -> Result<(), String> {
+    let value = serde_json::to_value(contest).map_err(|e| e.to_string())?;
+    let contest = serde_json::from_value::<AtCoderContest>(value).map_err(|e| e.to_string())?;
+    validate_hidden_contest(&contest)
+}
+
+fn validate_hidden_contest(contest: &AtCoderContest) -> Result<(), String> {
+    let is_valid_id = !contest.id.is_empty()
+        && contest
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !is_valid_id {
+        return Err(format!("invalid contest id {:?}", contest.id));
+    }
+    if contest.title.trim().is_empty() {
+        return Err(format!("{} has an empty title", contest.id));
+    }
+    if contest.duration_second == 0 {
+        return Err(format!("{} has no duration", contest.id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use atcoder_client::transport::ReplayTransport;
+    use atcoder_client::{AtCoderClient, RateLimitConfig};
+    use serde_json::json;
+
+    struct MockDB;
+
+    #[async_trait]
+    impl HiddenContestClient for MockDB {}
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            HiddenContestSource::parse("database"),
+            HiddenContestSource::Database
+        );
+        assert_eq!(
+            HiddenContestSource::parse("file:/tmp/hidden_contests.json"),
+            HiddenContestSource::File(PathBuf::from("/tmp/hidden_contests.json"))
+        );
+        assert_eq!(
+            HiddenContestSource::parse(DEFAULT_HIDDEN_CONTESTS_URL),
+            HiddenContestSource::default()
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_load() {
+        let dir = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/atcoder-client/test_resources/replay"
+        );
+        let client =
+            AtCoderClient::with_transport(ReplayTransport::new(dir), RateLimitConfig::default());
+        let entries = HiddenContestSource::default()
+            .load(&MockDB, &client)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["id"], "hidden1");
+
+        let path =
+            std::env::temp_dir().join(format!("hidden-contests-{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&entries).unwrap()).unwrap();
+        let loaded = HiddenContestSource::File(path.clone())
+            .load(&MockDB, &client)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, entries);
+    }
+
+    #[test]
+    fn test_validate_hidden_contests() {
+        let entries = vec![
+            json!({
+                "id": "hidden1",
+                "start_epoch_second": 1600000000,
+                "duration_second": 7200,
+                "title": "Hidden Contest 1",
+                "rate_change": "-"
+            }),
+            json!({"id": "broken"}),
+            json!({
+                "id": "hidden 2",
+                "start_epoch_second": 1600000000,
+                "duration_second": 7200,
+                "title": "Hidden Contest 2",
+                "rate_change": "-"
+            }),
+            json!({
+                "id": "hidden3",
+                "start_epoch_second": 1600000000,
+                "duration_second": 7200,
+                "title": "Hidden Contest 3",
+                "rate_change": "All"
+            }),
+        ];
+        let (contests, errors) = validate_hidden_contests(entries);
+        assert_eq!(
+            contests.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            vec!["hidden1", "hidden3"]
+        );
+        assert_eq!(contests[1].category, "Sponsored");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("entry 1: "));
+        assert!(errors[1].starts_with("entry 2: invalid contest id"));
+    }
+}
@@ -1,37 +1,125 @@
+mod crawl_request_worker;
+mod failed_page_crawler;
 mod fix_crawler;
+mod hidden_contests;
+mod parallel_contest_crawler;
 mod problem_crawler;
+mod rating_history_crawler;
 mod recent_crawler;
+mod standings_crawler;
+mod user_submission_crawler;
 pub(crate) mod utils;
 mod virtual_contest_crawler;
 mod whole_contest_crawler;
 
+pub use crawl_request_worker::CrawlRequestWorker;
+pub use failed_page_crawler::{FailedPageCrawler, FailedPageSummary};
 pub use fix_crawler::FixCrawler;
+pub use hidden_contests::{
+    validate_hidden_contest_row, validate_hidden_contests, HiddenContestSource,
+    DEFAULT_HIDDEN_CONTESTS_URL,
+};
+pub use parallel_contest_crawler::ParallelContestCrawler;
 pub use problem_crawler::ProblemCrawler;
+pub use rating_history_crawler::RatingHistoryCrawler;
 pub use recent_crawler::RecentCrawler;
+pub use standings_crawler::StandingsCrawler;
+pub use user_submission_crawler::UserSubmissionCrawler;
 pub use virtual_contest_crawler::VirtualContestCrawler;
-pub use whole_contest_crawler::WholeContestCrawler;
+pub use whole_contest_crawler::{Reconciliation, WholeContestCrawler};
 
-use actix_web::rt::time;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use atcoder_client::{AtCoderClient, AtCoderProblem, AtCoderSubmission, ContestTypeSpecifier};
+use atcoder_client::{
+    AtCoderClient, AtCoderContest, AtCoderError, AtCoderProblem, ContestTypeSpecifier,
+    SubmissionListFilter,
+};
 use log::info;
-use sql_client::models::{Contest, ContestProblem, Problem, Submission};
+use serde_json::Value;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::models::{
+    Contest, ContestProblem, ContestStanding, ContestStandingTaskResult, Problem, Submission,
+    SubmissionDetail, SubmissionTestcase, UserRatingHistory,
+};
+use sql_client::orphan::OrphanClient;
+use sql_client::submission_client::SubmissionClient;
 use std::time::Duration;
 
+/// What crawlers fetch from AtCoder.
+/// Fetchers fail on what they don't support, so that a test fetcher implements only what it uses.
 #[async_trait]
-pub trait AtCoderFetcher {
-    async fn fetch_submissions(&self, contest_id: &str, page: u32) -> (Vec<Submission>, u32);
-    async fn fetch_contests(&self, spf: ContestTypeSpecifier) -> Result<Vec<Contest>>;
-    async fn fetch_problems(&self, contest_id: &str)
-        -> Result<(Vec<Problem>, Vec<ContestProblem>)>;
+pub trait AtCoderFetcher: Sync {
+    async fn fetch_submissions(
+        &self,
+        contest_id: &str,
+        page: u32,
+    ) -> Result<(Vec<Submission>, u32)> {
+        self.fetch_filtered_submissions(contest_id, page, &SubmissionListFilter::default())
+            .await
+    }
+    async fn fetch_filtered_submissions(
+        &self,
+        _contest_id: &str,
+        _page: u32,
+        _filter: &SubmissionListFilter,
+    ) -> Result<(Vec<Submission>, u32)> {
+        Err(anyhow!("fetch_filtered_submissions is not supported"))
+    }
+    async fn fetch_contests(&self, _spf: ContestTypeSpecifier) -> Result<Vec<Contest>> {
+        Err(anyhow!("fetch_contests is not supported"))
+    }
+    async fn fetch_contest(&self, _contest_id: &str) -> Result<Contest> {
+        Err(anyhow!("fetch_contest is not supported"))
+    }
+    async fn fetch_problems(
+        &self,
+        _contest_id: &str,
+    ) -> Result<(Vec<Problem>, Vec<ContestProblem>)> {
+        Err(anyhow!("fetch_problems is not supported"))
+    }
+    async fn fetch_submission_detail(
+        &self,
+        _contest_id: &str,
+        _submission_id: i64,
+    ) -> Result<(SubmissionDetail, Vec<SubmissionTestcase>)> {
+        Err(anyhow!("fetch_submission_detail is not supported"))
+    }
+    async fn fetch_standings(
+        &self,
+        _contest_id: &str,
+    ) -> Result<(Vec<ContestStanding>, Vec<ContestStandingTaskResult>)> {
+        Err(anyhow!("fetch_standings is not supported"))
+    }
+    async fn fetch_user_history(&self, _user_id: &str) -> Result<Vec<UserRatingHistory>> {
+        Err(anyhow!("fetch_user_history is not supported"))
+    }
+    async fn fetch_hidden_contests(&self, _url: &str) -> Result<Vec<Value>> {
+        Err(anyhow!("fetch_hidden_contests is not supported"))
+    }
 }
 
 #[async_trait]
 impl AtCoderFetcher for AtCoderClient {
-    async fn fetch_submissions(&self, contest_id: &str, page: u32) -> (Vec<Submission>, u32) {
-        let (submissions, max_page) = retry_fetch_submissions(self, 9, contest_id, page).await;
-        let submissions = submissions
+    async fn fetch_filtered_submissions(
+        &self,
+        contest_id: &str,
+        page: u32,
+        filter: &SubmissionListFilter,
+    ) -> Result<(Vec<Submission>, u32)> {
+        let response = match self
+            .fetch_atcoder_submission_list(contest_id, Some(page), filter)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                let e: anyhow::Error = e.into();
+                let target = format!("submissions of {} page {}", contest_id, page);
+                handle_fetch_error(&e, &target).await;
+                return Err(e);
+            }
+        };
+        let submissions = response
+            .submissions
             .into_iter()
             .map(|s| Submission {
                 id: s.id as i64,
@@ -42,11 +130,12 @@ impl AtCoderFetcher for AtCoderClient {
                 language: s.language,
                 point: s.point,
                 length: s.length as i32,
-                result: s.result,
+                result: s.result.to_string(),
                 execution_time: s.execution_time.map(|t| t as i32),
+                memory: s.memory.map(|m| m as i32),
             })
             .collect();
-        (submissions, max_page)
+        Ok((submissions, response.max_page))
     }
 
     async fn fetch_contests(&self, spf: ContestTypeSpecifier) -> Result<Vec<Contest>> {
@@ -57,25 +146,28 @@ impl AtCoderFetcher for AtCoderClient {
             ContestTypeSpecifier::Permanent => {
                 info!("Fetching permanent contests");
             }
-            ContestTypeSpecifier::Hidden => {
-                info!("Fetching hidden contests");
+            ContestTypeSpecifier::Upcoming => {
+                info!("Fetching upcoming contests");
+            }
+            ContestTypeSpecifier::Top => {
+                info!("Fetching permanent and upcoming contests");
             }
         };
 
         let contests = self.fetch_atcoder_contests(spf).await?;
         let contests = contests
             .into_iter()
-            .map(|c| Contest {
-                id: c.id,
-                start_epoch_second: c.start_epoch_second as i64,
-                duration_second: c.duration_second as i64,
-                title: c.title,
-                rate_change: c.rate_change,
-            })
+            .map(convert_contest)
             .collect::<Vec<_>>();
         Ok(contests)
     }
 
+    async fn fetch_contest(&self, contest_id: &str) -> Result<Contest> {
+        info!("Fetching contest {} ...", contest_id);
+        let contest = self.fetch_contest_detail(contest_id).await?;
+        Ok(convert_contest(contest))
+    }
+
     async fn fetch_problems(
         &self,
         contest_id: &str,
@@ -96,32 +188,194 @@ impl AtCoderFetcher for AtCoderClient {
             .collect::<Vec<_>>();
         Ok((problems, contest_problem))
     }
+
+    async fn fetch_submission_detail(
+        &self,
+        contest_id: &str,
+        submission_id: i64,
+    ) -> Result<(SubmissionDetail, Vec<SubmissionTestcase>)> {
+        info!(
+            "Fetching submission {} of {} ...",
+            submission_id, contest_id
+        );
+        let detail = self
+            .fetch_submission_detail(contest_id, submission_id as u64)
+            .await?;
+        let testcases = detail
+            .testcases
+            .into_iter()
+            .map(|t| SubmissionTestcase {
+                submission_id,
+                case_name: t.name,
+                result: t.result,
+                execution_time: t.execution_time.map(|t| t as i32),
+                memory: t.memory.map(|m| m as i32),
+            })
+            .collect::<Vec<_>>();
+        let detail = SubmissionDetail {
+            submission_id,
+            compile_error: detail.compile_error,
+        };
+        Ok((detail, testcases))
+    }
+
+    async fn fetch_standings(
+        &self,
+        contest_id: &str,
+    ) -> Result<(Vec<ContestStanding>, Vec<ContestStandingTaskResult>)> {
+        info!("Fetching standings of {} ...", contest_id);
+        let rows = self.fetch_contest_standings(contest_id).await?;
+        let task_results = rows
+            .iter()
+            .flat_map(|row| {
+                row.task_results.iter().map(|t| ContestStandingTaskResult {
+                    contest_id: contest_id.to_string(),
+                    user_id: row.user_id.clone(),
+                    problem_id: t.problem_id.clone(),
+                    score: t.score,
+                    penalty: t.penalty as i32,
+                    elapsed_second: t.elapsed_second as i64,
+                })
+            })
+            .collect::<Vec<_>>();
+        let standings = rows
+            .into_iter()
+            .map(|row| ContestStanding {
+                contest_id: contest_id.to_string(),
+                user_id: row.user_id,
+                rank: row.rank as i32,
+                is_rated: row.is_rated,
+                old_rating: row.old_rating as i32,
+                new_rating: row.new_rating as i32,
+                score: row.score,
+                penalty: row.penalty as i32,
+                elapsed_second: row.elapsed_second as i64,
+            })
+            .collect::<Vec<_>>();
+        Ok((standings, task_results))
+    }
+
+    async fn fetch_user_history(&self, user_id: &str) -> Result<Vec<UserRatingHistory>> {
+        info!("Fetching history of {} ...", user_id);
+        let history = self
+            .fetch_user_history(user_id)
+            .await?
+            .into_iter()
+            .map(|h| UserRatingHistory {
+                user_id: user_id.to_string(),
+                contest_id: h.contest_id,
+                is_rated: h.is_rated,
+                rank: h.rank as i32,
+                performance: h.performance as i32,
+                old_rating: h.old_rating as i32,
+                new_rating: h.new_rating as i32,
+                end_epoch_second: h.end_epoch_second as i64,
+            })
+            .collect::<Vec<_>>();
+        Ok(history)
+    }
+
+    async fn fetch_hidden_contests(&self, url: &str) -> Result<Vec<Value>> {
+        info!("Fetching hidden contests from {} ...", url);
+        let entries = self.fetch_hidden_contest_list(url).await?;
+        Ok(entries)
+    }
 }
 
-async fn retry_fetch_submissions(
-    client: &AtCoderClient,
-    retry_count: usize,
-    contest_id: &str,
-    page: u32,
-) -> (Vec<AtCoderSubmission>, u32) {
-    let mut sleep_second = 1;
-    for _ in 0..retry_count {
-        match client
-            .fetch_atcoder_submission_list(contest_id, Some(page))
-            .await
-        {
-            Ok(response) => {
-                return (response.submissions, response.max_page);
+/// Used when AtCoder rate limits us without telling how long to wait.
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How a crawler should react to an error of fetching something from AtCoder.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum FetchFailure {
+    /// The page doesn't exist, e.g. the contest has been deleted. It's no use retrying.
+    NotFound,
+    /// AtCoder asks us to slow down.
+    BackOff(Duration),
+    /// The scraper is broken by a change of the pages, or the login has been rejected.
+    /// Someone needs to fix it by hand.
+    Broken,
+    /// Other errors such as connection errors, which may succeed next time.
+    Transient,
+}
+
+impl FetchFailure {
+    pub(crate) fn classify(e: &anyhow::Error) -> Self {
+        match e.downcast_ref::<AtCoderError>() {
+            Some(AtCoderError::NotFound(_)) => FetchFailure::NotFound,
+            Some(AtCoderError::RateLimited { retry_after }) => {
+                FetchFailure::BackOff(retry_after.unwrap_or(RATE_LIMIT_BACKOFF))
             }
-            Err(e) => {
-                log::error!("Error when fetching {} {}: {:?} ", contest_id, page, e);
-                log::info!("Sleeping {}s before retry ...", sleep_second);
-                time::sleep(Duration::from_secs(sleep_second)).await;
-                sleep_second *= 2;
+            Some(AtCoderError::CircuitOpen(wait)) => FetchFailure::BackOff(*wait),
+            Some(AtCoderError::Parse { .. }) | Some(AtCoderError::LoginFailed(_)) => {
+                FetchFailure::Broken
             }
+            _ => FetchFailure::Transient,
+        }
+    }
+}
+
+/// Logs an error of fetching `target` according to its kind,
+/// and waits before returning if AtCoder asks us to back off.
+pub(crate) async fn handle_fetch_error(e: &anyhow::Error, target: &str) -> FetchFailure {
+    let failure = FetchFailure::classify(e);
+    match &failure {
+        FetchFailure::NotFound => log::warn!("Skipping {}: {}", target, e),
+        FetchFailure::BackOff(wait) => {
+            log::warn!("Backing off {:?} after fetching {}: {}", wait, target, e);
+            actix_web::rt::time::sleep(*wait).await;
         }
+        FetchFailure::Broken => log::error!("[ALERT] Failed to fetch {}: {:?}", target, e),
+        FetchFailure::Transient => log::error!("Failed to fetch {}: {:?}", target, e),
+    }
+    failure
+}
+
+/// Puts a submission page which has failed to be fetched into the dead-letter queue,
+/// so that `FailedPageCrawler` retries it later. Pages which don't exist are not queued.
+/// `before_id` is the smallest submission id of the previous page if it has just been fetched.
+pub(crate) async fn dead_letter<C: FailedPageClient + Sync>(
+    db: &C,
+    crawler: &str,
+    contest_id: &str,
+    page: u32,
+    before_id: Option<i64>,
+    e: &anyhow::Error,
+) -> Result<FetchFailure> {
+    let failure = FetchFailure::classify(e);
+    if failure != FetchFailure::NotFound {
+        db.record_failed_page(crawler, contest_id, page, before_id, &format!("{:#}", e))
+            .await?;
+    }
+    Ok(failure)
+}
+
+/// Stores a batch of submissions and records the orphaned problems among them.
+/// Failing to record the orphans doesn't fail the batch, which has been stored anyway,
+/// since `batch_update` looks into all the submissions for them later.
+pub(crate) async fn store_submissions<C: SubmissionClient + OrphanClient>(
+    db: &C,
+    submissions: &[Submission],
+) -> Result<()> {
+    db.update_submissions(submissions).await?;
+    if let Err(e) = db.record_submission_orphans(submissions).await {
+        log::error!("Failed to record orphaned problems: {:?}", e);
+    }
+    Ok(())
+}
+
+fn convert_contest(c: AtCoderContest) -> Contest {
+    let rated_range = c.rated_range();
+    Contest {
+        category: c.category().as_str().to_string(),
+        rated_min: rated_range.map(|r| r.min.unwrap_or(0) as i32),
+        rated_max: rated_range.and_then(|r| r.max).map(|r| r as i32),
+        id: c.id,
+        start_epoch_second: c.start_epoch_second as i64,
+        duration_second: c.duration_second as i64,
+        title: c.title,
+        rate_change: c.rate_change,
     }
-    (Vec::new(), 0)
 }
 
 fn convert_problem(p: AtCoderProblem) -> Problem {
@@ -131,12 +385,41 @@ fn convert_problem(p: AtCoderProblem) -> Problem {
         problem_index: p.position.clone(),
         title: p.position + ". " + p.title.as_str(),
         name: p.title,
+        time_limit_ms: p.time_limit_ms.map(|t| t as i32),
+        memory_limit_kb: p.memory_limit_kb.map(|m| m as i32),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use atcoder_client::transport::ReplayTransport;
+    use atcoder_client::RateLimitConfig;
+
+    #[test]
+    fn test_convert_contest() {
+        let c = convert_contest(AtCoderContest {
+            id: "abc199".to_owned(),
+            start_epoch_second: 1619265600,
+            duration_second: 6000,
+            title: "AtCoder Beginner Contest 199".to_owned(),
+            rate_change: " ~ 1999".to_owned(),
+        });
+        assert_eq!(c.category, "ABC");
+        assert_eq!(c.rated_min, Some(0));
+        assert_eq!(c.rated_max, Some(1999));
+        assert_eq!(c.rate_change, " ~ 1999");
+
+        let c = convert_contest(AtCoderContest {
+            id: "practice".to_owned(),
+            start_epoch_second: 0,
+            duration_second: 0,
+            title: "Practice".to_owned(),
+            rate_change: "-".to_owned(),
+        });
+        assert_eq!(c.category, "Other");
+        assert_eq!((c.rated_min, c.rated_max), (None, None));
+    }
 
     #[test]
     fn test_convert_problem() {
@@ -145,10 +428,68 @@ mod tests {
             contest_id: "contest_id".to_owned(),
             title: "title".to_owned(),
             position: "A".to_owned(),
+            time_limit_ms: Some(2000),
+            memory_limit_kb: Some(1048576),
         };
         let p = convert_problem(p);
         assert_eq!(p.id, "id");
         assert_eq!(p.contest_id, "contest_id");
         assert_eq!(p.title, "A. title");
+        assert_eq!(p.time_limit_ms, Some(2000));
+        assert_eq!(p.memory_limit_kb, Some(1048576));
+    }
+
+    #[test]
+    fn test_classify_fetch_failure() {
+        let classify = |e: AtCoderError| FetchFailure::classify(&e.into());
+        assert_eq!(
+            classify(AtCoderError::NotFound("url".to_string())),
+            FetchFailure::NotFound
+        );
+        assert_eq!(
+            classify(AtCoderError::RateLimited {
+                retry_after: Some(Duration::from_secs(3))
+            }),
+            FetchFailure::BackOff(Duration::from_secs(3))
+        );
+        assert_eq!(
+            classify(AtCoderError::RateLimited { retry_after: None }),
+            FetchFailure::BackOff(RATE_LIMIT_BACKOFF)
+        );
+        assert_eq!(
+            classify(AtCoderError::Parse {
+                page: "tasks",
+                selector: "tbody",
+                row: None
+            }),
+            FetchFailure::Broken
+        );
+        assert_eq!(
+            classify(AtCoderError::Http(reqwest::StatusCode::FORBIDDEN)),
+            FetchFailure::Transient
+        );
+        assert_eq!(
+            FetchFailure::classify(&anyhow::anyhow!("database error")),
+            FetchFailure::Transient
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_fetch_from_replay() {
+        let dir = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/atcoder-client/test_resources/replay"
+        );
+        let client =
+            AtCoderClient::with_transport(ReplayTransport::new(dir), RateLimitConfig::default());
+
+        let (problems, contest_problems) = client.fetch_problems("abc107").await.unwrap();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(problems[0].title, "A. Train");
+        assert_eq!(contest_problems[3].problem_id, "arc101_b");
+
+        let (submissions, max_page) = client.fetch_submissions("abc107", 1).await.unwrap();
+        assert_eq!(submissions.len(), 20);
+        assert!(max_page > 1);
     }
 }
@@ -0,0 +1,150 @@
+use crate::crawler::{AtCoderFetcher, WholeContestCrawler};
+use anyhow::Error;
+use futures_util::future::join_all;
+use log::{error, info};
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::orphan::OrphanClient;
+use sql_client::submission_client::SubmissionClient;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Crawls contests by `WholeContestCrawler`s running concurrently, one for each fetcher.
+/// Every worker takes the next contest from the shared queue as soon as it gets idle,
+/// so the contests are spread across the workers even if they differ in size.
+pub struct ParallelContestCrawler<C, F> {
+    db: C,
+    fetchers: Vec<F>,
+}
+
+impl<C, F> ParallelContestCrawler<C, F>
+where
+    C: SubmissionClient + OrphanClient + CrawlStateClient + FailedPageClient + Clone + Sync,
+    F: AtCoderFetcher + Clone,
+{
+    /// The number of `fetchers` is the concurrency of the crawler.
+    pub fn new(db: C, fetchers: Vec<F>) -> Self {
+        Self { db, fetchers }
+    }
+
+    /// Returns the contests which have failed to be crawled with the errors.
+    pub async fn crawl(&self, contest_ids: &[&str]) -> Vec<(String, Error)> {
+        info!(
+            "Crawling {} contests with {} workers",
+            contest_ids.len(),
+            self.fetchers.len()
+        );
+        let queue = Mutex::new(contest_ids.iter().copied().collect::<VecDeque<_>>());
+        let workers = self
+            .fetchers
+            .iter()
+            .enumerate()
+            .map(|(worker, fetcher)| self.run_worker(worker, fetcher, &queue));
+        join_all(workers).await.into_iter().flatten().collect()
+    }
+
+    async fn run_worker(
+        &self,
+        worker: usize,
+        fetcher: &F,
+        queue: &Mutex<VecDeque<&str>>,
+    ) -> Vec<(String, Error)> {
+        let mut failures = vec![];
+        loop {
+            let next = queue.lock().unwrap().pop_front();
+            let contest_id = match next {
+                Some(contest_id) => contest_id,
+                None => break,
+            };
+            info!("Worker {} is crawling {}", worker, contest_id);
+            let crawler = WholeContestCrawler::new(self.db.clone(), fetcher.clone(), contest_id);
+            if let Err(e) = crawler.crawl().await {
+                error!("Worker {} failed to crawl {}: {:?}", worker, contest_id, e);
+                failures.push((contest_id.to_string(), e));
+            }
+        }
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::utils::MockFetcher;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use sql_client::models::{CrawlState, Submission};
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct MockDB {
+        finished: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl OrphanClient for MockDB {}
+
+    #[async_trait]
+    impl SubmissionClient for MockDB {
+        async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
+            Ok(submissions.len())
+        }
+    }
+
+    #[async_trait]
+    impl CrawlStateClient for MockDB {
+        async fn load_crawl_states(&self, _: &str) -> Result<Vec<CrawlState>> {
+            Ok(vec![])
+        }
+        async fn save_crawl_state(
+            &self,
+            _: &str,
+            _: &str,
+            _: u32,
+            _: u32,
+            _: Option<i64>,
+        ) -> Result<()> {
+            Ok(())
+        }
+        async fn delete_crawl_state(&self, _: &str, contest_id: &str) -> Result<()> {
+            self.finished.lock().unwrap().push(contest_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl FailedPageClient for MockDB {
+        async fn record_failed_page(
+            &self,
+            _: &str,
+            _: &str,
+            _: u32,
+            _: Option<i64>,
+            _: &str,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_parallel_contest_crawler() {
+        let fetcher = MockFetcher(|contest_id, page| match (contest_id, page) {
+            ("broken", _) => Err(anyhow::anyhow!("connection reset")),
+            (_, 1) => Ok(vec![Submission::default()]),
+            _ => Ok(vec![]),
+        });
+        let db = MockDB::default();
+        let crawler = ParallelContestCrawler::new(db.clone(), vec![fetcher.clone(), fetcher]);
+        let failures = crawler
+            .crawl(&["contest1", "broken", "contest2", "contest3"])
+            .await;
+
+        let failed = failures
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["broken"]);
+        let mut finished = db.finished.lock().unwrap().clone();
+        finished.sort();
+        assert_eq!(finished, vec!["contest1", "contest2", "contest3"]);
+    }
+}
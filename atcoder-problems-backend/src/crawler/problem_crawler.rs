@@ -1,57 +1,83 @@
-use crate::crawler::AtCoderFetcher;
-use actix_web::rt::time;
+use crate::crawler::{
+    handle_fetch_error, validate_hidden_contests, AtCoderFetcher, HiddenContestSource,
+};
 use anyhow::Result;
 use atcoder_client::ContestTypeSpecifier;
+use chrono::Utc;
 use sql_client::contest_problem::ContestProblemClient;
-use sql_client::models::{Contest, ContestProblem, Problem};
+use sql_client::hidden_contest::HiddenContestClient;
+use sql_client::models::{
+    Contest, ContestProblem, ContestRevision, OrphanedProblem, Problem, ProblemRevision,
+};
+use sql_client::orphan::OrphanClient;
+use sql_client::revision::RevisionClient;
 use sql_client::simple_client::SimpleClient;
-use std::collections::BTreeSet;
-use std::time::Duration;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Problems of contests which have started within this period are crawled again,
+/// since they are often revised just after the contests.
+const REVISED_CONTEST_SECOND: i64 = 7 * 24 * 3600;
+
+/// Orphans which have been crawled this many times without being resolved are given up,
+/// e.g. the ones whose contests have been deleted.
+const MAX_ORPHAN_ATTEMPTS: i32 = 5;
 
 pub struct ProblemCrawler<C, F> {
     db: C,
     fetcher: F,
+    hidden_contests: HiddenContestSource,
 }
 
 impl<C, F> ProblemCrawler<C, F>
 where
     F: AtCoderFetcher,
-    C: SimpleClient + ContestProblemClient,
+    C: SimpleClient + ContestProblemClient + HiddenContestClient + OrphanClient + RevisionClient,
 {
     pub fn new(db: C, fetcher: F) -> Self {
-        Self { db, fetcher }
+        Self {
+            db,
+            fetcher,
+            hidden_contests: HiddenContestSource::default(),
+        }
+    }
+
+    pub fn with_hidden_contests(self, hidden_contests: HiddenContestSource) -> Self {
+        Self {
+            hidden_contests,
+            ..self
+        }
     }
     pub async fn crawl(&self) -> Result<()> {
         log::info!("Starting...");
-        let mut contests = Vec::new();
-
-        match self
-            .fetcher
-            .fetch_contests(ContestTypeSpecifier::Permanent)
-            .await
-        {
-            Ok(c) => {
-                contests.extend(c);
+        let mut hidden = Vec::new();
+        match self.hidden_contests.load(&self.db, &self.fetcher).await {
+            Ok(entries) => {
+                let (c, errors) = validate_hidden_contests(entries);
+                for error in errors {
+                    log::error!("[ALERT] Malformed hidden contest: {}", error);
+                }
+                hidden.extend(c);
             }
             Err(e) => {
-                log::error!("{:?}", e);
+                log::error!(
+                    "Failed to load hidden contests from {:?}: {:?}",
+                    self.hidden_contests,
+                    e
+                );
             }
         }
-        time::sleep(Duration::from_millis(500)).await;
 
-        match self
-            .fetcher
-            .fetch_contests(ContestTypeSpecifier::Hidden)
-            .await
-        {
+        let mut listed = Vec::new();
+        match self.fetcher.fetch_contests(ContestTypeSpecifier::Top).await {
             Ok(c) => {
-                contests.extend(c);
+                listed.extend(c);
             }
             Err(e) => {
-                log::error!("{:?}", e);
+                handle_fetch_error(&e, "permanent and upcoming contests").await;
             }
         }
 
+        let mut archived = Vec::new();
         for page in 1.. {
             match self
                 .fetcher
@@ -62,48 +88,167 @@ where
                     if c.is_empty() {
                         break;
                     }
-                    contests.extend(c);
+                    archived.extend(c);
                 }
                 Err(e) => {
-                    log::error!("{:?}", e);
+                    handle_fetch_error(&e, &format!("contests page {}", page)).await;
                     break;
                 }
             }
-            time::sleep(Duration::from_millis(500)).await;
         }
 
+        let hidden = exclude_listed_contests(hidden, &[&listed, &archived]);
+        let contests = dedup_contests(vec![hidden, listed, archived]);
         log::info!("There are {} contests.", contests.len());
+        let now = Utc::now().timestamp();
+        let revisions = diff_contests(&self.db.load_contests().await?, &contests, now);
+        if !revisions.is_empty() {
+            log::info!("{} fields of contests have been revised.", revisions.len());
+            self.db.insert_contest_revisions(&revisions).await?;
+        }
         self.db.insert_contests(&contests).await?;
 
         let contests = self.db.load_contests().await?;
         let problems = self.db.load_problems().await?;
         let contest_problem = self.db.load_contest_problem().await?;
 
-        let no_problem_contests =
-            extract_no_problem_contests(&contests, &problems, &contest_problem);
+        let mut target_contests =
+            extract_no_problem_contests(&contests, &problems, &contest_problem, now);
+        target_contests.extend(
+            contests
+                .iter()
+                .filter(|c| (now - REVISED_CONTEST_SECOND..=now).contains(&c.start_epoch_second)),
+        );
 
-        for contest in no_problem_contests {
-            log::info!("Crawling problems of {}...", contest.id);
-            match self.fetcher.fetch_problems(&contest.id).await {
-                Ok((problems, contest_problem)) => {
-                    self.db.insert_problems(&problems).await?;
+        // Contests owning the problems which submissions reference but are not known yet.
+        let (orphans, unresolvable): (Vec<_>, Vec<_>) = self
+            .db
+            .load_orphans()
+            .await?
+            .into_iter()
+            .partition(|o| o.attempts < MAX_ORPHAN_ATTEMPTS);
+        if !unresolvable.is_empty() {
+            log::warn!("{} orphaned problems are unresolvable.", unresolvable.len());
+        }
+        let mut missing_contests = Vec::new();
+        for contest_id in extract_missing_contests(&orphans) {
+            match self.fetcher.fetch_contest(contest_id).await {
+                Ok(contest) => missing_contests.push(contest),
+                Err(e) => {
+                    handle_fetch_error(&e, &format!("contest {}", contest_id)).await;
+                }
+            }
+        }
+        if !missing_contests.is_empty() {
+            log::info!(
+                "{} missing contests have been found.",
+                missing_contests.len()
+            );
+            self.db.insert_contests(&missing_contests).await?;
+        }
+        let contest_ids = merge_orphaned_contests(&target_contests, &orphans);
+
+        for contest_id in contest_ids {
+            log::info!("Crawling problems of {}...", contest_id);
+            match self.fetcher.fetch_problems(contest_id).await {
+                Ok((crawled, contest_problem)) => {
+                    let (revised, revisions) = diff_problems(&problems, &crawled, now);
+                    self.db.insert_problems(&crawled).await?;
+                    if !revisions.is_empty() {
+                        log::info!(
+                            "{} problems of {} have been revised.",
+                            revised.len(),
+                            contest_id
+                        );
+                        self.db.revise_problems(&revised, &revisions).await?;
+                    }
                     self.db.insert_contest_problem(&contest_problem).await?;
                 }
                 Err(e) => {
-                    log::error!("{:?}", e);
+                    handle_fetch_error(&e, &format!("problems of {}", contest_id)).await;
                 }
             }
-            time::sleep(Duration::from_millis(500)).await;
         }
 
+        let resolved = self.db.resolve_orphans().await?;
+        let outstanding = orphans.len().saturating_sub(resolved);
+        log::info!(
+            "Resolved {} orphaned problems, {} are outstanding.",
+            resolved,
+            outstanding
+        );
+
         Ok(())
     }
+
+    /// Crawls the problems again whose limits are unknown to fill them.
+    pub async fn backfill_limits(&self) -> Result<()> {
+        let problems = self.db.load_problems().await?;
+        let contest_ids = extract_no_limit_contests(&problems);
+        log::info!(
+            "Backfilling the limits of problems in {} contests...",
+            contest_ids.len()
+        );
+        for contest_id in contest_ids {
+            match self.fetcher.fetch_problems(contest_id).await {
+                Ok((crawled, _)) => {
+                    self.db.insert_problems(&crawled).await?;
+                }
+                Err(e) => {
+                    handle_fetch_error(&e, &format!("problems of {}", contest_id)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Contests of the problems whose limits are unknown, e.g. which were stored before the limits were scraped.
+fn extract_no_limit_contests(problems: &[Problem]) -> Vec<&str> {
+    problems
+        .iter()
+        .filter(|p| p.time_limit_ms.is_none() || p.memory_limit_kb.is_none())
+        .map(|p| p.contest_id.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
 }
 
+/// Hidden contests which AtCoder lists now, e.g. in the archive, are no longer hidden.
+fn exclude_listed_contests(hidden: Vec<Contest>, listed: &[&[Contest]]) -> Vec<Contest> {
+    let listed_ids = listed
+        .iter()
+        .flat_map(|contests| contests.iter().map(|c| c.id.as_str()))
+        .collect::<BTreeSet<_>>();
+    hidden
+        .into_iter()
+        .filter(|c| {
+            let is_listed = listed_ids.contains(c.id.as_str());
+            if is_listed {
+                log::warn!("Hidden contest {} is listed by AtCoder, ignoring it.", c.id);
+            }
+            !is_listed
+        })
+        .collect()
+}
+
+/// Merges the contests from the sources in the ascending order of authority.
+/// A contest listed in several sources is taken from the last one,
+/// since the contests can't be upserted with duplicated ids at once.
+fn dedup_contests(sources: Vec<Vec<Contest>>) -> Vec<Contest> {
+    let mut contests = BTreeMap::new();
+    for contest in sources.into_iter().flatten() {
+        contests.insert(contest.id.clone(), contest);
+    }
+    contests.into_values().collect()
+}
+
+/// Problems of contests which haven't started are not public yet, so they are excluded.
 fn extract_no_problem_contests<'a>(
     contests: &'a [Contest],
     problems: &'a [Problem],
     contest_problems: &'a [ContestProblem],
+    now: i64,
 ) -> Vec<&'a Contest> {
     let problem_set = problems.iter().map(|p| &p.id).collect::<BTreeSet<_>>();
     let contest_with_problems = contest_problems
@@ -113,6 +258,372 @@ fn extract_no_problem_contests<'a>(
         .collect::<BTreeSet<_>>();
     contests
         .iter()
+        .filter(|c| c.start_epoch_second <= now)
         .filter(|c| !contest_with_problems.contains(&c.id))
         .collect::<Vec<_>>()
 }
+
+/// Ids of the contests of the orphans which are not known, without duplicates.
+fn extract_missing_contests(orphans: &[OrphanedProblem]) -> Vec<&str> {
+    orphans
+        .iter()
+        .filter(|o| o.contest_missing)
+        .map(|o| o.contest_id.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Contest ids to crawl problems of, without duplicates and keeping the order.
+fn merge_orphaned_contests<'a>(
+    no_problem_contests: &[&'a Contest],
+    orphans: &'a [OrphanedProblem],
+) -> Vec<&'a str> {
+    let mut contest_ids = Vec::new();
+    let ids = no_problem_contests
+        .iter()
+        .map(|c| c.id.as_str())
+        .chain(orphans.iter().map(|o| o.contest_id.as_str()));
+    for id in ids {
+        if !contest_ids.contains(&id) {
+            contest_ids.push(id);
+        }
+    }
+    contest_ids
+}
+
+/// Values of the fields of a contest whose changes are recorded.
+fn contest_fields(contest: &Contest) -> [(&'static str, Option<String>); 4] {
+    [
+        (
+            "start_epoch_second",
+            Some(contest.start_epoch_second.to_string()),
+        ),
+        ("duration_second", Some(contest.duration_second.to_string())),
+        ("title", Some(contest.title.clone())),
+        ("rate_change", Some(contest.rate_change.clone())),
+    ]
+}
+
+/// Values of the fields of a problem whose changes are recorded.
+fn problem_fields(problem: &Problem) -> [(&'static str, Option<String>); 4] {
+    [
+        ("name", Some(problem.name.clone())),
+        ("title", Some(problem.title.clone())),
+        (
+            "time_limit_ms",
+            problem.time_limit_ms.map(|t| t.to_string()),
+        ),
+        (
+            "memory_limit_kb",
+            problem.memory_limit_kb.map(|m| m.to_string()),
+        ),
+    ]
+}
+
+/// Returns the changed fields with the old and the new values.
+/// A field which has failed to be crawled, i.e. `None` in `new`, is not regarded as changed.
+fn diff_fields<const N: usize>(
+    old: [(&'static str, Option<String>); N],
+    new: [(&'static str, Option<String>); N],
+) -> Vec<(&'static str, Option<String>, Option<String>)> {
+    old.into_iter()
+        .zip(new)
+        .filter(|((_, old), (_, new))| new.is_some() && old != new)
+        .map(|((field, old), (_, new))| (field, old, new))
+        .collect()
+}
+
+fn diff_contests(stored: &[Contest], crawled: &[Contest], now: i64) -> Vec<ContestRevision> {
+    let stored = stored
+        .iter()
+        .map(|c| (c.id.as_str(), c))
+        .collect::<BTreeMap<_, _>>();
+    let mut revisions = vec![];
+    for new in crawled {
+        let old = match stored.get(new.id.as_str()) {
+            Some(old) => old,
+            None => continue,
+        };
+        for (field, old_value, new_value) in diff_fields(contest_fields(old), contest_fields(new)) {
+            revisions.push(ContestRevision {
+                contest_id: new.id.clone(),
+                field: field.to_string(),
+                old_value,
+                new_value,
+                detected_epoch_second: now,
+                ..Default::default()
+            });
+        }
+    }
+    revisions
+}
+
+/// Returns the revised problems with their revisions.
+/// Problems shared with other contests are compared only when they are crawled
+/// from their own contests, since their titles differ between the contests.
+fn diff_problems(
+    stored: &[Problem],
+    crawled: &[Problem],
+    now: i64,
+) -> (Vec<Problem>, Vec<ProblemRevision>) {
+    let stored = stored
+        .iter()
+        .map(|p| (p.id.as_str(), p))
+        .collect::<BTreeMap<_, _>>();
+    let mut revised = vec![];
+    let mut revisions = vec![];
+    for new in crawled {
+        let old = match stored.get(new.id.as_str()) {
+            Some(old) if old.contest_id == new.contest_id => old,
+            _ => continue,
+        };
+        let changes = diff_fields(problem_fields(old), problem_fields(new));
+        if changes.is_empty() {
+            continue;
+        }
+        revised.push(new.clone());
+        for (field, old_value, new_value) in changes {
+            revisions.push(ProblemRevision {
+                problem_id: new.id.clone(),
+                field: field.to_string(),
+                old_value,
+                new_value,
+                detected_epoch_second: now,
+                ..Default::default()
+            });
+        }
+    }
+    (revised, revisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_no_problem_contests() {
+        let contests = [
+            Contest {
+                id: "crawled".to_string(),
+                ..Default::default()
+            },
+            Contest {
+                id: "not_crawled".to_string(),
+                ..Default::default()
+            },
+            Contest {
+                id: "upcoming".to_string(),
+                start_epoch_second: 200,
+                ..Default::default()
+            },
+        ];
+        let problems = [Problem {
+            id: "crawled_a".to_string(),
+            contest_id: "crawled".to_string(),
+            problem_index: "A".to_string(),
+            name: "A".to_string(),
+            title: "A. A".to_string(),
+            time_limit_ms: None,
+            memory_limit_kb: None,
+        }];
+        let contest_problems = [ContestProblem {
+            contest_id: "crawled".to_string(),
+            problem_id: "crawled_a".to_string(),
+            problem_index: "A".to_string(),
+        }];
+        let contests = extract_no_problem_contests(&contests, &problems, &contest_problems, 100);
+        assert_eq!(
+            contests.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            vec!["not_crawled"]
+        );
+    }
+
+    #[test]
+    fn test_dedup_contests() {
+        let contest = |id: &str, title: &str| Contest {
+            id: id.to_string(),
+            title: title.to_string(),
+            ..Default::default()
+        };
+        let hidden = vec![contest("abc001", "Hidden"), contest("hidden", "Hidden")];
+        let listed = vec![contest("abc002", "Upcoming")];
+        let archived = vec![contest("abc001", "Archived"), contest("abc002", "Archived")];
+        let hidden = exclude_listed_contests(hidden, &[&listed, &archived]);
+        let contests = dedup_contests(vec![hidden, listed, archived]);
+        assert_eq!(
+            contests
+                .iter()
+                .map(|c| (c.id.as_str(), c.title.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("abc001", "Archived"),
+                ("abc002", "Archived"),
+                ("hidden", "Hidden")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exclude_listed_contests() {
+        let contest = |id: &str| Contest {
+            id: id.to_string(),
+            ..Default::default()
+        };
+        let hidden = vec![contest("abc001"), contest("hidden"), contest("abc002")];
+        let listed = [contest("abc002")];
+        let archived = [contest("abc001")];
+        let hidden = exclude_listed_contests(hidden, &[&listed, &archived]);
+        assert_eq!(
+            hidden.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            vec!["hidden"]
+        );
+    }
+
+    #[test]
+    fn test_extract_no_limit_contests() {
+        let problem = |id: &str, contest_id: &str, time_limit_ms: Option<i32>| Problem {
+            id: id.to_string(),
+            contest_id: contest_id.to_string(),
+            problem_index: "A".to_string(),
+            name: "A".to_string(),
+            title: "A. A".to_string(),
+            time_limit_ms,
+            memory_limit_kb: Some(1024),
+        };
+        let problems = [
+            problem("abc001_a", "abc001", Some(2000)),
+            problem("abc002_a", "abc002", None),
+            problem("abc002_b", "abc002", None),
+            problem("abc003_a", "abc003", None),
+        ];
+        assert_eq!(
+            extract_no_limit_contests(&problems),
+            vec!["abc002", "abc003"]
+        );
+    }
+
+    #[test]
+    fn test_merge_orphaned_contests() {
+        let contest = |id: &str| Contest {
+            id: id.to_string(),
+            ..Default::default()
+        };
+        let orphan = |contest_id: &str, problem_id: &str| OrphanedProblem {
+            contest_id: contest_id.to_string(),
+            problem_id: problem_id.to_string(),
+            ..Default::default()
+        };
+        let contests = [contest("no_problem"), contest("orphaned")];
+        let orphans = [
+            orphan("orphaned", "orphaned_b"),
+            orphan("unlisted", "unlisted_a"),
+            orphan("unlisted", "unlisted_b"),
+        ];
+        let contest_ids = merge_orphaned_contests(&contests.iter().collect::<Vec<_>>(), &orphans);
+        assert_eq!(contest_ids, vec!["no_problem", "orphaned", "unlisted"]);
+    }
+
+    #[test]
+    fn test_extract_missing_contests() {
+        let orphan = |contest_id: &str, problem_id: &str, contest_missing: bool| OrphanedProblem {
+            contest_id: contest_id.to_string(),
+            problem_id: problem_id.to_string(),
+            contest_missing,
+            ..Default::default()
+        };
+        let orphans = [
+            orphan("known", "known_a", false),
+            orphan("unknown", "unknown_a", true),
+            orphan("unknown", "unknown_b", true),
+        ];
+        assert_eq!(extract_missing_contests(&orphans), vec!["unknown"]);
+    }
+
+    #[test]
+    fn test_diff_contests() {
+        let contest = |id: &str, duration_second: i64, rate_change: &str| Contest {
+            id: id.to_string(),
+            duration_second,
+            rate_change: rate_change.to_string(),
+            ..Default::default()
+        };
+        let stored = [
+            contest("abc001", 6000, " ~ 1999"),
+            contest("abc002", 6000, "-"),
+        ];
+        let crawled = [
+            contest("abc001", 7200, "-"),
+            contest("abc002", 6000, "-"),
+            contest("abc003", 6000, "-"),
+        ];
+        let revisions = diff_contests(&stored, &crawled, 100);
+        let changes = revisions
+            .iter()
+            .map(|r| {
+                (
+                    r.contest_id.as_str(),
+                    r.field.as_str(),
+                    r.old_value.as_deref(),
+                    r.new_value.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                ("abc001", "duration_second", Some("6000"), Some("7200")),
+                ("abc001", "rate_change", Some(" ~ 1999"), Some("-")),
+            ]
+        );
+        assert!(revisions.iter().all(|r| r.detected_epoch_second == 100));
+    }
+
+    #[test]
+    fn test_diff_problems() {
+        let problem =
+            |id: &str, contest_id: &str, title: &str, time_limit_ms: Option<i32>| Problem {
+                id: id.to_string(),
+                contest_id: contest_id.to_string(),
+                problem_index: "A".to_string(),
+                name: "Name".to_string(),
+                title: title.to_string(),
+                time_limit_ms,
+                memory_limit_kb: None,
+            };
+        let stored = [
+            problem("abc001_a", "abc001", "A. Title", Some(2000)),
+            problem("abc001_b", "abc001", "B. Title", None),
+            problem("shared", "abc001", "C. Shared", Some(2000)),
+        ];
+        let crawled = [
+            problem("abc001_a", "abc001", "A. Retitled", None),
+            problem("abc001_b", "abc001", "B. Title", Some(2000)),
+            problem("shared", "abc002", "A. Shared", Some(2000)),
+            problem("abc001_d", "abc001", "D. New", Some(2000)),
+        ];
+        let (revised, revisions) = diff_problems(&stored, &crawled, 100);
+        assert_eq!(
+            revised.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(),
+            vec!["abc001_a", "abc001_b"]
+        );
+        let changes = revisions
+            .iter()
+            .map(|r| {
+                (
+                    r.problem_id.as_str(),
+                    r.field.as_str(),
+                    r.old_value.as_deref(),
+                    r.new_value.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                ("abc001_a", "title", Some("A. Title"), Some("A. Retitled")),
+                ("abc001_b", "time_limit_ms", None, Some("2000")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::crawler::{handle_fetch_error, AtCoderFetcher};
+use crate::utils::EXCLUDED_USERS;
+use anyhow::Result;
+use log::info;
+use sql_client::rating_history::RatingHistoryClient;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use std::collections::BTreeSet;
+
+pub struct RatingHistoryCrawler<C, F> {
+    db: C,
+    fetcher: F,
+}
+
+impl<C, F> RatingHistoryCrawler<C, F>
+where
+    C: SubmissionClient + RatingHistoryClient,
+    F: AtCoderFetcher,
+{
+    pub fn new(db: C, fetcher: F) -> Self {
+        Self { db, fetcher }
+    }
+
+    /// Refreshes the contest history of the users who got accepted recently,
+    /// i.e. the same users `delta_update` aggregates.
+    pub async fn crawl(&self) -> Result<()> {
+        info!("Started");
+        let request = SubmissionRequest::RecentAccepted { count: 200 };
+        let user_ids = self
+            .db
+            .get_submissions(request)
+            .await?
+            .into_iter()
+            .filter(|submission| !EXCLUDED_USERS.contains(&submission.user_id.as_str()))
+            .map(|s| s.user_id)
+            .collect::<BTreeSet<_>>();
+
+        info!("There are {} users to crawl.", user_ids.len());
+        for user_id in user_ids {
+            if let Err(e) = self.crawl_user(&user_id).await {
+                let target = format!("history of {}", user_id);
+                handle_fetch_error(&e, &target).await;
+            }
+        }
+
+        info!("Finished");
+        Ok(())
+    }
+
+    pub async fn crawl_user(&self, user_id: &str) -> Result<()> {
+        let history = self.fetcher.fetch_user_history(user_id).await?;
+        self.db
+            .update_user_rating_history(user_id, &history)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use sql_client::models::{Submission, UserRatingHistory};
+
+    struct MockFetcher;
+
+    #[async_trait]
+    impl AtCoderFetcher for MockFetcher {
+        async fn fetch_user_history(&self, user_id: &str) -> Result<Vec<UserRatingHistory>> {
+            assert_eq!(user_id, "user");
+            Ok(vec![UserRatingHistory {
+                user_id: user_id.to_string(),
+                ..Default::default()
+            }])
+        }
+    }
+
+    struct MockDB;
+
+    #[async_trait]
+    impl SubmissionClient for MockDB {
+        async fn get_submissions<'a>(
+            &self,
+            request: SubmissionRequest<'a>,
+        ) -> Result<Vec<Submission>> {
+            match request {
+                SubmissionRequest::RecentAccepted { count } => {
+                    assert_eq!(count, 200);
+                    Ok(vec![
+                        Submission {
+                            user_id: "user".to_string(),
+                            ..Default::default()
+                        },
+                        Submission {
+                            user_id: "user".to_string(),
+                            ..Default::default()
+                        },
+                        Submission {
+                            user_id: EXCLUDED_USERS[0].to_string(),
+                            ..Default::default()
+                        },
+                    ])
+                }
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RatingHistoryClient for MockDB {
+        async fn update_user_rating_history(
+            &self,
+            user_id: &str,
+            history: &[UserRatingHistory],
+        ) -> Result<()> {
+            assert_eq!(user_id, "user");
+            assert_eq!(history.len(), 1);
+            Ok(())
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_rating_history_crawler() {
+        let crawler = RatingHistoryCrawler::new(MockDB, MockFetcher);
+        assert!(crawler.crawl().await.is_ok());
+    }
+}
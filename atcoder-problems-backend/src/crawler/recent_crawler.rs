@@ -1,134 +1,638 @@
-use crate::crawler::AtCoderFetcher;
-use actix_web::rt::time;
+use crate::crawler::{dead_letter, store_submissions, AtCoderFetcher};
 use anyhow::Result;
+use chrono::Utc;
 use log::info;
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::models::{Contest, CrawlState};
+use sql_client::orphan::OrphanClient;
 use sql_client::simple_client::SimpleClient;
-use sql_client::submission_client::SubmissionClient;
-use std::time::Duration;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use std::collections::BTreeMap;
+
+const CRAWLER_NAME: &str = "recent";
+const DEFAULT_PAGE_BUDGET: u32 = 100;
+
+/// Number of the latest stored submissions used to measure how active each contest is.
+const ACTIVITY_SAMPLE_SIZE: i64 = 1000;
+/// Bonus for a contest which has just ended, which halves every day after its end.
+const RECENCY_BONUS: f64 = 100.0;
+/// Contests longer than this, e.g. permanent ones, get no bonus for being recent.
+const LONG_CONTEST_SECOND: i64 = 7 * 24 * 3600;
+const DAY_SECOND: f64 = 24.0 * 3600.0;
+/// Priority a contest gains for each hour since it was last crawled, up to a week.
+const STALENESS_PER_HOUR: f64 = 10.0;
+const MAX_STALENESS_SECOND: i64 = 7 * 24 * 3600;
+/// Share of the page budget reserved for the contests with recent submissions,
+/// so that stale contests never use up the budget while new submissions are pending.
+const ACTIVE_BUDGET_PERCENT: u32 = 50;
 
 pub struct RecentCrawler<C, F> {
     db: C,
     fetcher: F,
+    page_budget: u32,
 }
 
 impl<C, F> RecentCrawler<C, F>
 where
-    C: SubmissionClient + SimpleClient + Sync,
+    C: SubmissionClient + OrphanClient + SimpleClient + CrawlStateClient + FailedPageClient + Sync,
     F: AtCoderFetcher,
 {
     pub fn new(db: C, fetcher: F) -> Self {
-        Self { db, fetcher }
+        Self {
+            db,
+            fetcher,
+            page_budget: DEFAULT_PAGE_BUDGET,
+        }
+    }
+
+    /// Sets the maximum number of pages fetched in a call of `crawl`.
+    pub fn with_page_budget(self, page_budget: u32) -> Self {
+        Self {
+            page_budget,
+            ..self
+        }
     }
 
+    /// Sets the page budget from `RECENT_PAGE_BUDGET` if it's set.
+    pub fn with_page_budget_from_env(self) -> Self {
+        match std::env::var("RECENT_PAGE_BUDGET")
+            .ok()
+            .and_then(|budget| budget.parse().ok())
+        {
+            Some(page_budget) => self.with_page_budget(page_budget),
+            None => self,
+        }
+    }
+
+    /// Crawls contests in the order of `crawl_priority` until the page budget runs out.
+    /// A part of the budget is used for the contests with recent submissions first.
     pub async fn crawl(&self) -> Result<()> {
         info!("Started");
+        let now = Utc::now().timestamp();
         let contests = self.db.load_contests().await?;
-        for contest in contests {
-            for page in 1.. {
-                info!("Crawling {}-{} ...", contest.id, page);
-                let (submissions, max_page) =
-                    self.fetcher.fetch_submissions(&contest.id, page).await;
-                if submissions.is_empty() {
-                    info!("There is no submission on {}-{}", contest.id, page);
-                    break;
-                }
 
-                let min_id = submissions.iter().map(|s| s.id).min().unwrap();
-                let exists = self.db.count_stored_submissions(&[min_id]).await? != 0;
-                self.db.update_submissions(&submissions).await?;
-                time::sleep(Duration::from_millis(200)).await;
+        // Older pages of a contest interrupted last time would never be crawled again,
+        // because its newest page is already stored.
+        let states = self
+            .db
+            .load_crawl_states(CRAWLER_NAME)
+            .await?
+            .into_iter()
+            .map(|state| (state.contest_id.clone(), state))
+            .collect::<BTreeMap<_, _>>();
+        let last_crawled = self
+            .db
+            .load_last_crawled(CRAWLER_NAME)
+            .await?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let recent_counts = self
+            .db
+            .get_submissions(SubmissionRequest::RecentAll {
+                count: ACTIVITY_SAMPLE_SIZE,
+            })
+            .await?
+            .into_iter()
+            .fold(BTreeMap::new(), |mut map, submission| {
+                *map.entry(submission.contest_id).or_insert(0) += 1;
+                map
+            });
+
+        let mut contests = contests
+            .iter()
+            .filter(|c| c.start_epoch_second <= now)
+            .map(|c| {
+                let recent_count = recent_counts.get(&c.id).copied().unwrap_or(0);
+                let priority =
+                    crawl_priority(c, recent_count, last_crawled.get(&c.id).copied(), now);
+                (priority, recent_count > 0, c)
+            })
+            .collect::<Vec<_>>();
+        contests.sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
 
-                if exists {
-                    info!("Finished crawling {}", contest.id);
+        // Contests without recent submissions can't use the reserved pages.
+        let mut remaining_pages = self.page_budget;
+        let mut inactive_pages = self.page_budget - self.page_budget * ACTIVE_BUDGET_PERCENT / 100;
+        for (_, active, contest) in contests {
+            if remaining_pages == 0 {
+                info!("Used up the budget of {} pages", self.page_budget);
+                break;
+            }
+            let page_budget = if active {
+                remaining_pages
+            } else {
+                remaining_pages.min(inactive_pages)
+            };
+            if page_budget == 0 {
+                continue;
+            }
+            let pages = self
+                .visit(contest, &states, &last_crawled, page_budget, now)
+                .await?;
+            remaining_pages -= pages;
+            if !active {
+                inactive_pages -= pages;
+            }
+        }
+
+        info!("Finished");
+        Ok(())
+    }
+
+    /// Crawls a contest within `page_budget` pages and records when it has been crawled.
+    /// Returns the number of fetched pages.
+    async fn visit(
+        &self,
+        contest: &Contest,
+        states: &BTreeMap<String, CrawlState>,
+        last_crawled: &BTreeMap<String, i64>,
+        page_budget: u32,
+        now: i64,
+    ) -> Result<u32> {
+        let (pages, completed) = self
+            .crawl_contest(&contest.id, states.get(&contest.id), page_budget)
+            .await?;
+        // A contest which has never been crawled is recorded even if it's unfinished,
+        // otherwise it would stay the stalest one and take the budget every time.
+        // The rest of it is crawled later from the crawl state.
+        if completed || !last_crawled.contains_key(&contest.id) {
+            self.db
+                .save_last_crawled(CRAWLER_NAME, &contest.id, now)
+                .await?;
+        }
+        Ok(pages)
+    }
+
+    /// Crawls new submissions of a contest within `page_budget` pages,
+    /// resuming from `state` if the previous crawl has been interrupted.
+    /// New submissions push the crawled ones to later pages, so a resumed crawl skips the pages
+    /// newer than the anchor and only the submissions older than it can be the stored ones,
+    /// as `FailedPageCrawler` does.
+    /// Returns the number of fetched pages and whether it has reached the stored submissions.
+    async fn crawl_contest(
+        &self,
+        contest_id: &str,
+        state: Option<&CrawlState>,
+        page_budget: u32,
+    ) -> Result<(u32, bool)> {
+        let (first_page, mut before_id) = match state {
+            Some(state) => {
+                info!(
+                    "Resuming {} from page {} before {:?}",
+                    contest_id,
+                    state.last_page + 1,
+                    state.before_id
+                );
+                (state.last_page as u32 + 1, state.before_id)
+            }
+            None => (1, None),
+        };
+        let mut anchor = before_id;
+        let mut has_state = state.is_some();
+        let mut completed = true;
+        let mut pages = 0;
+
+        for page in first_page.. {
+            if pages == page_budget {
+                completed = false;
+                break;
+            }
+            info!("Crawling {}-{} ...", contest_id, page);
+            let result = self.fetcher.fetch_submissions(contest_id, page).await;
+            pages += 1;
+            let (submissions, max_page) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    // The next call resumes from this page, since the progress is kept.
+                    dead_letter(&self.db, CRAWLER_NAME, contest_id, page, before_id, &e).await?;
+                    completed = false;
                     break;
                 }
+            };
+            if submissions.is_empty() {
+                info!("There is no submission on {}-{}", contest_id, page);
+                break;
+            }
+
+            let min_id = submissions.iter().map(|s| s.id).min().unwrap();
+            if anchor.map_or(false, |anchor| min_id >= anchor) {
+                // The page has been crawled before being pushed here.
+                info!("Skipping {}-{} newer than {:?}", contest_id, page, anchor);
+                store_submissions(&self.db, &submissions).await?;
                 if page == max_page {
-                    info!(
-                        "Finished crawling {} since it's last page: {}",
-                        contest.id, page
-                    );
                     break;
                 }
+                self.db
+                    .save_crawl_state(CRAWLER_NAME, contest_id, page, max_page, before_id)
+                    .await?;
+                continue;
             }
+            anchor = None;
+            before_id = Some(min_id);
+            let exists = self.db.count_stored_submissions(&[min_id]).await? != 0;
+            store_submissions(&self.db, &submissions).await?;
+
+            if exists {
+                info!("Finished crawling {}", contest_id);
+                break;
+            }
+            if page == max_page {
+                info!(
+                    "Finished crawling {} since it's last page: {}",
+                    contest_id, page
+                );
+                break;
+            }
+            self.db
+                .save_crawl_state(CRAWLER_NAME, contest_id, page, max_page, before_id)
+                .await?;
+            has_state = true;
         }
 
-        info!("Finished");
-        Ok(())
+        if has_state && completed {
+            self.db.delete_crawl_state(CRAWLER_NAME, contest_id).await?;
+        }
+        Ok((pages, completed))
     }
 }
 
+/// Contests with more recent submissions and contests which have ended more recently are hotter.
+/// A contest is crawled first if it's hot or if it hasn't been crawled for a long time,
+/// so that hot contests are crawled often and cold ones are still crawled once in a while.
+/// The staleness is added rather than multiplied and is capped,
+/// so that contests never crawled can't outweigh the hot ones by orders of magnitude.
+fn crawl_priority(
+    contest: &Contest,
+    recent_count: usize,
+    last_crawled: Option<i64>,
+    now: i64,
+) -> f64 {
+    let recency_bonus = if contest.duration_second > LONG_CONTEST_SECOND {
+        0.0
+    } else {
+        let end = contest.start_epoch_second + contest.duration_second;
+        let days_since_end = (now - end).max(0) as f64 / DAY_SECOND;
+        RECENCY_BONUS * 0.5f64.powf(days_since_end)
+    };
+    let heat = 1.0 + recent_count as f64 + recency_bonus;
+    let staleness = last_crawled.map_or(MAX_STALENESS_SECOND, |last_crawled| {
+        (now - last_crawled).clamp(0, MAX_STALENESS_SECOND)
+    });
+    heat + STALENESS_PER_HOUR * staleness as f64 / 3600.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::crawler::utils::MockFetcher;
     use async_trait::async_trait;
-    use sql_client::models::{Contest, Problem, Submission};
-    use sql_client::submission_client::SubmissionRequest;
-    use std::ops::Range;
+    use sql_client::models::Submission;
+    use std::sync::{Arc, Mutex};
 
-    #[actix_web::test]
-    async fn test_recent_crawler() {
-        let fetcher = MockFetcher(|contest_id: &str, page: u32| {
+    #[derive(Default)]
+    struct MockDB {
+        last_page: Option<i32>,
+        before_id: Option<i64>,
+        /// Ids of the stored submissions other than 0, which is always stored.
+        stored_ids: Vec<i64>,
+        last_crawled: Option<i64>,
+        /// Adds a contest without recent submissions, which has never been crawled.
+        with_stale: bool,
+        deleted: Mutex<bool>,
+        /// Pairs of the last page and the anchor of the saved crawl states.
+        saved_states: Mutex<Vec<(u32, Option<i64>)>>,
+        crawled: Mutex<Vec<String>>,
+        failed_pages: Mutex<Vec<u32>>,
+        /// Number of the submissions looked into for orphaned problems.
+        orphan_checked: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl SubmissionClient for MockDB {
+        async fn get_submissions<'a>(
+            &self,
+            request: SubmissionRequest<'a>,
+        ) -> Result<Vec<Submission>> {
+            match request {
+                SubmissionRequest::ByIds { ids } => Ok(ids
+                    .iter()
+                    .filter(|&&id| id == 0 || self.stored_ids.contains(&id))
+                    .map(|&id| Submission {
+                        id,
+                        ..Default::default()
+                    })
+                    .collect()),
+                SubmissionRequest::RecentAll { count } => {
+                    assert_eq!(count, ACTIVITY_SAMPLE_SIZE);
+                    Ok(vec![Submission {
+                        contest_id: "contest".to_string(),
+                        ..Default::default()
+                    }])
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
+            assert_eq!(submissions.len(), 2);
+            Ok(2)
+        }
+    }
+    #[async_trait]
+    impl OrphanClient for MockDB {
+        async fn record_submission_orphans(&self, submissions: &[Submission]) -> Result<usize> {
+            *self.orphan_checked.lock().unwrap() += submissions.len();
+            Ok(0)
+        }
+    }
+    #[async_trait]
+    impl SimpleClient for MockDB {
+        async fn load_contests(&self) -> Result<Vec<Contest>> {
+            let ids = if self.with_stale {
+                vec!["contest", "stale"]
+            } else {
+                vec!["contest"]
+            };
+            Ok(ids
+                .into_iter()
+                .map(|id| Contest {
+                    id: id.to_string(),
+                    ..Default::default()
+                })
+                .collect())
+        }
+    }
+    #[async_trait]
+    impl CrawlStateClient for MockDB {
+        async fn load_crawl_states(&self, _: &str) -> Result<Vec<CrawlState>> {
+            let states = self.last_page.map(|last_page| CrawlState {
+                crawler: CRAWLER_NAME.to_string(),
+                contest_id: "contest".to_string(),
+                last_page,
+                before_id: self.before_id,
+                ..Default::default()
+            });
+            Ok(states.into_iter().collect())
+        }
+        async fn save_crawl_state(
+            &self,
+            _: &str,
+            _: &str,
+            last_page: u32,
+            _: u32,
+            before_id: Option<i64>,
+        ) -> Result<()> {
+            self.saved_states
+                .lock()
+                .unwrap()
+                .push((last_page, before_id));
+            Ok(())
+        }
+        async fn delete_crawl_state(&self, _: &str, contest_id: &str) -> Result<()> {
             assert_eq!(contest_id, "contest");
-            assert_eq!(page, 1);
-            vec![
+            *self.deleted.lock().unwrap() = true;
+            Ok(())
+        }
+        async fn load_last_crawled(&self, _: &str) -> Result<Vec<(String, i64)>> {
+            let last_crawled = self
+                .last_crawled
+                .map(|last_crawled| ("contest".to_string(), last_crawled));
+            Ok(last_crawled.into_iter().collect())
+        }
+        async fn save_last_crawled(&self, _: &str, contest_id: &str, _: i64) -> Result<()> {
+            self.crawled.lock().unwrap().push(contest_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl FailedPageClient for MockDB {
+        async fn record_failed_page(
+            &self,
+            crawler: &str,
+            contest_id: &str,
+            page: u32,
+            _: Option<i64>,
+            _: &str,
+        ) -> Result<()> {
+            assert_eq!((crawler, contest_id), (CRAWLER_NAME, "contest"));
+            self.failed_pages.lock().unwrap().push(page);
+            Ok(())
+        }
+    }
+
+    fn fetcher(
+        expected_page: u32,
+        min_id: i64,
+    ) -> MockFetcher<impl Fn(&str, u32) -> Result<Vec<Submission>> + Send + Sync> {
+        MockFetcher(move |contest_id: &str, page: u32| {
+            assert_eq!(contest_id, "contest");
+            assert!(page >= expected_page);
+            Ok(vec![
                 Submission {
-                    id: 0,
+                    id: min_id,
                     ..Default::default()
                 },
                 Submission {
-                    id: 1,
+                    id: min_id + 1,
                     ..Default::default()
                 },
-            ]
-        });
+            ])
+        })
+    }
 
-        struct MockDB;
-        #[async_trait]
-        impl SubmissionClient for MockDB {
-            async fn get_submissions<'a>(
-                &self,
-                request: SubmissionRequest<'a>,
-            ) -> Result<Vec<Submission>> {
-                match request {
-                    SubmissionRequest::ByIds { ids } => {
-                        assert_eq!(ids, &[0]);
-                        Ok(vec![Submission {
-                            ..Default::default()
-                        }])
-                    }
-                    _ => unimplemented!(),
-                }
-            }
+    #[actix_web::test]
+    async fn test_recent_crawler() {
+        let crawler = RecentCrawler::new(MockDB::default(), fetcher(1, 0));
+        assert!(crawler.crawl().await.is_ok());
+        assert!(!*crawler.db.deleted.lock().unwrap());
+        assert_eq!(*crawler.db.orphan_checked.lock().unwrap(), 2);
+        assert_eq!(*crawler.db.crawled.lock().unwrap(), vec!["contest"]);
+    }
 
-            async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
-                assert_eq!(submissions.len(), 2);
-                Ok(2)
-            }
-            async fn get_user_submission_count(&self, _: &str, _: Range<i64>) -> Result<usize> {
-                unimplemented!()
-            }
-        }
-        #[async_trait]
-        impl SimpleClient for MockDB {
-            async fn insert_contests(&self, _: &[Contest]) -> Result<usize> {
-                unimplemented!()
-            }
-            async fn insert_problems(&self, _: &[Problem]) -> Result<usize> {
-                unimplemented!()
-            }
-            async fn load_problems(&self) -> Result<Vec<Problem>> {
-                unimplemented!()
-            }
-            async fn load_contests(&self) -> Result<Vec<Contest>> {
-                Ok(vec![Contest {
-                    id: "contest".to_string(),
+    #[actix_web::test]
+    async fn test_recent_crawler_resumes() {
+        let db = MockDB {
+            last_page: Some(2),
+            ..Default::default()
+        };
+        let crawler = RecentCrawler::new(db, fetcher(3, 0));
+        assert!(crawler.crawl().await.is_ok());
+        assert!(*crawler.db.deleted.lock().unwrap());
+    }
+
+    #[actix_web::test]
+    async fn test_recent_crawler_resumes_after_shifted_pages() {
+        // New submissions have pushed the pages crawled last time from 1-2 to 2-3.
+        let fetched = Arc::new(Mutex::new(Vec::new()));
+        let fetcher = {
+            let fetched = fetched.clone();
+            MockFetcher(move |_: &str, page: u32| {
+                fetched.lock().unwrap().push(page);
+                let min_id = match page {
+                    3 => 10,
+                    4 => 8,
+                    5 => 6,
+                    _ => return Ok(vec![]),
+                };
+                Ok(vec![
+                    Submission {
+                        id: min_id,
+                        ..Default::default()
+                    },
+                    Submission {
+                        id: min_id + 1,
+                        ..Default::default()
+                    },
+                ])
+            })
+        };
+        let db = MockDB {
+            last_page: Some(2),
+            before_id: Some(10),
+            stored_ids: vec![10, 11, 12, 13],
+            ..Default::default()
+        };
+        let crawler = RecentCrawler::new(db, fetcher);
+        assert!(crawler.crawl().await.is_ok());
+
+        // Page 3 has been stored, but the older submissions after it are still crawled.
+        assert_eq!(*fetched.lock().unwrap(), vec![3, 4, 5, 6]);
+        assert_eq!(
+            *crawler.db.saved_states.lock().unwrap(),
+            vec![(3, Some(10)), (4, Some(8)), (5, Some(6))]
+        );
+        assert!(*crawler.db.deleted.lock().unwrap());
+    }
+
+    #[actix_web::test]
+    async fn test_recent_crawler_page_budget() {
+        // None of the submissions are stored, so it would keep crawling without the budget.
+        let db = MockDB {
+            last_crawled: Some(Utc::now().timestamp()),
+            ..Default::default()
+        };
+        let crawler = RecentCrawler::new(db, fetcher(1, 5)).with_page_budget(2);
+        assert!(crawler.crawl().await.is_ok());
+        assert!(crawler.db.crawled.lock().unwrap().is_empty());
+        // The progress is anchored to the smallest id crawled so far.
+        assert_eq!(
+            *crawler.db.saved_states.lock().unwrap(),
+            vec![(1, Some(5)), (2, Some(5))]
+        );
+
+        // A contest crawled for the first time is recorded even if it's unfinished.
+        let crawler = RecentCrawler::new(MockDB::default(), fetcher(1, 5)).with_page_budget(2);
+        assert!(crawler.crawl().await.is_ok());
+        assert_eq!(*crawler.db.crawled.lock().unwrap(), vec!["contest"]);
+
+        let crawler = RecentCrawler::new(MockDB::default(), MockFetcher(|_, _| unreachable!()))
+            .with_page_budget(0);
+        assert!(crawler.crawl().await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_recent_crawler_failed_page() {
+        let fetcher = MockFetcher(|_, page| match page {
+            1 => Ok(vec![
+                Submission {
+                    id: 5,
                     ..Default::default()
-                }])
-            }
-        }
+                },
+                Submission {
+                    id: 6,
+                    ..Default::default()
+                },
+            ]),
+            2 => Err(anyhow::anyhow!("connection reset")),
+            _ => unreachable!("The contest should be resumed from page 2 next time"),
+        });
+        let crawler = RecentCrawler::new(MockDB::default(), fetcher);
+        assert!(crawler.crawl().await.is_ok());
+        assert_eq!(*crawler.db.failed_pages.lock().unwrap(), vec![2]);
+        assert!(!*crawler.db.deleted.lock().unwrap());
+    }
 
-        let crawler = RecentCrawler::new(MockDB, fetcher);
+    #[actix_web::test]
+    async fn test_recent_crawler_reserves_budget_for_active_contests() {
+        let fetched = Arc::new(Mutex::new(Vec::new()));
+        let fetcher = {
+            let fetched = fetched.clone();
+            MockFetcher(move |contest_id: &str, page: u32| {
+                fetched.lock().unwrap().push((contest_id.to_string(), page));
+                Ok(vec![
+                    Submission {
+                        id: 5,
+                        ..Default::default()
+                    },
+                    Submission {
+                        id: 6,
+                        ..Default::default()
+                    },
+                ])
+            })
+        };
+        let db = MockDB {
+            last_crawled: Some(Utc::now().timestamp()),
+            with_stale: true,
+            ..Default::default()
+        };
+        let crawler = RecentCrawler::new(db, fetcher).with_page_budget(4);
         assert!(crawler.crawl().await.is_ok());
+
+        // The stale contest comes first, but can't use the half reserved for the active one.
+        let page = |contest_id: &str, page: u32| (contest_id.to_string(), page);
+        assert_eq!(
+            *fetched.lock().unwrap(),
+            vec![
+                page("stale", 1),
+                page("stale", 2),
+                page("contest", 1),
+                page("contest", 2)
+            ]
+        );
+        assert_eq!(*crawler.db.crawled.lock().unwrap(), vec!["stale"]);
+    }
+
+    #[test]
+    fn test_crawl_priority() {
+        let now = 1_700_000_000;
+        let contest = |end: i64| Contest {
+            start_epoch_second: end - 6000,
+            duration_second: 6000,
+            ..Default::default()
+        };
+        let old = contest(0);
+        let recent = contest(now - 3600);
+        let permanent = Contest {
+            duration_second: 100 * 365 * 24 * 3600,
+            ..Default::default()
+        };
+
+        let minute_ago = Some(now - 60);
+        assert!(
+            crawl_priority(&recent, 0, minute_ago, now) > crawl_priority(&old, 0, minute_ago, now)
+        );
+        assert!(
+            crawl_priority(&old, 50, minute_ago, now) > crawl_priority(&old, 0, minute_ago, now)
+        );
+        assert!(
+            crawl_priority(&recent, 0, minute_ago, now)
+                > crawl_priority(&permanent, 0, minute_ago, now)
+        );
+
+        // A cold contest left for a day comes before a hot contest crawled a minute ago.
+        let day_ago = Some(now - 24 * 3600);
+        assert!(crawl_priority(&old, 0, day_ago, now) > crawl_priority(&old, 100, minute_ago, now));
+        assert!(crawl_priority(&old, 0, None, now) > crawl_priority(&recent, 100, day_ago, now));
+
+        // The staleness is capped, so a very hot contest still wins over the contests never crawled.
+        let year_ago = Some(now - 365 * 24 * 3600);
+        assert_eq!(
+            crawl_priority(&old, 0, year_ago, now),
+            crawl_priority(&old, 0, None, now)
+        );
+        assert!(crawl_priority(&old, 2000, minute_ago, now) > crawl_priority(&old, 0, None, now));
     }
 }
@@ -0,0 +1,146 @@
+use crate::crawler::{handle_fetch_error, AtCoderFetcher};
+use anyhow::Result;
+use chrono::Utc;
+use log::info;
+use sql_client::simple_client::SimpleClient;
+use sql_client::standings::StandingsClient;
+use std::collections::BTreeSet;
+
+pub struct StandingsCrawler<C, F> {
+    db: C,
+    fetcher: F,
+}
+
+impl<C, F> StandingsCrawler<C, F>
+where
+    C: SimpleClient + StandingsClient,
+    F: AtCoderFetcher,
+{
+    pub fn new(db: C, fetcher: F) -> Self {
+        Self { db, fetcher }
+    }
+
+    /// Crawls the standings of every finished contest whose standings are not stored yet.
+    pub async fn crawl(&self) -> Result<()> {
+        info!("Started");
+        let now = Utc::now().timestamp();
+        let crawled = self
+            .db
+            .load_standings_contest_ids()
+            .await?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let contests = self.db.load_contests().await?;
+        let contest_ids = contests
+            .iter()
+            .filter(|c| c.start_epoch_second + c.duration_second <= now)
+            .filter(|c| !crawled.contains(&c.id))
+            .map(|c| c.id.as_str())
+            .collect::<Vec<_>>();
+
+        info!("There are {} contests to crawl.", contest_ids.len());
+        for contest_id in contest_ids {
+            if let Err(e) = self.crawl_contest(contest_id).await {
+                let target = format!("standings of {}", contest_id);
+                handle_fetch_error(&e, &target).await;
+            }
+        }
+
+        info!("Finished");
+        Ok(())
+    }
+
+    pub async fn crawl_contest(&self, contest_id: &str) -> Result<()> {
+        info!("Crawling standings of {} ...", contest_id);
+        let (standings, task_results) = self.fetcher.fetch_standings(contest_id).await?;
+        self.db
+            .update_contest_standings(contest_id, &standings, &task_results)
+            .await?;
+        info!(
+            "Saved {} rows of {} standings.",
+            standings.len(),
+            contest_id
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use atcoder_client::AtCoderError;
+    use sql_client::models::{Contest, ContestStanding, ContestStandingTaskResult};
+
+    struct MockFetcher;
+
+    #[async_trait]
+    impl AtCoderFetcher for MockFetcher {
+        async fn fetch_standings(
+            &self,
+            contest_id: &str,
+        ) -> Result<(Vec<ContestStanding>, Vec<ContestStandingTaskResult>)> {
+            if contest_id == "deleted" {
+                return Err(AtCoderError::NotFound(contest_id.to_string()).into());
+            }
+            assert_eq!(contest_id, "finished");
+            Ok((
+                vec![ContestStanding {
+                    contest_id: contest_id.to_string(),
+                    ..Default::default()
+                }],
+                vec![],
+            ))
+        }
+    }
+
+    struct MockDB;
+
+    #[async_trait]
+    impl SimpleClient for MockDB {
+        async fn load_contests(&self) -> Result<Vec<Contest>> {
+            Ok(vec![
+                Contest {
+                    id: "finished".to_string(),
+                    ..Default::default()
+                },
+                Contest {
+                    id: "deleted".to_string(),
+                    ..Default::default()
+                },
+                Contest {
+                    id: "crawled".to_string(),
+                    ..Default::default()
+                },
+                Contest {
+                    id: "running".to_string(),
+                    start_epoch_second: i64::MAX / 2,
+                    ..Default::default()
+                },
+            ])
+        }
+    }
+
+    #[async_trait]
+    impl StandingsClient for MockDB {
+        async fn update_contest_standings(
+            &self,
+            contest_id: &str,
+            standings: &[ContestStanding],
+            _: &[ContestStandingTaskResult],
+        ) -> Result<()> {
+            assert_eq!(contest_id, "finished");
+            assert_eq!(standings.len(), 1);
+            Ok(())
+        }
+        async fn load_standings_contest_ids(&self) -> Result<Vec<String>> {
+            Ok(vec!["crawled".to_string()])
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_standings_crawler() {
+        let crawler = StandingsCrawler::new(MockDB, MockFetcher);
+        assert!(crawler.crawl().await.is_ok());
+    }
+}
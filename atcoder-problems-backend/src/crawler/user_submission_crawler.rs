@@ -0,0 +1,167 @@
+use crate::crawler::{store_submissions, AtCoderFetcher};
+use anyhow::Result;
+use atcoder_client::SubmissionListFilter;
+use chrono::Utc;
+use log::info;
+use sql_client::orphan::OrphanClient;
+use sql_client::simple_client::SimpleClient;
+use sql_client::submission_client::SubmissionClient;
+
+pub struct UserSubmissionCrawler<C, F> {
+    db: C,
+    fetcher: F,
+    user_id: String,
+}
+
+impl<C, F> UserSubmissionCrawler<C, F>
+where
+    C: SubmissionClient + OrphanClient + SimpleClient + Sync,
+    F: AtCoderFetcher,
+{
+    pub fn new<S: ToString>(db: C, fetcher: F, user_id: S) -> Self {
+        Self {
+            db,
+            fetcher,
+            user_id: user_id.to_string(),
+        }
+    }
+
+    /// Crawls the submissions of the user in every contest which has already started.
+    pub async fn crawl(&self) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let contests = self.db.load_contests().await?;
+        let contest_ids = contests
+            .iter()
+            .filter(|c| c.start_epoch_second <= now)
+            .map(|c| c.id.as_str())
+            .collect::<Vec<_>>();
+        self.crawl_contests(&contest_ids).await
+    }
+
+    pub async fn crawl_contests(&self, contest_ids: &[&str]) -> Result<()> {
+        info!("Crawling submissions of {} ...", self.user_id);
+        let filter = SubmissionListFilter::user(&self.user_id);
+        for contest_id in contest_ids {
+            for page in 1.. {
+                let (submissions, max_page) = self
+                    .fetcher
+                    .fetch_filtered_submissions(contest_id, page, &filter)
+                    .await?;
+                if submissions.is_empty() {
+                    break;
+                }
+
+                info!(
+                    "Found {} submissions of {} in {}-{}",
+                    submissions.len(),
+                    self.user_id,
+                    contest_id,
+                    page
+                );
+                store_submissions(&self.db, &submissions).await?;
+
+                if page >= max_page {
+                    break;
+                }
+            }
+        }
+
+        info!("Finished crawling {}", self.user_id);
+        Ok(())
+    }
+
+    /// Crawls the submissions of the user in each contest from the newest one,
+    /// until it reaches the ones already stored, or until it fetches `page_budget` pages.
+    /// Returns the number of fetched pages.
+    pub async fn crawl_new_submissions(
+        &self,
+        contest_ids: &[&str],
+        page_budget: u32,
+    ) -> Result<u32> {
+        info!("Crawling new submissions of {} ...", self.user_id);
+        let filter = SubmissionListFilter::user(&self.user_id);
+        let mut pages = 0;
+        for contest_id in contest_ids {
+            for page in 1.. {
+                if pages >= page_budget {
+                    info!("Used up the budget of {} pages", page_budget);
+                    return Ok(pages);
+                }
+                let (submissions, max_page) = self
+                    .fetcher
+                    .fetch_filtered_submissions(contest_id, page, &filter)
+                    .await?;
+                pages += 1;
+                if submissions.is_empty() {
+                    break;
+                }
+
+                let ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+                let stored = self.db.count_stored_submissions(&ids).await?;
+                // The stored ones are updated too, since their results may have changed.
+                store_submissions(&self.db, &submissions).await?;
+                if stored == submissions.len() || page >= max_page {
+                    break;
+                }
+            }
+        }
+
+        info!("Finished crawling {}", self.user_id);
+        Ok(pages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::utils::MockFetcher;
+    use async_trait::async_trait;
+    use sql_client::models::{Contest, Submission};
+
+    struct MockDB;
+
+    impl OrphanClient for MockDB {}
+
+    #[async_trait]
+    impl SubmissionClient for MockDB {
+        async fn update_submissions(&self, submissions: &[Submission]) -> Result<usize> {
+            assert_eq!(submissions.len(), 1);
+            assert_eq!(submissions[0].contest_id, "started");
+            Ok(1)
+        }
+    }
+
+    #[async_trait]
+    impl SimpleClient for MockDB {
+        async fn load_contests(&self) -> Result<Vec<Contest>> {
+            Ok(vec![
+                Contest {
+                    id: "started".to_string(),
+                    ..Default::default()
+                },
+                Contest {
+                    id: "upcoming".to_string(),
+                    start_epoch_second: i64::MAX,
+                    ..Default::default()
+                },
+            ])
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_user_submission_crawler() {
+        let fetcher = MockFetcher(|contest_id, page| {
+            assert_eq!(contest_id, "started");
+            assert_eq!(page, 1);
+            let submission = |user_id: &str| Submission {
+                contest_id: contest_id.to_string(),
+                user_id: user_id.to_string(),
+                ..Default::default()
+            };
+            // The submission of the other user is filtered out by the user filter.
+            Ok(vec![submission("user"), submission("other")])
+        });
+        let crawler = UserSubmissionCrawler::new(MockDB, fetcher, "user");
+        assert!(crawler.crawl().await.is_ok());
+    }
+}
@@ -1,25 +1,42 @@
 use crate::crawler::AtCoderFetcher;
 use anyhow::Result;
 use async_trait::async_trait;
-use atcoder_client::ContestTypeSpecifier;
-use sql_client::models::{Contest, ContestProblem, Problem, Submission};
+use atcoder_client::SubmissionListFilter;
+use sql_client::models::Submission;
 
-pub(crate) struct MockFetcher<F: Fn(&str, u32) -> Vec<Submission>>(pub(crate) F);
+#[derive(Clone)]
+pub(crate) struct MockFetcher<F: Fn(&str, u32) -> Result<Vec<Submission>>>(pub(crate) F);
 
 #[async_trait]
 impl<F> AtCoderFetcher for MockFetcher<F>
 where
-    F: Fn(&str, u32) -> Vec<Submission> + Send + Sync,
+    F: Fn(&str, u32) -> Result<Vec<Submission>> + Send + Sync,
 {
-    async fn fetch_submissions(&self, contest_id: &str, page: u32) -> (Vec<Submission>, u32) {
-        ((self.0)(contest_id, page), 0)
+    async fn fetch_submissions(
+        &self,
+        contest_id: &str,
+        page: u32,
+    ) -> Result<(Vec<Submission>, u32)> {
+        Ok(((self.0)(contest_id, page)?, 0))
     }
 
-    async fn fetch_contests(&self, _: ContestTypeSpecifier) -> Result<Vec<Contest>> {
-        unimplemented!()
-    }
-
-    async fn fetch_problems(&self, _: &str) -> Result<(Vec<Problem>, Vec<ContestProblem>)> {
-        unimplemented!()
+    /// Filters the submissions as AtCoder does, so that tests can see which filter has been used.
+    async fn fetch_filtered_submissions(
+        &self,
+        contest_id: &str,
+        page: u32,
+        filter: &SubmissionListFilter,
+    ) -> Result<(Vec<Submission>, u32)> {
+        let matches = |value: &str, filter: &Option<String>| {
+            filter.as_deref().map_or(true, |filter| value == filter)
+        };
+        let submissions = (self.0)(contest_id, page)?
+            .into_iter()
+            .filter(|s| matches(&s.problem_id, &filter.task))
+            .filter(|s| matches(&s.user_id, &filter.user))
+            .filter(|s| matches(&s.language, &filter.language))
+            .filter(|s| matches(&s.result, &filter.status))
+            .collect();
+        Ok((submissions, 0))
     }
 }
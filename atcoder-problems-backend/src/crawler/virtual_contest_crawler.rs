@@ -1,15 +1,16 @@
-use crate::crawler::AtCoderFetcher;
-use actix_web::rt::time;
+use crate::crawler::{dead_letter, store_submissions, AtCoderFetcher};
 use anyhow::Result;
 use chrono::Utc;
 use rand::distributions::Uniform;
 use rand::Rng;
 use sql_client::contest_problem::ContestProblemClient;
+use sql_client::failed_page::FailedPageClient;
 use sql_client::internal::virtual_contest_manager::VirtualContestManager;
+use sql_client::orphan::OrphanClient;
 use sql_client::submission_client::SubmissionClient;
 use std::collections::BTreeSet;
-use std::time::Duration;
 
+const CRAWLER_NAME: &str = "virtual_contest";
 const CRAWLED_STREAK: usize = 3;
 const CONTEST_LENGTH_LIMIT_SECOND: i64 = 60 * 60 * 5;
 
@@ -21,7 +22,12 @@ pub struct VirtualContestCrawler<'a, P, F, R> {
 
 impl<'a, P, F, R> VirtualContestCrawler<'a, P, F, R>
 where
-    P: ContestProblemClient + VirtualContestManager + SubmissionClient + Sync,
+    P: ContestProblemClient
+        + VirtualContestManager
+        + SubmissionClient
+        + OrphanClient
+        + FailedPageClient
+        + Sync,
     F: AtCoderFetcher,
     R: Rng,
 {
@@ -65,14 +71,24 @@ where
         for contest in contest_set {
             log::info!("Starting {} ...", contest);
             let mut streak = 0;
+            let mut before_id = None;
             for page in 1.. {
                 log::info!("Fetching from {} {} ...", contest, page);
-                let (submissions, max_page) = self.fetcher.fetch_submissions(&contest, page).await;
+                let (submissions, max_page) =
+                    match self.fetcher.fetch_submissions(&contest, page).await {
+                        Ok(result) => result,
+                        Err(e) => {
+                            dead_letter(&self.db_pool, CRAWLER_NAME, &contest, page, before_id, &e)
+                                .await?;
+                            break;
+                        }
+                    };
                 if submissions.is_empty() {
                     log::info!("No submission is fetched");
                     break;
                 }
                 let fetched_ids = submissions.iter().map(|s| s.id).collect::<Vec<_>>();
+                before_id = fetched_ids.iter().min().copied();
                 let stored_submissions =
                     self.db_pool.count_stored_submissions(&fetched_ids).await?;
 
@@ -84,14 +100,12 @@ where
                 }
 
                 log::info!("Updating submissions ...");
-                self.db_pool.update_submissions(&submissions).await?;
+                store_submissions(&self.db_pool, &submissions).await?;
                 log::info!("Updated");
 
                 if streak >= CRAWLED_STREAK || page == max_page {
                     break;
                 }
-                log::info!("Sleeping for 200ms");
-                time::sleep(Duration::from_millis(200)).await;
             }
             log::info!("Finished {}", contest);
         }
@@ -1,10 +1,61 @@
-use crate::crawler::AtCoderFetcher;
+use crate::crawler::{dead_letter, store_submissions, AtCoderFetcher, FetchFailure};
 use anyhow::Result;
+use atcoder_client::JudgeStatus;
+use chrono::Utc;
+use log::{error, info};
+use sql_client::crawl_state::CrawlStateClient;
+use sql_client::failed_page::FailedPageClient;
+use sql_client::models::{Submission, SubmissionChange};
+use sql_client::orphan::OrphanClient;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use sql_client::submission_history::SubmissionHistoryClient;
+use std::collections::{BTreeMap, BTreeSet};
 
-use actix_web::rt::time;
-use log::info;
-use sql_client::submission_client::SubmissionClient;
-use std::time::Duration;
+const CRAWLER_NAME: &str = "whole_contest";
+const RECONCILER_NAME: &str = "reconcile";
+/// So many submissions never disappear at once unless something is wrong with the crawled pages.
+const MAX_VANISHED_SUBMISSIONS: usize = 1000;
+
+/// Differences between the stored submissions of a contest and the ones on AtCoder.
+#[derive(Debug, Default, PartialEq)]
+pub struct Reconciliation {
+    pub rejudged: Vec<SubmissionChange>,
+    pub vanished: Vec<Submission>,
+}
+
+impl Reconciliation {
+    pub fn is_empty(&self) -> bool {
+        self.rejudged.is_empty() && self.vanished.is_empty()
+    }
+
+    /// Users whose statistics may have been changed by the differences.
+    pub fn affected_user_ids(&self) -> Vec<&str> {
+        let rejudged = self.rejudged.iter().map(|c| c.user_id.as_str());
+        let vanished = self.vanished.iter().map(|s| s.user_id.as_str());
+        rejudged
+            .chain(vanished)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Problems whose solvers may have been changed by the differences.
+    pub fn affected_problem_ids(&self) -> Vec<&str> {
+        let rejudged = self.rejudged.iter().map(|c| c.problem_id.as_str());
+        let vanished = self.vanished.iter().map(|s| s.problem_id.as_str());
+        rejudged
+            .chain(vanished)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn affected_submission_ids(&self) -> Vec<i64> {
+        let rejudged = self.rejudged.iter().map(|c| c.submission_id);
+        let vanished = self.vanished.iter().map(|s| s.id);
+        rejudged.chain(vanished).collect()
+    }
+}
 
 pub struct WholeContestCrawler<C, F> {
     db: C,
@@ -14,7 +65,7 @@ pub struct WholeContestCrawler<C, F> {
 
 impl<C, F> WholeContestCrawler<C, F>
 where
-    C: SubmissionClient,
+    C: SubmissionClient + OrphanClient + CrawlStateClient + FailedPageClient + Sync,
     F: AtCoderFetcher,
 {
     pub fn new<S: ToString>(db: C, fetcher: F, contest_id: S) -> Self {
@@ -25,27 +76,219 @@ where
         }
     }
 
+    /// Crawls all the pages of the contest, resuming from the last completed page
+    /// if the previous crawl has been interrupted.
+    /// Pages which fail to be fetched are skipped and left to `FailedPageCrawler`,
+    /// unless the number of pages is not known yet.
     pub async fn crawl(&self) -> Result<()> {
-        for page in 1.. {
+        let state = self
+            .db
+            .load_crawl_state(CRAWLER_NAME, &self.contest_id)
+            .await?;
+        let (first_page, mut known_max_page, mut before_id) = match state {
+            Some(state) => {
+                info!(
+                    "Resuming {} from page {}/{}",
+                    self.contest_id,
+                    state.last_page + 1,
+                    state.max_page
+                );
+                (
+                    state.last_page as u32 + 1,
+                    state.max_page as u32,
+                    state.before_id,
+                )
+            }
+            None => (1, 0, None),
+        };
+
+        for page in first_page.. {
             info!("Crawling {} {} ...", self.contest_id, page);
-            let (submissions, max_page) =
-                self.fetcher.fetch_submissions(&self.contest_id, page).await;
+            let fetched = self
+                .fetch_page(CRAWLER_NAME, page, known_max_page, before_id)
+                .await?;
+            let (submissions, max_page) = match fetched {
+                Some(result) => result,
+                None if page < known_max_page => {
+                    before_id = None;
+                    continue;
+                }
+                None => break,
+            };
             if submissions.is_empty() {
                 info!("{}-{} is empty.", self.contest_id, page);
                 break;
             }
+            before_id = submissions.iter().map(|s| s.id).min();
+
+            store_submissions(&self.db, &submissions).await?;
             if page == max_page {
                 info!("Finished crawling {}", self.contest_id);
                 break;
             }
-
-            self.db.update_submissions(&submissions).await?;
-            time::sleep(Duration::from_millis(200)).await;
+            known_max_page = known_max_page.max(max_page);
+            self.db
+                .save_crawl_state(
+                    CRAWLER_NAME,
+                    &self.contest_id,
+                    page,
+                    known_max_page,
+                    before_id,
+                )
+                .await?;
         }
 
+        self.db
+            .delete_crawl_state(CRAWLER_NAME, &self.contest_id)
+            .await?;
         info!("Finished");
         Ok(())
     }
+
+    /// Fetches a page, leaving it to the dead-letter queue if it fails.
+    /// Fails if the number of pages is not known yet, since the rest can't be crawled then,
+    /// or if the scraper is broken, since the other pages would fail in the same way.
+    async fn fetch_page(
+        &self,
+        crawler: &str,
+        page: u32,
+        known_max_page: u32,
+        before_id: Option<i64>,
+    ) -> Result<Option<(Vec<Submission>, u32)>> {
+        match self.fetcher.fetch_submissions(&self.contest_id, page).await {
+            Ok(result) => Ok(Some(result)),
+            Err(e) => {
+                let failure =
+                    dead_letter(&self.db, crawler, &self.contest_id, page, before_id, &e).await?;
+                match failure {
+                    FetchFailure::Broken => Err(e),
+                    FetchFailure::NotFound => Ok(None),
+                    _ if known_max_page == 0 => Err(e),
+                    _ => Ok(None),
+                }
+            }
+        }
+    }
+}
+
+impl<C, F> WholeContestCrawler<C, F>
+where
+    C: SubmissionClient
+        + OrphanClient
+        + CrawlStateClient
+        + FailedPageClient
+        + SubmissionHistoryClient
+        + Sync,
+    F: AtCoderFetcher,
+{
+    /// Crawls all the pages of the contest and compares them with the stored submissions.
+    /// Rejudged submissions are recorded in the history before being updated,
+    /// and stored submissions which no longer exist on AtCoder are removed.
+    ///
+    /// Vanished submissions are detected only if every page has been fetched,
+    /// so the progress is not kept unlike `crawl`.
+    pub async fn reconcile(&self) -> Result<Reconciliation> {
+        let now = Utc::now().timestamp();
+        let mut stored = self
+            .db
+            .get_submissions(SubmissionRequest::ContestAll {
+                contest_id: &self.contest_id,
+            })
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect::<BTreeMap<_, _>>();
+        info!(
+            "Reconciling {} stored submissions of {}",
+            stored.len(),
+            self.contest_id
+        );
+
+        let mut reconciliation = Reconciliation::default();
+        let mut fetched_ids = BTreeSet::new();
+        let mut complete = true;
+        let mut known_max_page = 0;
+        let mut before_id = None;
+        for page in 1.. {
+            info!("Crawling {} {} ...", self.contest_id, page);
+            let fetched = self
+                .fetch_page(RECONCILER_NAME, page, known_max_page, before_id)
+                .await?;
+            let (submissions, max_page) = match fetched {
+                Some(result) => result,
+                None => {
+                    complete = false;
+                    before_id = None;
+                    if page < known_max_page {
+                        continue;
+                    }
+                    break;
+                }
+            };
+            if submissions.is_empty() {
+                break;
+            }
+            before_id = submissions.iter().map(|s| s.id).min();
+
+            fetched_ids.extend(submissions.iter().map(|s| s.id));
+            let rejudged = submissions
+                .iter()
+                .filter_map(|new| {
+                    let old = stored.get(&new.id)?;
+                    is_rejudged(old, new).then(|| SubmissionChange::rejudged(old, new, now))
+                })
+                .collect::<Vec<_>>();
+            // The old verdicts are recorded before they are overwritten.
+            if !rejudged.is_empty() {
+                self.db.insert_submission_changes(&rejudged).await?;
+                reconciliation.rejudged.extend(rejudged);
+            }
+            store_submissions(&self.db, &submissions).await?;
+            if page == max_page {
+                break;
+            }
+            known_max_page = known_max_page.max(max_page);
+        }
+
+        info!(
+            "{} submissions of {} have been rejudged",
+            reconciliation.rejudged.len(),
+            self.contest_id
+        );
+
+        stored.retain(|id, _| !fetched_ids.contains(id));
+        let vanished = stored.into_values().collect::<Vec<_>>();
+        if !complete {
+            info!(
+                "Skipped detecting vanished submissions of {} since some pages have failed",
+                self.contest_id
+            );
+        } else if vanished.len() > MAX_VANISHED_SUBMISSIONS {
+            error!(
+                "[ALERT] Refused to remove {} vanished submissions of {}",
+                vanished.len(),
+                self.contest_id
+            );
+        } else if !vanished.is_empty() {
+            info!(
+                "{} submissions of {} have vanished",
+                vanished.len(),
+                self.contest_id
+            );
+            self.db.remove_vanished_submissions(&vanished, now).await?;
+            reconciliation.vanished = vanished;
+        }
+        Ok(reconciliation)
+    }
+}
+
+/// Status changes of a submission which is judged for the first time are not rejudges.
+fn is_rejudged(old: &Submission, new: &Submission) -> bool {
+    let judged = matches!(
+        JudgeStatus::parse(&old.result),
+        Some(JudgeStatus::Final(_) | JudgeStatus::WaitingForRejudge)
+    );
+    judged && (old.result != new.result || old.point != new.point)
 }
 
 #[cfg(test)]
@@ -53,41 +296,277 @@ mod tests {
     use super::*;
     use crate::crawler::utils::MockFetcher;
     use async_trait::async_trait;
-    use sql_client::models::Submission;
-    use sql_client::submission_client::SubmissionRequest;
-    use std::ops::Range;
+    use atcoder_client::AtCoderError;
+    use sql_client::models::CrawlState;
+    use sql_client::submission_history::SUBMISSION_REJUDGED;
+    use std::sync::Mutex;
 
-    struct MockDB;
+    #[derive(Default)]
+    struct MockDB {
+        state: Option<CrawlState>,
+        saved_pages: Mutex<Vec<u32>>,
+        failed_pages: Mutex<Vec<u32>>,
+        deleted: Mutex<bool>,
+        stored: Vec<Submission>,
+        changes: Mutex<Vec<SubmissionChange>>,
+        vanished: Mutex<Vec<i64>>,
+    }
+
+    impl OrphanClient for MockDB {}
 
     #[async_trait]
     impl SubmissionClient for MockDB {
-        async fn get_submissions<'a>(&self, _: SubmissionRequest<'a>) -> Result<Vec<Submission>> {
-            unimplemented!()
+        async fn get_submissions<'a>(
+            &self,
+            request: SubmissionRequest<'a>,
+        ) -> Result<Vec<Submission>> {
+            match request {
+                SubmissionRequest::ContestAll { contest_id } => {
+                    assert_eq!(contest_id, "contest-id");
+                    Ok(self.stored.clone())
+                }
+                _ => unimplemented!(),
+            }
         }
 
         async fn update_submissions(&self, _: &[Submission]) -> Result<usize> {
             Ok(1)
         }
+    }
+
+    #[async_trait]
+    impl CrawlStateClient for MockDB {
+        async fn load_crawl_states(&self, crawler: &str) -> Result<Vec<CrawlState>> {
+            assert_eq!(crawler, CRAWLER_NAME);
+            Ok(self.state.iter().cloned().collect())
+        }
+        async fn save_crawl_state(
+            &self,
+            _: &str,
+            _: &str,
+            last_page: u32,
+            _: u32,
+            _: Option<i64>,
+        ) -> Result<()> {
+            self.saved_pages.lock().unwrap().push(last_page);
+            Ok(())
+        }
+        async fn delete_crawl_state(&self, _: &str, contest_id: &str) -> Result<()> {
+            assert_eq!(contest_id, "contest-id");
+            *self.deleted.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl FailedPageClient for MockDB {
+        async fn record_failed_page(
+            &self,
+            crawler: &str,
+            contest_id: &str,
+            page: u32,
+            _: Option<i64>,
+            _: &str,
+        ) -> Result<()> {
+            assert!(crawler == CRAWLER_NAME || crawler == RECONCILER_NAME);
+            assert_eq!(contest_id, "contest-id");
+            self.failed_pages.lock().unwrap().push(page);
+            Ok(())
+        }
+    }
 
-        async fn count_stored_submissions(&self, _: &[i64]) -> Result<usize> {
-            unimplemented!()
+    #[async_trait]
+    impl SubmissionHistoryClient for MockDB {
+        async fn insert_submission_changes(&self, changes: &[SubmissionChange]) -> Result<()> {
+            self.changes.lock().unwrap().extend_from_slice(changes);
+            Ok(())
         }
-        async fn get_user_submission_count(&self, _: &str, _: Range<i64>) -> Result<usize> {
-            unimplemented!()
+        async fn remove_vanished_submissions(
+            &self,
+            submissions: &[Submission],
+            _: i64,
+        ) -> Result<()> {
+            let mut vanished = self.vanished.lock().unwrap();
+            vanished.extend(submissions.iter().map(|s| s.id));
+            Ok(())
         }
     }
+
+    fn submission(id: i64, user_id: &str, result: &str) -> Submission {
+        Submission {
+            id,
+            problem_id: format!("{}_problem", user_id),
+            user_id: user_id.to_string(),
+            result: result.to_string(),
+            ..Default::default()
+        }
+    }
+
     #[actix_web::test]
     async fn whole_contest_crawler() {
         let fetcher = MockFetcher(|_, page| {
-            if page == 1 {
-                vec![Submission {
+            if page <= 2 {
+                Ok(vec![Submission {
                     ..Default::default()
-                }]
+                }])
             } else {
-                vec![]
+                Ok(vec![])
+            }
+        });
+        let db = MockDB::default();
+        let crawler = WholeContestCrawler::new(db, fetcher, "contest-id");
+        assert!(crawler.crawl().await.is_ok());
+        assert_eq!(*crawler.db.saved_pages.lock().unwrap(), vec![1, 2]);
+        assert!(*crawler.db.deleted.lock().unwrap());
+    }
+
+    #[actix_web::test]
+    async fn whole_contest_crawler_resumes() {
+        let fetcher = MockFetcher(|_, page| {
+            assert!(page > 3, "Completed pages must not be crawled again");
+            match page {
+                4 | 6 => Ok(vec![Submission {
+                    ..Default::default()
+                }]),
+                5 => Err(anyhow::anyhow!("connection reset")),
+                _ => Ok(vec![]),
             }
         });
-        let crawler = WholeContestCrawler::new(MockDB, fetcher, "contest-id");
+        let db = MockDB {
+            state: Some(CrawlState {
+                crawler: CRAWLER_NAME.to_string(),
+                contest_id: "contest-id".to_string(),
+                last_page: 3,
+                max_page: 10,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let crawler = WholeContestCrawler::new(db, fetcher, "contest-id");
+
+        // Page 5 is left to the dead-letter queue, and the rest are crawled.
         assert!(crawler.crawl().await.is_ok());
+        assert_eq!(*crawler.db.saved_pages.lock().unwrap(), vec![4, 6]);
+        assert_eq!(*crawler.db.failed_pages.lock().unwrap(), vec![5]);
+        assert!(*crawler.db.deleted.lock().unwrap());
+    }
+
+    #[actix_web::test]
+    async fn whole_contest_crawler_stops_when_broken() {
+        let fetcher = MockFetcher(|_, page| {
+            assert!(page < 6, "Pages after the broken one must not be crawled");
+            match page {
+                4 => Ok(vec![Submission::default()]),
+                _ => Err(AtCoderError::Parse {
+                    page: "submissions",
+                    selector: "table",
+                    row: None,
+                }
+                .into()),
+            }
+        });
+        let db = MockDB {
+            state: Some(CrawlState {
+                crawler: CRAWLER_NAME.to_string(),
+                contest_id: "contest-id".to_string(),
+                last_page: 3,
+                max_page: 10,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let crawler = WholeContestCrawler::new(db, fetcher, "contest-id");
+        assert!(crawler.crawl().await.is_err());
+        assert_eq!(*crawler.db.saved_pages.lock().unwrap(), vec![4]);
+        assert_eq!(*crawler.db.failed_pages.lock().unwrap(), vec![5]);
+        assert!(!*crawler.db.deleted.lock().unwrap());
+    }
+
+    #[actix_web::test]
+    async fn whole_contest_crawler_fails_without_max_page() {
+        let fetcher = MockFetcher(|_, page| {
+            assert_eq!(page, 1);
+            Err(anyhow::anyhow!("connection reset"))
+        });
+        let crawler = WholeContestCrawler::new(MockDB::default(), fetcher, "contest-id");
+        assert!(crawler.crawl().await.is_err());
+        assert_eq!(*crawler.db.failed_pages.lock().unwrap(), vec![1]);
+        assert!(!*crawler.db.deleted.lock().unwrap());
+    }
+
+    #[actix_web::test]
+    async fn whole_contest_crawler_reconciles() {
+        let db = MockDB {
+            stored: vec![
+                submission(1, "rejudged", "AC"),
+                submission(2, "judging", "WJ"),
+                submission(3, "unchanged", "WA"),
+                submission(4, "vanished", "AC"),
+            ],
+            ..Default::default()
+        };
+        let fetcher = MockFetcher(|_, page| match page {
+            1 => Ok(vec![
+                submission(5, "new", "AC"),
+                submission(3, "unchanged", "WA"),
+            ]),
+            2 => Ok(vec![
+                submission(2, "judging", "AC"),
+                submission(1, "rejudged", "WA"),
+            ]),
+            _ => Ok(vec![]),
+        });
+        let crawler = WholeContestCrawler::new(db, fetcher, "contest-id");
+        let reconciliation = crawler.reconcile().await.unwrap();
+
+        assert_eq!(reconciliation.rejudged.len(), 1);
+        let change = &reconciliation.rejudged[0];
+        assert_eq!(change.submission_id, 1);
+        assert_eq!(change.kind, SUBMISSION_REJUDGED);
+        assert_eq!(
+            (change.old_result.as_str(), change.new_result.as_deref()),
+            ("AC", Some("WA"))
+        );
+        assert_eq!(
+            reconciliation
+                .vanished
+                .iter()
+                .map(|s| s.id)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert_eq!(
+            reconciliation.affected_user_ids(),
+            vec!["rejudged", "vanished"]
+        );
+        assert_eq!(reconciliation.affected_submission_ids(), vec![1, 4]);
+        assert_eq!(
+            reconciliation.affected_problem_ids(),
+            vec!["rejudged_problem", "vanished_problem"]
+        );
+        assert_eq!(*crawler.db.changes.lock().unwrap(), reconciliation.rejudged);
+        assert_eq!(*crawler.db.vanished.lock().unwrap(), vec![4]);
+        assert!(crawler.db.saved_pages.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn whole_contest_crawler_reconciles_incomplete_pages() {
+        let db = MockDB {
+            stored: vec![submission(1, "user", "AC"), submission(2, "user", "WR")],
+            ..Default::default()
+        };
+        let fetcher = MockFetcher(|_, page| match page {
+            1 => Ok(vec![submission(2, "user", "WA")]),
+            _ => Err(anyhow::anyhow!("connection reset")),
+        });
+        let crawler = WholeContestCrawler::new(db, fetcher, "contest-id");
+
+        // Submission 1 may be on the failed page, so it must not be removed.
+        assert!(crawler.reconcile().await.is_err());
+        let changes = crawler.db.changes.lock().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].submission_id, 2);
+        assert!(crawler.db.vanished.lock().unwrap().is_empty());
+        assert_eq!(*crawler.db.failed_pages.lock().unwrap(), vec![2]);
     }
 }
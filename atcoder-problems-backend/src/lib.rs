@@ -1,5 +1,10 @@
 pub mod config;
 pub mod crawler;
 pub mod s3;
+pub mod scheduler;
+pub mod scraper_check;
 pub mod server;
+pub mod session;
+pub mod sink;
+pub mod updater;
 pub mod utils;
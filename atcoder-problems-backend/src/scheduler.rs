@@ -0,0 +1,411 @@
+//! Runs crawlers and updaters as periodic jobs in a single process.
+//!
+//! Different jobs run concurrently up to a limit, sharing one AtCoder session and its rate limit,
+//! but a job never overlaps with itself. When more jobs are due than the limit,
+//! the ones with the highest priority run first, where a job gains priority as it gets overdue
+//! so that low-priority jobs are not starved by frequent high-priority ones.
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use anyhow::Result;
+use chrono::Utc;
+use futures_util::future::{self, Either, FutureExt, LocalBoxFuture};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A job gains one priority for each of this period overdue.
+const AGING_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JobConfig {
+    pub interval: Duration,
+    pub priority: u32,
+}
+
+impl JobConfig {
+    pub fn new(interval: Duration, priority: u32) -> Self {
+        Self { interval, priority }
+    }
+
+    /// Overrides `default` with `<NAME>_INTERVAL_SECONDS` and `<NAME>_PRIORITY`.
+    pub fn from_env(name: &str, default: Self) -> Self {
+        Self::from_vars(name, default, |key| std::env::var(key).ok())
+    }
+
+    /// Same as `from_env`, but reads the variables through `get`.
+    pub fn from_vars<G: Fn(&str) -> Option<String>>(name: &str, default: Self, get: G) -> Self {
+        let var = |suffix: &str| {
+            let key = format!("{}_{}", name.to_uppercase(), suffix);
+            get(&key).and_then(|value| value.parse().ok())
+        };
+        Self {
+            interval: var("INTERVAL_SECONDS")
+                .map(Duration::from_secs)
+                .unwrap_or(default.interval),
+            priority: var("PRIORITY")
+                .map(|p: u64| p as u32)
+                .unwrap_or(default.priority),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval_second: u64,
+    pub priority: u32,
+    pub running: bool,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started_epoch_second: Option<i64>,
+    pub last_finished_epoch_second: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Status of all the jobs, which can be read while the scheduler is running.
+#[derive(Clone, Default)]
+pub struct SchedulerStatus(Arc<Mutex<Vec<JobStatus>>>);
+
+impl SchedulerStatus {
+    pub fn jobs(&self) -> Vec<JobStatus> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn update<F: FnOnce(&mut JobStatus)>(&self, index: usize, f: F) {
+        f(&mut self.0.lock().unwrap()[index]);
+    }
+}
+
+type JobFn = Box<dyn Fn() -> LocalBoxFuture<'static, Result<()>>>;
+
+struct Job {
+    name: String,
+    config: JobConfig,
+    run: JobFn,
+    next_run: Instant,
+    running: bool,
+}
+
+impl Job {
+    /// Priority which grows while the job is overdue.
+    fn effective_priority(&self, now: Instant) -> u64 {
+        let overdue = now.saturating_duration_since(self.next_run);
+        u64::from(self.config.priority) + overdue.as_secs() / AGING_PERIOD.as_secs()
+    }
+}
+
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    status: SchedulerStatus,
+    max_concurrent_jobs: usize,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self {
+            jobs: Vec::new(),
+            status: SchedulerStatus::default(),
+            max_concurrent_jobs: 1,
+        }
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets up to `max_concurrent_jobs` different jobs run at the same time, which is 1 by default.
+    pub fn with_max_concurrent_jobs(self, max_concurrent_jobs: usize) -> Self {
+        Self {
+            max_concurrent_jobs: max_concurrent_jobs.max(1),
+            ..self
+        }
+    }
+
+    /// Adds a job which is due immediately, and then `config.interval` after each run finishes.
+    pub fn add_job<F, Fut>(&mut self, name: &str, config: JobConfig, job: F)
+    where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        self.jobs.push(Job {
+            name: name.to_string(),
+            config,
+            run: Box::new(move || job().boxed_local()),
+            next_run: Instant::now(),
+            running: false,
+        });
+        self.status.0.lock().unwrap().push(JobStatus {
+            name: name.to_string(),
+            interval_second: config.interval.as_secs(),
+            priority: config.priority,
+            ..Default::default()
+        });
+    }
+
+    pub fn status(&self) -> SchedulerStatus {
+        self.status.clone()
+    }
+
+    /// Picks the due job with the highest priority, preferring the one which has waited longest.
+    /// Running jobs are never picked again until they finish.
+    fn next_job(&self, now: Instant) -> Option<usize> {
+        self.jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| !job.running && job.next_run <= now)
+            .max_by(|(_, a), (_, b)| {
+                a.effective_priority(now)
+                    .cmp(&b.effective_priority(now))
+                    .then(b.next_run.cmp(&a.next_run))
+            })
+            .map(|(i, _)| i)
+    }
+
+    /// Marks the job as running, and returns the run which resolves to the index and the result.
+    fn start(&mut self, index: usize) -> LocalBoxFuture<'static, (usize, Result<()>)> {
+        let job = &mut self.jobs[index];
+        log::info!("Starting job {}", job.name);
+        job.running = true;
+        self.status.update(index, |status| {
+            status.running = true;
+            status.last_started_epoch_second = Some(Utc::now().timestamp());
+        });
+        (job.run)().map(move |result| (index, result)).boxed_local()
+    }
+
+    fn finish(&mut self, index: usize, result: Result<()>) {
+        let job = &mut self.jobs[index];
+        match &result {
+            Ok(()) => log::info!("Finished job {}", job.name),
+            Err(e) => log::error!("Job {} failed: {:?}", job.name, e),
+        }
+        job.running = false;
+        job.next_run = Instant::now() + job.config.interval;
+        self.status.update(index, |status| {
+            status.running = false;
+            status.runs += 1;
+            status.last_finished_epoch_second = Some(Utc::now().timestamp());
+            status.last_error = result.err().map(|e| format!("{:?}", e));
+            if status.last_error.is_some() {
+                status.failures += 1;
+            }
+        });
+    }
+
+    /// Runs one due job if any, and returns whether it has run a job.
+    pub async fn run_once(&mut self) -> bool {
+        let index = match self.next_job(Instant::now()) {
+            Some(index) => index,
+            None => return false,
+        };
+        let (index, result) = self.start(index).await;
+        self.finish(index, result);
+        true
+    }
+
+    pub async fn run(mut self) {
+        let mut running = FuturesUnordered::new();
+        loop {
+            while running.len() < self.max_concurrent_jobs {
+                match self.next_job(Instant::now()) {
+                    Some(index) => running.push(self.start(index)),
+                    None => break,
+                }
+            }
+
+            if running.len() >= self.max_concurrent_jobs {
+                if let Some((index, result)) = running.next().await {
+                    self.finish(index, result);
+                }
+                continue;
+            }
+
+            let next_run = self
+                .jobs
+                .iter()
+                .filter(|job| !job.running)
+                .map(|job| job.next_run)
+                .min();
+            let wait = next_run.map_or(Duration::from_secs(1), |next_run| {
+                next_run.saturating_duration_since(Instant::now())
+            });
+            let sleep = Box::pin(actix_web::rt::time::sleep(wait));
+            if running.is_empty() {
+                sleep.await;
+                continue;
+            }
+            if let Either::Left((Some((index, result)), _)) =
+                future::select(running.next(), sleep).await
+            {
+                self.finish(index, result);
+            }
+        }
+    }
+}
+
+/// Serves the status of the jobs at `GET /status`.
+pub async fn run_status_server(status: SchedulerStatus, port: u16) -> Result<()> {
+    HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(status.clone()))
+            .route("/status", web::get().to(get_status))
+    })
+    .bind(("0.0.0.0", port))?
+    .workers(1)
+    .run()
+    .await?;
+    Ok(())
+}
+
+async fn get_status(status: web::Data<SchedulerStatus>) -> HttpResponse {
+    HttpResponse::Ok().json(status.jobs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    fn recording_job(
+        name: &'static str,
+        log: &Rc<RefCell<Vec<&'static str>>>,
+    ) -> impl Fn() -> LocalBoxFuture<'static, Result<()>> {
+        let log = log.clone();
+        move || {
+            let log = log.clone();
+            async move {
+                log.borrow_mut().push(name);
+                if name == "failing" {
+                    anyhow::bail!("failed");
+                }
+                Ok(())
+            }
+            .boxed_local()
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_scheduler_runs_jobs_by_priority() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut scheduler = Scheduler::new();
+        let hour = Duration::from_secs(3600);
+        scheduler.add_job("low", JobConfig::new(hour, 1), recording_job("low", &log));
+        scheduler.add_job(
+            "high",
+            JobConfig::new(hour, 10),
+            recording_job("high", &log),
+        );
+        scheduler.add_job(
+            "failing",
+            JobConfig::new(hour, 5),
+            recording_job("failing", &log),
+        );
+
+        while scheduler.run_once().await {}
+        assert_eq!(*log.borrow(), vec!["high", "failing", "low"]);
+
+        // Nothing is due until the interval passes.
+        assert!(!scheduler.run_once().await);
+
+        let status = scheduler.status().jobs();
+        assert_eq!(status.len(), 3);
+        assert!(status.iter().all(|s| s.runs == 1 && !s.running));
+        assert_eq!(status[2].failures, 1);
+        assert!(status[2].last_error.as_ref().unwrap().contains("failed"));
+        assert_eq!(status[0].last_error, None);
+    }
+
+    #[actix_web::test]
+    async fn test_status_endpoint() {
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(
+            "job",
+            JobConfig::new(Duration::from_secs(60), 3),
+            || async { Ok(()) },
+        );
+        scheduler.run_once().await;
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(web::Data::new(scheduler.status()))
+                .route("/status", web::get().to(get_status)),
+        )
+        .await;
+        let request = actix_web::test::TestRequest::get()
+            .uri("/status")
+            .to_request();
+        let jobs: Vec<serde_json::Value> =
+            actix_web::test::call_and_read_body_json(&app, request).await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0]["name"], "job");
+        assert_eq!(jobs[0]["interval_second"], 60);
+        assert_eq!(jobs[0]["runs"], 1);
+    }
+
+    #[actix_web::test]
+    async fn test_scheduler_runs_jobs_concurrently() {
+        let released = Rc::new(Cell::new(false));
+        let mut scheduler = Scheduler::new().with_max_concurrent_jobs(2);
+        let hour = Duration::from_secs(3600);
+        let waiting = released.clone();
+        scheduler.add_job("waiting", JobConfig::new(hour, 10), move || {
+            let waiting = waiting.clone();
+            async move {
+                while !waiting.get() {
+                    actix_web::rt::time::sleep(Duration::from_millis(1)).await;
+                }
+                Ok(())
+            }
+        });
+        let releasing = released.clone();
+        scheduler.add_job("releasing", JobConfig::new(hour, 1), move || {
+            releasing.set(true);
+            async { Ok(()) }
+        });
+
+        let status = scheduler.status();
+        let run = actix_web::rt::time::timeout(Duration::from_secs(1), scheduler.run());
+        assert!(run.await.is_err());
+        assert!(status.jobs().iter().all(|s| s.runs == 1 && !s.running));
+    }
+
+    #[test]
+    fn test_next_job_ages_overdue_jobs() {
+        let mut scheduler = Scheduler::new();
+        let hour = Duration::from_secs(3600);
+        scheduler.add_job("low", JobConfig::new(hour, 1), || async { Ok(()) });
+        scheduler.add_job("high", JobConfig::new(hour, 10), || async { Ok(()) });
+
+        let now = Instant::now();
+        scheduler.jobs[0].next_run = now;
+        scheduler.jobs[1].next_run = now;
+        assert_eq!(scheduler.next_job(now), Some(1));
+
+        scheduler.jobs[0].next_run = now - AGING_PERIOD * 10;
+        assert_eq!(scheduler.next_job(now), Some(0));
+
+        scheduler.jobs[0].running = true;
+        assert_eq!(scheduler.next_job(now), Some(1));
+    }
+
+    #[test]
+    fn test_job_config_from_vars() {
+        let default = JobConfig::new(Duration::from_secs(60), 3);
+        let vars = HashMap::from([
+            ("TEST_JOB_INTERVAL_SECONDS", "10"),
+            ("TEST_JOB_PRIORITY", "invalid"),
+        ]);
+        let get = |key: &str| vars.get(key).map(|value| value.to_string());
+        assert_eq!(
+            JobConfig::from_vars("test_job", default, get),
+            JobConfig::new(Duration::from_secs(10), 3)
+        );
+        assert_eq!(JobConfig::from_vars("other", default, get), default);
+    }
+}
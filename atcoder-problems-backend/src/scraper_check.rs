@@ -0,0 +1,454 @@
+//! Detects breakages of the scrapers in `atcoder_client` caused by changes of AtCoder's markup,
+//! by running them against live pages and comparing the results with recorded fixtures.
+
+use atcoder_client::{
+    AtCoderClient, AtCoderContest, AtCoderProblem, AtCoderStandingsRow,
+    AtCoderSubmissionListResponse, AtCoderUserHistory, ContestTypeSpecifier, SubmissionListFilter,
+};
+use serde::Serialize;
+
+/// Contest and page whose recorded fixtures are used as the reference.
+const CONTEST_ID: &str = "abc107";
+/// User whose rating history is recorded, who has taken part in `CONTEST_ID`.
+const USER_ID: &str = "kenkoooo";
+
+/// 2012-01-01T00:00:00Z, which is before the first contest on AtCoder.
+const MIN_EPOCH_SECOND: u64 = 1325376000;
+const MAX_FUTURE_SECOND: u64 = 365 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct ScraperCheckReport {
+    pub passed: bool,
+    pub checked_epoch_second: u64,
+    pub checks: Vec<ScraperCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScraperCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Number of items scraped from the live page.
+    pub items: usize,
+    pub violations: Vec<String>,
+}
+
+/// Runs every HTML scraper against `live`, which usually sends requests to AtCoder,
+/// and `fixture`, which serves the recorded pages.
+pub async fn check_scrapers(
+    live: &AtCoderClient,
+    fixture: &AtCoderClient,
+    now: u64,
+) -> ScraperCheckReport {
+    let spf = || ContestTypeSpecifier::Normal { page: 1 };
+    let normal_contests = check(
+        "contests_normal",
+        live.fetch_atcoder_contests(spf()).await,
+        fixture.fetch_atcoder_contests(spf()).await,
+        |contests| validate_contests(contests, now, true),
+        |live, fixture| compare_count(live.len(), fixture.len()),
+    );
+
+    let spf = || ContestTypeSpecifier::Permanent;
+    let permanent_contests = check(
+        "contests_permanent",
+        live.fetch_atcoder_contests(spf()).await,
+        fixture.fetch_atcoder_contests(spf()).await,
+        |contests| validate_contests(contests, now, false),
+        |_, _| Vec::new(),
+    );
+
+    // There may be no upcoming contests, and then the table is omitted.
+    let spf = || ContestTypeSpecifier::Upcoming;
+    let upcoming_contests = check(
+        "contests_upcoming",
+        live.fetch_atcoder_contests(spf()).await,
+        fixture.fetch_atcoder_contests(spf()).await,
+        |contests| {
+            if contests.is_empty() {
+                Vec::new()
+            } else {
+                validate_contests(contests, now, true)
+            }
+        },
+        |_, _| Vec::new(),
+    );
+
+    let contest = check(
+        "contest",
+        live.fetch_contest_detail(CONTEST_ID).await.map(|c| vec![c]),
+        fixture
+            .fetch_contest_detail(CONTEST_ID)
+            .await
+            .map(|c| vec![c]),
+        |contest| validate_contests(contest, now, true),
+        |live, fixture| compare_contests(live, fixture),
+    );
+
+    let problems = check(
+        "problems",
+        live.fetch_problem_list(CONTEST_ID).await,
+        fixture.fetch_problem_list(CONTEST_ID).await,
+        |problems| validate_problems(problems),
+        |live, fixture| compare_problems(live, fixture),
+    );
+
+    let filter = SubmissionListFilter::default();
+    let submissions = check(
+        "submissions",
+        live.fetch_atcoder_submission_list(CONTEST_ID, Some(1), &filter)
+            .await,
+        fixture
+            .fetch_atcoder_submission_list(CONTEST_ID, Some(1), &filter)
+            .await,
+        |response| validate_submissions(response, now),
+        compare_submissions,
+    );
+
+    let standings = check(
+        "standings",
+        live.fetch_contest_standings(CONTEST_ID).await,
+        fixture.fetch_contest_standings(CONTEST_ID).await,
+        |rows| validate_standings(rows),
+        |live, fixture| compare_growing(live.len(), fixture.len()),
+    );
+
+    let history = check(
+        "rating_history",
+        live.fetch_user_history(USER_ID).await,
+        fixture.fetch_user_history(USER_ID).await,
+        |history| validate_history(history, now),
+        |live, fixture| compare_growing(live.len(), fixture.len()),
+    );
+
+    let checks = vec![
+        normal_contests,
+        permanent_contests,
+        upcoming_contests,
+        contest,
+        problems,
+        submissions,
+        standings,
+        history,
+    ];
+    ScraperCheckReport {
+        passed: checks.iter().all(|c| c.passed),
+        checked_epoch_second: now,
+        checks,
+    }
+}
+
+trait Items {
+    fn items(&self) -> usize;
+}
+
+impl<T> Items for Vec<T> {
+    fn items(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Items for AtCoderSubmissionListResponse {
+    fn items(&self) -> usize {
+        self.submissions.len()
+    }
+}
+
+fn check<T: Items>(
+    name: &'static str,
+    live: atcoder_client::Result<T>,
+    fixture: atcoder_client::Result<T>,
+    validate: impl Fn(&T) -> Vec<String>,
+    compare: impl Fn(&T, &T) -> Vec<String>,
+) -> ScraperCheck {
+    let mut violations = Vec::new();
+    let fixture = match fixture {
+        Ok(fixture) => {
+            let fixture_violations = validate(&fixture);
+            violations.extend(
+                fixture_violations
+                    .into_iter()
+                    .map(|v| format!("fixture: {}", v)),
+            );
+            Some(fixture)
+        }
+        Err(e) => {
+            violations.push(format!("fixture: {}", e));
+            None
+        }
+    };
+
+    let items = match live {
+        Ok(live) => {
+            violations.extend(validate(&live));
+            if let Some(fixture) = fixture.as_ref() {
+                violations.extend(compare(&live, fixture));
+            }
+            live.items()
+        }
+        Err(e) => {
+            violations.push(e.to_string());
+            0
+        }
+    };
+
+    ScraperCheck {
+        name,
+        passed: violations.is_empty(),
+        items,
+        violations,
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn is_sane_epoch_second(epoch_second: u64, now: u64) -> bool {
+    (MIN_EPOCH_SECOND..=now + MAX_FUTURE_SECOND).contains(&epoch_second)
+}
+
+fn validate_contests(contests: &[AtCoderContest], now: u64, has_duration: bool) -> Vec<String> {
+    let mut violations = Vec::new();
+    if contests.is_empty() {
+        violations.push("no contests".to_string());
+    }
+    for (i, contest) in contests.iter().enumerate() {
+        if !is_valid_id(&contest.id) {
+            violations.push(format!("row {}: invalid id {:?}", i, contest.id));
+        }
+        if contest.title.trim().is_empty() {
+            violations.push(format!("row {}: empty title", i));
+        }
+        if has_duration {
+            if !is_sane_epoch_second(contest.start_epoch_second, now) {
+                violations.push(format!(
+                    "row {}: start_epoch_second {} is out of range",
+                    i, contest.start_epoch_second
+                ));
+            }
+            if contest.duration_second == 0 {
+                violations.push(format!("row {}: no duration", i));
+            }
+        }
+    }
+    violations
+}
+
+fn validate_problems(problems: &[AtCoderProblem]) -> Vec<String> {
+    let mut violations = Vec::new();
+    if problems.is_empty() {
+        violations.push("no problems".to_string());
+    }
+    for (i, problem) in problems.iter().enumerate() {
+        if !is_valid_id(&problem.id) {
+            violations.push(format!("row {}: invalid id {:?}", i, problem.id));
+        }
+        if problem.contest_id != CONTEST_ID {
+            violations.push(format!(
+                "row {}: contest_id {:?} doesn't match",
+                i, problem.contest_id
+            ));
+        }
+        if problem.position.is_empty() || problem.title.trim().is_empty() {
+            violations.push(format!("row {}: empty position or title", i));
+        }
+    }
+    violations
+}
+
+fn validate_submissions(response: &AtCoderSubmissionListResponse, now: u64) -> Vec<String> {
+    let mut violations = Vec::new();
+    if response.submissions.is_empty() {
+        violations.push("no submissions".to_string());
+    }
+    if response.max_page == 0 {
+        violations.push("max_page is 0".to_string());
+    }
+    for (i, submission) in response.submissions.iter().enumerate() {
+        if submission.id == 0 {
+            violations.push(format!("row {}: id is 0", i));
+        }
+        if !is_valid_id(&submission.user_id) || !is_valid_id(&submission.problem_id) {
+            violations.push(format!("row {}: invalid user_id or problem_id", i));
+        }
+        if submission.contest_id != CONTEST_ID {
+            violations.push(format!(
+                "row {}: contest_id {:?} doesn't match",
+                i, submission.contest_id
+            ));
+        }
+        if !is_sane_epoch_second(submission.epoch_second, now) {
+            violations.push(format!(
+                "row {}: epoch_second {} is out of range",
+                i, submission.epoch_second
+            ));
+        }
+    }
+    violations
+}
+
+fn validate_standings(rows: &[AtCoderStandingsRow]) -> Vec<String> {
+    let mut violations = Vec::new();
+    if rows.is_empty() {
+        violations.push("no rows".to_string());
+    }
+    for (i, row) in rows.iter().enumerate() {
+        if !is_valid_id(&row.user_id) {
+            violations.push(format!("row {}: invalid user_id {:?}", i, row.user_id));
+        }
+        if row.rank == 0 {
+            violations.push(format!("row {}: rank is 0", i));
+        }
+        if row.task_results.iter().any(|t| !is_valid_id(&t.problem_id)) {
+            violations.push(format!("row {}: invalid problem_id", i));
+        }
+    }
+    violations
+}
+
+fn validate_history(history: &[AtCoderUserHistory], now: u64) -> Vec<String> {
+    let mut violations = Vec::new();
+    if history.is_empty() {
+        violations.push("no history".to_string());
+    }
+    for (i, h) in history.iter().enumerate() {
+        if !is_valid_id(&h.contest_id) {
+            violations.push(format!("row {}: invalid contest_id {:?}", i, h.contest_id));
+        }
+        if !is_sane_epoch_second(h.end_epoch_second, now) {
+            violations.push(format!(
+                "row {}: end_epoch_second {} is out of range",
+                i, h.end_epoch_second
+            ));
+        }
+    }
+    if !history.iter().any(|h| h.contest_id == CONTEST_ID) {
+        violations.push(format!("{} is missing", CONTEST_ID));
+    }
+    violations
+}
+
+/// Pages which keep growing should still have as many rows as the recorded ones.
+fn compare_count(live: usize, fixture: usize) -> Vec<String> {
+    if live == fixture {
+        Vec::new()
+    } else {
+        vec![format!("{} rows while the fixture has {}", live, fixture)]
+    }
+}
+
+/// Pages which may grow, e.g. with late participants or new contests, shouldn't shrink.
+fn compare_growing(live: usize, fixture: usize) -> Vec<String> {
+    if live >= fixture {
+        Vec::new()
+    } else {
+        vec![format!("{} rows while the fixture has {}", live, fixture)]
+    }
+}
+
+/// A past contest is never rescheduled.
+fn compare_contests(live: &[AtCoderContest], fixture: &[AtCoderContest]) -> Vec<String> {
+    if live == fixture {
+        Vec::new()
+    } else {
+        vec![format!(
+            "contest {:?} doesn't match the fixture {:?}",
+            live, fixture
+        )]
+    }
+}
+
+/// The problems of a past contest never change.
+fn compare_problems(live: &[AtCoderProblem], fixture: &[AtCoderProblem]) -> Vec<String> {
+    let ids = |problems: &[AtCoderProblem]| {
+        problems
+            .iter()
+            .map(|p| (p.id.clone(), p.position.clone()))
+            .collect::<Vec<_>>()
+    };
+    if ids(live) == ids(fixture) {
+        Vec::new()
+    } else {
+        vec![format!(
+            "problems {:?} don't match the fixture {:?}",
+            ids(live),
+            ids(fixture)
+        )]
+    }
+}
+
+fn compare_submissions(
+    live: &AtCoderSubmissionListResponse,
+    fixture: &AtCoderSubmissionListResponse,
+) -> Vec<String> {
+    let mut violations = compare_count(live.submissions.len(), fixture.submissions.len());
+    if live.max_page < fixture.max_page {
+        violations.push(format!(
+            "max_page {} is less than {} of the fixture",
+            live.max_page, fixture.max_page
+        ));
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atcoder_client::transport::ReplayTransport;
+    use atcoder_client::RateLimitConfig;
+
+    fn replay_client() -> AtCoderClient {
+        let dir = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/atcoder-client/test_resources/replay"
+        );
+        AtCoderClient::with_transport(ReplayTransport::new(dir), RateLimitConfig::default())
+    }
+
+    #[actix_web::test]
+    async fn test_check_scrapers_against_fixtures() {
+        let client = replay_client();
+        let report = check_scrapers(&client, &client, 1700000000).await;
+        assert!(report.passed, "{:?}", report);
+        assert_eq!(report.checks.len(), 8);
+        assert!(report.checks.iter().all(|c| c.items > 0));
+    }
+
+    #[actix_web::test]
+    async fn test_check_scrapers_broken_pages() {
+        let config = RateLimitConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let live =
+            AtCoderClient::with_transport(ReplayTransport::new(env!("CARGO_MANIFEST_DIR")), config);
+        let report = check_scrapers(&live, &replay_client(), 1700000000).await;
+        assert!(!report.passed);
+        assert!(report.checks.iter().all(|c| !c.passed && c.items == 0));
+    }
+
+    #[test]
+    fn test_validate_contests() {
+        let contest = |id: &str, start_epoch_second: u64| AtCoderContest {
+            id: id.to_string(),
+            start_epoch_second,
+            duration_second: 6000,
+            title: "title".to_string(),
+            rate_change: "-".to_string(),
+        };
+        let now = 1700000000;
+        assert!(validate_contests(&[contest("abc107", now)], now, true).is_empty());
+        assert_eq!(validate_contests(&[], now, true), vec!["no contests"]);
+        assert_eq!(
+            validate_contests(&[contest("", now), contest("abc107", 0)], now, true),
+            vec![
+                "row 0: invalid id \"\"",
+                "row 1: start_epoch_second 0 is out of range"
+            ]
+        );
+        assert!(validate_contests(&[contest("abc107", 0)], now, false).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use actix_web::{error, get, post, web, HttpResponse, Result};
+use serde::Deserialize;
+use sql_client::{
+    hidden_contest::HiddenContestClient, internal::user_manager::UserManager,
+    models::HiddenContest, PgPool,
+};
+
+use crate::crawler::validate_hidden_contest_row;
+use crate::server::{error::ApiResult, middleware::github_auth::GithubToken};
+
+async fn ensure_admin(pool: &PgPool, token: &GithubToken) -> Result<()> {
+    let is_admin = pool
+        .is_admin(&token.id.to_string())
+        .await
+        .map_internal_server_err()?;
+    if is_admin {
+        Ok(())
+    } else {
+        Err(error::ErrorForbidden(
+            "Only admins can manage hidden contests.",
+        ))
+    }
+}
+
+#[get("/internal-api/hidden_contests/list")]
+pub async fn get_hidden_contests(
+    token: web::ReqData<GithubToken>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse> {
+    ensure_admin(&pool, &token).await?;
+    let contests = pool
+        .load_hidden_contests()
+        .await
+        .map_internal_server_err()?;
+    Ok(HttpResponse::Ok().json(&contests))
+}
+
+#[post("/internal-api/hidden_contests/update")]
+pub async fn update_hidden_contests(
+    token: web::ReqData<GithubToken>,
+    pool: web::Data<PgPool>,
+    body: web::Json<Vec<HiddenContest>>,
+) -> Result<HttpResponse> {
+    ensure_admin(&pool, &token).await?;
+    let errors = body
+        .iter()
+        .filter_map(|contest| validate_hidden_contest_row(contest).err())
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(&errors));
+    }
+    pool.update_hidden_contests(&body)
+        .await
+        .map_internal_server_err()?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    contest_id: String,
+}
+
+#[post("/internal-api/hidden_contests/delete")]
+pub async fn delete_hidden_contest(
+    token: web::ReqData<GithubToken>,
+    pool: web::Data<PgPool>,
+    query: web::Json<DeleteQuery>,
+) -> Result<HttpResponse> {
+    ensure_admin(&pool, &token).await?;
+    pool.delete_hidden_contest(&query.contest_id)
+        .await
+        .map_internal_server_err()?;
+    Ok(HttpResponse::Ok().finish())
+}
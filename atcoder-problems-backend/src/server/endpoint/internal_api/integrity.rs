@@ -0,0 +1,11 @@
+use actix_web::{get, web, HttpResponse, Result};
+use sql_client::{orphan::OrphanClient, PgPool};
+
+use crate::server::error::ApiResult;
+
+/// Lists the problems referenced by submissions which are still missing in the problem tables.
+#[get("/internal-api/integrity/orphans")]
+pub async fn get_orphans(pool: web::Data<PgPool>) -> Result<HttpResponse> {
+    let orphans = pool.load_orphans().await.map_internal_server_err()?;
+    Ok(HttpResponse::Ok().json(&orphans))
+}
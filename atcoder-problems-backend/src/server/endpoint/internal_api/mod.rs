@@ -1,6 +1,9 @@
 pub mod contest;
+pub mod hidden_contest;
+pub mod integrity;
 pub mod list;
 pub mod progress_reset;
+pub mod refresh;
 pub mod user;
 
 use actix_web::{cookie::Cookie, get, web, HttpResponse, Result};
@@ -0,0 +1,68 @@
+use actix_web::{error, get, http::header, post, web, HttpResponse, Result};
+use chrono::Utc;
+use sql_client::{
+    crawl_request::{CrawlRequestClient, CRAWL_REQUEST_QUEUED, CRAWL_REQUEST_RUNNING},
+    internal::user_manager::UserManager,
+    PgPool,
+};
+
+use crate::server::{error::ApiResult, middleware::github_auth::GithubToken};
+
+/// Minimum interval between requests of a user.
+const REFRESH_COOLDOWN_SECOND: i64 = 5 * 60;
+
+async fn get_atcoder_user_id(pool: &PgPool, token: &GithubToken) -> Result<String> {
+    let info = pool
+        .get_internal_user_info(&token.id.to_string())
+        .await
+        .map_internal_server_err()?;
+    match info.atcoder_user_id {
+        Some(atcoder_user_id) if !atcoder_user_id.is_empty() => Ok(atcoder_user_id),
+        _ => Err(error::ErrorBadRequest("AtCoder ID is not registered.")),
+    }
+}
+
+/// Enqueues a request to crawl the submissions of the AtCoder user linked to the login user.
+/// Returns the pending request instead if there is already one.
+#[post("/internal-api/refresh")]
+pub async fn request_refresh(
+    token: web::ReqData<GithubToken>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse> {
+    let atcoder_user_id = get_atcoder_user_id(&pool, &token).await?;
+    let latest = pool
+        .get_latest_crawl_request(&atcoder_user_id)
+        .await
+        .map_internal_server_err()?;
+    if let Some(latest) = latest {
+        if latest.status == CRAWL_REQUEST_QUEUED || latest.status == CRAWL_REQUEST_RUNNING {
+            return Ok(HttpResponse::Ok().json(&latest));
+        }
+        let wait = latest.requested_epoch_second + REFRESH_COOLDOWN_SECOND - Utc::now().timestamp();
+        if wait > 0 {
+            return Ok(HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, wait.to_string()))
+                .json(&latest));
+        }
+    }
+
+    let request = pool
+        .insert_crawl_request(&atcoder_user_id)
+        .await
+        .map_internal_server_err()?;
+    Ok(HttpResponse::Ok().json(&request))
+}
+
+/// Returns the latest request of the login user, or `null` if there is none.
+#[get("/internal-api/refresh/status")]
+pub async fn get_refresh_status(
+    token: web::ReqData<GithubToken>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse> {
+    let atcoder_user_id = get_atcoder_user_id(&pool, &token).await?;
+    let latest = pool
+        .get_latest_crawl_request(&atcoder_user_id)
+        .await
+        .map_internal_server_err()?;
+    Ok(HttpResponse::Ok().json(&latest))
+}
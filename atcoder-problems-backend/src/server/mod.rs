@@ -3,6 +3,7 @@ pub mod error;
 pub(crate) mod language_count;
 pub mod middleware;
 pub(crate) mod ranking;
+pub(crate) mod revisions;
 pub(crate) mod services;
 pub(crate) mod time_submissions;
 pub(crate) mod user_info;
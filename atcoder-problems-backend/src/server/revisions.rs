@@ -0,0 +1,38 @@
+use crate::server::MakeCors;
+use actix_web::{error, web, HttpResponse, Result};
+use serde::Deserialize;
+use sql_client::{revision::RevisionClient, PgPool};
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct GetContestRevisionQuery {
+    contest_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct GetProblemRevisionQuery {
+    problem_id: String,
+}
+
+pub(crate) async fn get_contest_revisions(
+    pool: web::Data<PgPool>,
+    query: web::Query<GetContestRevisionQuery>,
+) -> Result<HttpResponse> {
+    let revisions = pool
+        .load_contest_revisions(&query.contest_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let response = HttpResponse::Ok().make_cors().json(&revisions);
+    Ok(response)
+}
+
+pub(crate) async fn get_problem_revisions(
+    pool: web::Data<PgPool>,
+    query: web::Query<GetProblemRevisionQuery>,
+) -> Result<HttpResponse> {
+    let revisions = pool
+        .load_problem_revisions(&query.problem_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let response = HttpResponse::Ok().make_cors().json(&revisions);
+    Ok(response)
+}
@@ -7,6 +7,7 @@ use crate::server::{
         AcRanking, LanguageRanking, RankingSelector, RatedPointSumRanking, StreakRanking,
         UserRankSelector,
     },
+    revisions::{get_contest_revisions, get_problem_revisions},
     time_submissions::get_time_submissions,
     user_info::get_user_info,
     user_submissions::get_user_submission_count,
@@ -41,6 +42,12 @@ pub fn config_services(cfg: &mut web::ServiceConfig) {
         .service(endpoint::internal_api::progress_reset::get_progress_reset_list)
         .service(endpoint::internal_api::progress_reset::add_progress_reset_item)
         .service(endpoint::internal_api::progress_reset::delete_progress_reset_item)
+        .service(endpoint::internal_api::hidden_contest::get_hidden_contests)
+        .service(endpoint::internal_api::hidden_contest::update_hidden_contests)
+        .service(endpoint::internal_api::hidden_contest::delete_hidden_contest)
+        .service(endpoint::internal_api::refresh::request_refresh)
+        .service(endpoint::internal_api::refresh::get_refresh_status)
+        .service(endpoint::internal_api::integrity::get_orphans)
         .service(
             web::scope("/atcoder-api")
                 .service(web::resource("/results").route(web::get().to(get_user_submissions)))
@@ -112,6 +119,14 @@ pub fn config_services(cfg: &mut web::ServiceConfig) {
                         )
                         .service(
                             web::resource("language_list").route(web::get().to(get_language_list)),
+                        )
+                        .service(
+                            web::resource("/contest_revisions")
+                                .route(web::get().to(get_contest_revisions)),
+                        )
+                        .service(
+                            web::resource("/problem_revisions")
+                                .route(web::get().to(get_problem_revisions)),
                         ),
                 ),
         )
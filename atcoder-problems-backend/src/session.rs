@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use atcoder_client::{AtCoderClient, RateLimitConfig};
+use log::{info, warn};
+use sql_client::session::SessionClient;
+use std::fmt;
+
+/// Account of AtCoder used by crawlers.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credential {
+    pub username: String,
+    pub password: String,
+}
+
+/// The password is redacted not to be leaked into logs.
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credential")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Parses a list of accounts written as `<username>:<password>` in each line.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_credentials(text: &str) -> Result<Vec<Credential>> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            let (username, password) = line
+                .split_once(':')
+                .with_context(|| format!("Line {} is not <username>:<password>", i + 1))?;
+            Ok(Credential {
+                username: username.to_string(),
+                password: password.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Creates a client with the session saved in the database,
+/// and logs in again only if the session has expired.
+pub async fn login<C: SessionClient>(
+    db: &C,
+    username: &str,
+    password: &str,
+) -> Result<AtCoderClient> {
+    let client = AtCoderClient::without_login(RateLimitConfig::default())?;
+    restore(db, client, username, password).await
+}
+
+/// Same as `login`, but the client shares the rate limiter of `base` with a session of its own.
+pub async fn login_sharing_limit<C: SessionClient>(
+    db: &C,
+    base: &AtCoderClient,
+    username: &str,
+    password: &str,
+) -> Result<AtCoderClient> {
+    restore(db, base.with_new_session()?, username, password).await
+}
+
+async fn restore<C: SessionClient>(
+    db: &C,
+    client: AtCoderClient,
+    username: &str,
+    password: &str,
+) -> Result<AtCoderClient> {
+    match db.load_atcoder_session(username).await {
+        Ok(Some(session)) => {
+            if let Err(e) = client.restore_session(&session) {
+                warn!("Failed to restore the session of {}: {:?}", username, e);
+            }
+        }
+        Ok(None) => info!("No session of {} is saved.", username),
+        Err(e) => warn!("Failed to load the session of {}: {:?}", username, e),
+    }
+    refresh(db, &client, username, password).await?;
+    Ok(client)
+}
+
+/// Logs in again if the session of `client` has expired, and saves the new session.
+pub async fn refresh<C: SessionClient>(
+    db: &C,
+    client: &AtCoderClient,
+    username: &str,
+    password: &str,
+) -> Result<()> {
+    if client.ensure_logged_in(username, password).await? {
+        db.save_atcoder_session(username, &client.export_session()?)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_credentials() {
+        let text = "# crawler accounts\nuser1:pass:word\n\n  user2:pass2  \n";
+        assert_eq!(
+            parse_credentials(text).unwrap(),
+            vec![
+                Credential {
+                    username: "user1".to_string(),
+                    password: "pass:word".to_string(),
+                },
+                Credential {
+                    username: "user2".to_string(),
+                    password: "pass2".to_string(),
+                },
+            ]
+        );
+        assert!(parse_credentials("user1:pass1\nuser2").is_err());
+    }
+
+    #[test]
+    fn test_credential_debug_redacts_password() {
+        let credential = Credential {
+            username: "user1".to_string(),
+            password: "secret".to_string(),
+        };
+        let debug = format!("{:?}", credential);
+        assert!(debug.contains("user1"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+const DEFAULT_JSONL_DIR: &str = "crawled";
+
+/// Where crawler binaries write the crawled records, which is chosen by `--sink`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    Postgres,
+    /// Appends JSON Lines to the directory of `--out`, which can be loaded later by `import_jsonl`.
+    Jsonl(PathBuf),
+}
+
+impl Sink {
+    /// Takes `--sink <postgres|jsonl>` and `--out <dir>` out of the arguments,
+    /// and leaves the rest for the binary.
+    pub fn from_args(args: &mut Vec<String>) -> Result<Self> {
+        let sink = take_option(args, "--sink")?;
+        let out = take_option(args, "--out")?;
+        match (sink.as_deref(), out) {
+            (None | Some("postgres"), None) => Ok(Sink::Postgres),
+            (Some("jsonl"), out) => Ok(Sink::Jsonl(
+                out.unwrap_or_else(|| DEFAULT_JSONL_DIR.to_string()).into(),
+            )),
+            (None | Some("postgres"), Some(_)) => bail!("--out is only for --sink jsonl"),
+            (Some(sink), _) => bail!("Unknown sink {}, which must be postgres or jsonl", sink),
+        }
+    }
+}
+
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    let i = match args.iter().position(|arg| arg == name) {
+        Some(i) => i,
+        None => return Ok(None),
+    };
+    args.remove(i);
+    let value = (i < args.len())
+        .then(|| args.remove(i))
+        .with_context(|| format!("{} needs a value", name))?;
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sink_from_args() {
+        let mut rest = args(&["abc001", "--reconcile"]);
+        assert_eq!(Sink::from_args(&mut rest).unwrap(), Sink::Postgres);
+        assert_eq!(rest, args(&["abc001", "--reconcile"]));
+
+        let mut rest = args(&["--sink", "jsonl", "abc001"]);
+        assert_eq!(
+            Sink::from_args(&mut rest).unwrap(),
+            Sink::Jsonl(DEFAULT_JSONL_DIR.into())
+        );
+        assert_eq!(rest, args(&["abc001"]));
+
+        let mut rest = args(&["abc001", "--out", "/tmp/out", "--sink", "jsonl"]);
+        assert_eq!(
+            Sink::from_args(&mut rest).unwrap(),
+            Sink::Jsonl("/tmp/out".into())
+        );
+        assert_eq!(rest, args(&["abc001"]));
+
+        assert!(Sink::from_args(&mut args(&["--sink", "mysql"])).is_err());
+        assert!(Sink::from_args(&mut args(&["--sink"])).is_err());
+        assert!(Sink::from_args(&mut args(&["--out", "/tmp/out"])).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::crawler::Reconciliation;
+use crate::utils::EXCLUDED_USERS;
+use anyhow::Result;
+use log::info;
+use sql_client::accepted_count::AcceptedCountClient;
+use sql_client::language_count::LanguageCountClient;
+use sql_client::models::Submission;
+use sql_client::orphan::OrphanClient;
+use sql_client::problem_info::ProblemInfoUpdater;
+use sql_client::problems_submissions::ProblemsSubmissionUpdater;
+use sql_client::rated_point_sum::RatedPointSumClient;
+use sql_client::streak::StreakClient;
+use sql_client::submission_client::{SubmissionClient, SubmissionRequest};
+use sql_client::user_statistics::UserStatisticsClient;
+use sql_client::PgPool;
+use std::collections::BTreeSet;
+
+/// Recalculates all the statistics from all the accepted submissions.
+pub async fn batch_update(conn: &PgPool) -> Result<()> {
+    info!("Loading submissions ...");
+    let mut all_accepted_submissions: Vec<Submission> =
+        conn.get_submissions(SubmissionRequest::AllAccepted).await?;
+
+    info!("Filter submission by user_id ...");
+    all_accepted_submissions = all_accepted_submissions
+        .into_iter()
+        .filter(|submission| !EXCLUDED_USERS.contains(&submission.user_id.as_str()))
+        .collect::<Vec<_>>();
+
+    info!(
+        "There are {} AC submissions.",
+        all_accepted_submissions.len()
+    );
+
+    info!("Sorting by id ...");
+    all_accepted_submissions.sort_by_key(|s| s.id);
+
+    info!("Executing update_accepted_count...");
+    conn.update_accepted_count(&all_accepted_submissions)
+        .await?;
+
+    info!("Executing update_problem_solver_count...");
+    conn.update_solver_count().await?;
+
+    info!("Executing update_rated_point_sums...");
+    conn.update_rated_point_sum(&all_accepted_submissions)
+        .await?;
+
+    let current_count = conn.load_language_count().await?;
+    info!("Executing update_language_count...");
+    conn.update_language_count(&all_accepted_submissions, &current_count)
+        .await?;
+
+    info!("Executing update_submissions_of_problems...");
+    conn.update_submissions_of_problems().await?;
+
+    info!("Executing update_problem_points...");
+    conn.update_problem_points().await?;
+
+    info!("Executing update_streak_count...");
+    conn.update_streak_count(&all_accepted_submissions).await?;
+
+    // The crawlers record the orphans of each batch they store, and this catches what they miss.
+    info!("Executing record_orphans...");
+    let orphans = conn.record_orphans(0).await?;
+    info!("There are {} orphaned problems.", orphans);
+
+    Ok(())
+}
+
+/// Recalculates the statistics of the users who have recently got accepted.
+pub async fn delta_update(conn: &PgPool) -> Result<()> {
+    info!("Loading submissions ...");
+    let request = SubmissionRequest::RecentAccepted { count: 200 };
+    let recent_submissions = conn.get_submissions(request).await?;
+
+    info!("Filter submission by user_id ...");
+
+    let user_ids = recent_submissions
+        .into_iter()
+        .filter(|submission| !EXCLUDED_USERS.contains(&submission.user_id.as_str()))
+        .map(|s| s.user_id)
+        .collect::<BTreeSet<_>>();
+    let user_ids = user_ids.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+    info!("Loading submissions of {} users ...", user_ids.len());
+    let request = SubmissionRequest::UsersAccepted {
+        user_ids: &user_ids,
+    };
+    let mut user_accepted_submissions = conn.get_submissions(request).await?;
+    info!("There are {} submissions.", user_accepted_submissions.len());
+
+    info!("Sorting by id ...");
+    user_accepted_submissions.sort_by_key(|s| s.id);
+
+    info!("Executing update_rated_point_sum...");
+    conn.update_rated_point_sum(&user_accepted_submissions)
+        .await?;
+
+    info!("Executing update_accepted_count...");
+    conn.update_accepted_count(&user_accepted_submissions)
+        .await?;
+
+    info!("Executing update_language_count...");
+    conn.update_language_count(&user_accepted_submissions, &[])
+        .await?;
+
+    info!("Executing update_streak_count...");
+    conn.update_streak_count(&user_accepted_submissions).await?;
+
+    Ok(())
+}
+
+/// Recalculates the statistics affected by rejudged or vanished submissions.
+/// Unlike `delta_update`, the statistics of the users are calculated from scratch,
+/// since the updates never remove what the submissions have counted before.
+pub async fn reconciliation_update(conn: &PgPool, reconciliation: &Reconciliation) -> Result<()> {
+    let user_ids = reconciliation
+        .affected_user_ids()
+        .into_iter()
+        .filter(|user_id| !EXCLUDED_USERS.contains(user_id))
+        .collect::<Vec<_>>();
+
+    let request = SubmissionRequest::UsersAccepted {
+        user_ids: &user_ids,
+    };
+    let mut user_accepted_submissions = conn.get_submissions(request).await?;
+    info!("There are {} submissions.", user_accepted_submissions.len());
+    user_accepted_submissions.sort_by_key(|s| s.id);
+
+    info!("Replacing statistics of {} users ...", user_ids.len());
+    conn.replace_user_statistics(&user_ids, &user_accepted_submissions)
+        .await?;
+
+    info!("Executing update_solver_count_of_problems...");
+    conn.update_solver_count_of_problems(&reconciliation.affected_problem_ids())
+        .await?;
+
+    let submission_ids = reconciliation.affected_submission_ids();
+    if !submission_ids.is_empty() {
+        info!("Executing update_submissions_of_problems...");
+        conn.delete_submissions_of_problems(&submission_ids).await?;
+        conn.update_submissions_of_problems().await?;
+    }
+
+    Ok(())
+}
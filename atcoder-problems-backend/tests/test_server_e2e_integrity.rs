@@ -0,0 +1,42 @@
+use actix_web::{test, web, App};
+use atcoder_problems_backend::server::config_services;
+use serde_json::{json, Value};
+use sql_client::models::Submission;
+use sql_client::orphan::OrphanClient;
+use sql_client::submission_client::SubmissionClient;
+
+pub mod utils;
+
+#[actix_web::test]
+async fn test_orphans() {
+    let conn = utils::initialize_and_connect_to_test_sql().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(conn.clone()))
+            .configure(config_services),
+    )
+    .await;
+
+    conn.update_submissions(&[Submission {
+        id: 1,
+        contest_id: "contest1".to_string(),
+        problem_id: "problem1".to_string(),
+        ..Default::default()
+    }])
+    .await
+    .unwrap();
+    conn.record_orphans(0).await.unwrap();
+
+    let request = test::TestRequest::get()
+        .uri("/internal-api/integrity/orphans")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    let response: Value = test::read_body_json(response).await;
+    assert_eq!(response.as_array().unwrap().len(), 1);
+    assert_eq!(response[0]["contest_id"], json!("contest1"));
+    assert_eq!(response[0]["problem_id"], json!("problem1"));
+    assert_eq!(response[0]["problem_missing"], json!(true));
+    assert_eq!(response[0]["contest_missing"], json!(true));
+    assert_eq!(response[0]["contest_problem_missing"], json!(true));
+}
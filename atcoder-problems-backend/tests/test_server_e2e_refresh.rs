@@ -0,0 +1,97 @@
+use actix_web::{cookie::Cookie, http::StatusCode, test};
+use atcoder_problems_backend::server::middleware::github_auth::{
+    GithubAuthentication, GithubClient, GithubToken,
+};
+use serde_json::{json, Value};
+use sql_client::crawl_request::CrawlRequestClient;
+
+pub mod utils;
+
+#[actix_web::test]
+async fn test_refresh() {
+    let token = "access_token";
+    let mock_server = utils::start_mock_github_server(token);
+    let mock_server_base_url = mock_server.base_url();
+    let mock_api_server = utils::start_mock_github_api_server(token, GithubToken { id: 0 });
+    let mock_api_server_base_url = mock_api_server.base_url();
+
+    let pg_pool = utils::initialize_and_connect_to_test_sql().await;
+
+    let github =
+        GithubClient::new("", "", &mock_server_base_url, &mock_api_server_base_url).unwrap();
+
+    let app = test::init_service(
+        actix_web::App::new()
+            .wrap(GithubAuthentication::new(github.clone()))
+            .app_data(actix_web::web::Data::new(pg_pool.clone()))
+            .app_data(actix_web::web::Data::new(github))
+            .configure(atcoder_problems_backend::server::config_services),
+    )
+    .await;
+
+    let response = test::TestRequest::get()
+        .uri("/internal-api/authorize?code=a")
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+
+    let cookie = Cookie::new("token", token);
+    let response = test::TestRequest::post()
+        .uri("/internal-api/refresh")
+        .cookie(cookie.clone())
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = test::TestRequest::post()
+        .uri("/internal-api/user/update")
+        .cookie(cookie.clone())
+        .set_json(json!({"atcoder_user_id": "atcoder_user1"}))
+        .send_request(&app)
+        .await;
+    assert!(response.status().is_success());
+
+    let request = test::TestRequest::get()
+        .uri("/internal-api/refresh/status")
+        .cookie(cookie.clone())
+        .to_request();
+    let response: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(response, Value::Null);
+
+    let request = test::TestRequest::post()
+        .uri("/internal-api/refresh")
+        .cookie(cookie.clone())
+        .to_request();
+    let queued: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(queued["atcoder_user_id"], "atcoder_user1");
+    assert_eq!(queued["status"], "queued");
+
+    // A pending request is returned instead of enqueuing another one.
+    let request = test::TestRequest::post()
+        .uri("/internal-api/refresh")
+        .cookie(cookie.clone())
+        .to_request();
+    let response: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(response["id"], queued["id"]);
+
+    let popped = pg_pool.pop_crawl_request().await.unwrap().unwrap();
+    assert_eq!(json!(popped.id), queued["id"]);
+    pg_pool.finish_crawl_request(popped.id, None).await.unwrap();
+
+    let request = test::TestRequest::get()
+        .uri("/internal-api/refresh/status")
+        .cookie(cookie.clone())
+        .to_request();
+    let response: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(response["id"], queued["id"]);
+    assert_eq!(response["status"], "finished");
+    assert!(response["finished_epoch_second"].is_i64());
+
+    let response = test::TestRequest::post()
+        .uri("/internal-api/refresh")
+        .cookie(cookie.clone())
+        .send_request(&app)
+        .await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+}
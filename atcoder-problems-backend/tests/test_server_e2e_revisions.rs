@@ -0,0 +1,48 @@
+use actix_web::{test, web, App};
+use atcoder_problems_backend::server::config_services;
+use serde_json::{json, Value};
+use sql_client::models::ContestRevision;
+use sql_client::revision::RevisionClient;
+
+pub mod utils;
+
+#[actix_web::test]
+async fn test_contest_revisions() {
+    let conn = utils::initialize_and_connect_to_test_sql().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(conn.clone()))
+            .configure(config_services),
+    )
+    .await;
+
+    conn.insert_contest_revisions(&[ContestRevision {
+        contest_id: "contest1".to_string(),
+        field: "rate_change".to_string(),
+        old_value: Some(" ~ 1999".to_string()),
+        new_value: Some("-".to_string()),
+        detected_epoch_second: 100,
+        ..Default::default()
+    }])
+    .await
+    .unwrap();
+
+    let request = test::TestRequest::get()
+        .uri("/atcoder-api/v3/contest_revisions?contest_id=contest1")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    let response: Value = test::read_body_json(response).await;
+    assert_eq!(response.as_array().unwrap().len(), 1);
+    assert_eq!(response[0]["field"], json!("rate_change"));
+    assert_eq!(response[0]["old_value"], json!(" ~ 1999"));
+    assert_eq!(response[0]["new_value"], json!("-"));
+
+    let request = test::TestRequest::get()
+        .uri("/atcoder-api/v3/problem_revisions?problem_id=problem1")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    let response: Value = test::read_body_json(response).await;
+    assert_eq!(response, json!([]));
+}
@@ -18,18 +18,18 @@ async fn prepare_data_set(conn: &PgPool) {
     sql_client::query(
         r"
     INSERT INTO
-        submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length)
+        submissions (epoch_second, problem_id, contest_id, user_id, result, id, language, point, length, memory)
         VALUES
-            (0,  'p1',   'c1',   'u1',   'WA',   1,  'Rust',    0.0,    0),
-            (1,  'p1',   'c1',   'u1',   'RE',   2,  'Rust',    0.0,    0),
-            (2,  'p1',   'c1',   'u1',   'AC',   3,  'Rust',    0.0,    0),
-            (3,  'p1',   'c1',   'u1',   'AC',   4,  'Rust',    0.0,    0),
-            (100,'p1',   'c1',   'u1',   'AC',   5,  'Rust',    0.0,    0),
-            (4,  'p1',   'c1',   'u2',   'WA',   6,  'Rust',    0.0,    0),
-            (5,  'p1',   'c1',   'u2',   'RE',   7,  'Rust',    0.0,    0),
-            (6,  'p1',   'c1',   'u2',   'AC',   8,  'Rust',    0.0,    0),
-            (7,  'p1',   'c1',   'u2',   'AC',   9,  'Rust',    0.0,    0),
-            (200,'p1',   'c1',   'u2',   'AC',   10, 'Rust',    0.0,    0)",
+            (0,  'p1',   'c1',   'u1',   'WA',   1,  'Rust',    0.0,    0,  1024),
+            (1,  'p1',   'c1',   'u1',   'RE',   2,  'Rust',    0.0,    0,  1024),
+            (2,  'p1',   'c1',   'u1',   'AC',   3,  'Rust',    0.0,    0,  1024),
+            (3,  'p1',   'c1',   'u1',   'AC',   4,  'Rust',    0.0,    0,  2048),
+            (100,'p1',   'c1',   'u1',   'AC',   5,  'Rust',    0.0,    0,  4096),
+            (4,  'p1',   'c1',   'u2',   'WA',   6,  'Rust',    0.0,    0,  NULL),
+            (5,  'p1',   'c1',   'u2',   'RE',   7,  'Rust',    0.0,    0,  NULL),
+            (6,  'p1',   'c1',   'u2',   'AC',   8,  'Rust',    0.0,    0,  NULL),
+            (7,  'p1',   'c1',   'u2',   'AC',   9,  'Rust',    0.0,    0,  NULL),
+            (200,'p1',   'c1',   'u2',   'AC',   10, 'Rust',    0.0,    0,  NULL)",
     )
     .execute(conn)
     .await
@@ -55,6 +55,7 @@ async fn test_user_submissions() {
 
     assert_eq!(submissions.len(), 5);
     assert!(submissions.iter().all(|s| s.user_id == "u1"));
+    assert!(submissions.iter().all(|s| s.memory.is_some()));
 
     let response = test::TestRequest::get()
         .uri("/atcoder-api/results?user=u2")
@@ -84,6 +85,8 @@ async fn test_user_submissions_fromtime() {
 
     assert_eq!(submissions.len(), 2);
     assert!(submissions.iter().all(|s| s.user_id == "u1"));
+    assert_eq!(submissions[0].memory, Some(2048));
+    assert_eq!(submissions[1].memory, Some(4096));
 
     let request = test::TestRequest::get()
         .uri("/atcoder-api/v3/user/submissions?user=u2&from_second=6")
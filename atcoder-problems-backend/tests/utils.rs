@@ -1,10 +1,9 @@
 use atcoder_problems_backend::server::middleware::github_auth::GithubToken;
 use httpmock::MockServer;
 use serde_json::json;
+use sql_client::migration::MigrationClient;
 use sql_client::{initialize_pool, PgPool};
-use std::fs::read_to_string;
 
-const SQL_FILE: &str = "../config/database-definition.sql";
 const SQL_URL_ENV_KEY: &str = "SQL_URL";
 
 pub fn get_sql_url_from_env() -> String {
@@ -14,9 +13,10 @@ pub fn get_sql_url_from_env() -> String {
 pub async fn initialize_and_connect_to_test_sql() -> PgPool {
     let conn = initialize_pool(get_sql_url_from_env()).await.unwrap();
 
-    for query_str in read_to_string(SQL_FILE).unwrap().split(';') {
+    for query_str in ["DROP SCHEMA public CASCADE", "CREATE SCHEMA public"] {
         sql_client::query(query_str).execute(&conn).await.unwrap();
     }
+    conn.migrate_up().await.unwrap();
     conn
 }
 